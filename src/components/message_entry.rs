@@ -4,17 +4,41 @@ use glib::clone;
 use glib::subclass::Signal;
 use glib::WeakRef;
 use gtk::gdk;
+use gtk::gio;
 use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
+use locale_config::Locale;
 use once_cell::sync::Lazy;
+use tdlib::enums;
 use tdlib::enums::FormattedText as EnumFormattedText;
+use tdlib::enums::MessageSender;
+use tdlib::enums::TextEntityType;
 use tdlib::functions;
+use tdlib::types::BotCommand;
 use tdlib::types::FormattedText;
+use tdlib::types::TextEntity;
+use tdlib::types::TextEntityTypeMentionName;
 
+use crate::components::Avatar;
+use crate::config::APP_ID;
+use crate::strings;
 use crate::tdlib::BoxedFormattedText;
 use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::User;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+
+/// The maximum length of the keyword of the emoji suggestions, after the colon.
+const MAX_EMOJI_KEYWORD_LENGTH: usize = 32;
+const MAX_RECENT_EMOJIS: usize = 24;
+const MAX_MENTION_SUGGESTIONS: i32 = 20;
+const MAX_MENTION_QUERY_LENGTH: usize = 32;
+/// The prefix of the names of the text tags marking the mentions, followed by the id
+/// of the mentioned user.
+const MENTION_TAG_PREFIX: &str = "mention-";
 
 mod imp {
     use super::*;
@@ -24,14 +48,38 @@ mod imp {
     pub(crate) struct MessageEntry {
         pub(super) chat: WeakRef<Chat>,
         pub(super) formatted_text: RefCell<Option<BoxedFormattedText>>,
+        /// The emojis currently suggested for the `:keyword` before the cursor
+        pub(super) emoji_suggestions: RefCell<Vec<String>>,
+        pub(super) emoji_search_generation: RequestGeneration,
+        /// The members currently suggested for the `@query` before the cursor
+        pub(super) mention_suggestions: RefCell<Vec<User>>,
+        pub(super) mention_search_generation: RequestGeneration,
+        /// The commands of the bots of the chat
+        pub(super) bot_commands: RefCell<Vec<BotCommand>>,
+        /// The commands currently suggested for the `/query` at the start of the text
+        pub(super) command_suggestions: RefCell<Vec<BotCommand>>,
         #[template_child]
         pub(super) overlay: TemplateChild<gtk::Overlay>,
         #[template_child]
         pub(super) placeholder: TemplateChild<gtk::Inscription>,
         #[template_child]
+        pub(super) sticker_button: TemplateChild<gtk::Image>,
+        #[template_child]
         pub(super) emoji_button: TemplateChild<gtk::Image>,
         #[template_child]
         pub(super) text_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        pub(super) emoji_suggestions_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) emoji_suggestions_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) mention_suggestions_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) mention_suggestions_list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) command_suggestions_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) command_suggestions_list_box: TemplateChild<gtk::ListBox>,
     }
 
     #[glib::object_subclass]
@@ -58,6 +106,9 @@ mod imp {
                     Signal::builder("emoji-button-press")
                         .param_types([gtk::Image::static_type()])
                         .build(),
+                    Signal::builder("sticker-button-press")
+                        .param_types([gtk::Image::static_type()])
+                        .build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -110,11 +161,48 @@ mod imp {
             self.placeholder
                 .connect_text_notify(clone!(@weak obj => move |_| obj.notify("placeholder-text")));
 
+            self.emoji_suggestions_popover.set_parent(&*self.text_view);
+            self.mention_suggestions_popover
+                .set_parent(&*self.text_view);
+            self.command_suggestions_popover
+                .set_parent(&*self.text_view);
+
+            self.mention_suggestions_list_box.connect_row_activated(
+                clone!(@weak obj => move |_, row| {
+                    let user = obj
+                        .imp()
+                        .mention_suggestions
+                        .borrow()
+                        .get(row.index() as usize)
+                        .cloned();
+                    if let Some(user) = user {
+                        obj.pick_mention_suggestion(&user);
+                    }
+                }),
+            );
+            self.command_suggestions_list_box.connect_row_activated(
+                clone!(@weak obj => move |_, row| {
+                    let command = obj
+                        .imp()
+                        .command_suggestions
+                        .borrow()
+                        .get(row.index() as usize)
+                        .cloned();
+                    if let Some(command) = command {
+                        obj.pick_command_suggestion(&command);
+                    }
+                }),
+            );
+
             // Handle the enter key to emit the "activate" signal if neither the "ctrl" nor the
             // "shift" modifier are pressed at the same time.
             let key_events = gtk::EventControllerKey::new();
             key_events.connect_key_pressed(
                 clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, key, _, modifier| {
+                    if obj.handle_suggestions_key(key) {
+                        return glib::Propagation::Stop;
+                    }
+
                     if !modifier.contains(gdk::ModifierType::CONTROL_MASK)
                         && !modifier.contains(gdk::ModifierType::SHIFT_MASK)
                         && (key == gdk::Key::Return || key == gdk::Key::KP_Enter)
@@ -134,6 +222,12 @@ mod imp {
             }));
             self.emoji_button.add_controller(press);
 
+            let press = gtk::GestureClick::new();
+            press.connect_pressed(clone!(@weak obj => move |_, _, _, _| {
+                obj.emit_by_name::<()>("sticker-button-press", &[&*obj.imp().sticker_button]);
+            }));
+            self.sticker_button.add_controller(press);
+
             self.text_view
                 .buffer()
                 .connect_changed(clone!(@weak obj => move |_| {
@@ -147,6 +241,9 @@ mod imp {
         }
 
         fn dispose(&self) {
+            self.emoji_suggestions_popover.unparent();
+            self.mention_suggestions_popover.unparent();
+            self.command_suggestions_popover.unparent();
             self.overlay.unparent();
         }
     }
@@ -181,7 +278,7 @@ impl MessageEntry {
         } else {
             let formatted_text = FormattedText {
                 text,
-                entities: vec![],
+                entities: self.mention_entities(),
             };
             imp.formatted_text
                 .replace(Some(BoxedFormattedText(formatted_text)));
@@ -189,9 +286,516 @@ impl MessageEntry {
             imp.placeholder.set_visible(false);
         }
 
+        self.update_emoji_suggestions();
+        self.update_mention_suggestions();
+        self.update_command_suggestions();
         self.notify("formatted-text");
     }
 
+    /// Handles the keys to pick the first suggestion or to dismiss the suggestions,
+    /// returning whether the key has been handled.
+    fn handle_suggestions_key(&self, key: gdk::Key) -> bool {
+        let imp = self.imp();
+
+        let popover = [
+            &*imp.emoji_suggestions_popover,
+            &*imp.mention_suggestions_popover,
+            &*imp.command_suggestions_popover,
+        ]
+        .into_iter()
+        .find(|popover| popover.is_visible());
+        let Some(popover) = popover else {
+            return false;
+        };
+
+        match key {
+            gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::Tab => {
+                if popover == &*imp.emoji_suggestions_popover {
+                    let emoji = imp.emoji_suggestions.borrow().first().cloned();
+                    if let Some(emoji) = emoji {
+                        self.pick_emoji_suggestion(&emoji);
+                        return true;
+                    }
+                } else if popover == &*imp.mention_suggestions_popover {
+                    let user = imp.mention_suggestions.borrow().first().cloned();
+                    if let Some(user) = user {
+                        self.pick_mention_suggestion(&user);
+                        return true;
+                    }
+                } else {
+                    let command = imp.command_suggestions.borrow().first().cloned();
+                    if let Some(command) = command {
+                        self.pick_command_suggestion(&command);
+                        return true;
+                    }
+                }
+
+                false
+            }
+            gdk::Key::Escape => {
+                popover.popdown();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Shows a popover of suggestions, pointing to the cursor.
+    fn popup_at_cursor(&self, popover: &gtk::Popover) {
+        let text_view = &*self.imp().text_view;
+        let buffer = text_view.buffer();
+        let location = text_view.iter_location(&buffer.iter_at_mark(&buffer.get_insert()));
+        let (x, y) = text_view.buffer_to_window_coords(
+            gtk::TextWindowType::Widget,
+            location.x(),
+            location.y(),
+        );
+
+        popover.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 1, location.height())));
+        popover.popup();
+    }
+
+    /// Returns the `/query` of a bot command being typed at the start of the text, without
+    /// the slash, along with the iterators at its bounds.
+    fn command_query(&self) -> Option<(gtk::TextIter, gtk::TextIter, String)> {
+        let buffer = self.imp().text_view.buffer();
+        let start = buffer.start_iter();
+        let end = buffer.iter_at_mark(&buffer.get_insert());
+
+        let text = buffer.text(&start, &end, false);
+        let query = text.strip_prefix('/')?;
+        if query.contains(char::is_whitespace) {
+            return None;
+        }
+
+        Some((start, end, query.to_owned()))
+    }
+
+    /// Suggests the bot commands starting with the `/query` at the start of the text.
+    fn update_command_suggestions(&self) {
+        let imp = self.imp();
+
+        let commands = self
+            .command_query()
+            .map(|(_, _, query)| {
+                let query = query.to_lowercase();
+                imp.bot_commands
+                    .borrow()
+                    .iter()
+                    .filter(|c| c.command.to_lowercase().starts_with(&query))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.set_command_suggestions(commands);
+    }
+
+    fn set_command_suggestions(&self, commands: Vec<BotCommand>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.command_suggestions_list_box.first_child() {
+            imp.command_suggestions_list_box.remove(&child);
+        }
+
+        if commands.is_empty() {
+            imp.command_suggestions_popover.popdown();
+            imp.command_suggestions.replace(commands);
+            return;
+        }
+
+        for command in &commands {
+            let row = gtk::ListBoxRow::builder()
+                .child(&bot_command_row_content(command))
+                .focusable(false)
+                .build();
+            imp.command_suggestions_list_box.append(&row);
+        }
+        imp.command_suggestions.replace(commands);
+
+        self.popup_at_cursor(&imp.command_suggestions_popover);
+    }
+
+    /// Replaces the `/query` at the start of the text with the bot command.
+    fn pick_command_suggestion(&self, command: &BotCommand) {
+        if let Some((mut start, mut end, _)) = self.command_query() {
+            let buffer = self.imp().text_view.buffer();
+            buffer.begin_user_action();
+            buffer.delete(&mut start, &mut end);
+            buffer.insert(&mut start, &format!("/{} ", command.command));
+            buffer.end_user_action();
+        }
+
+        self.set_command_suggestions(vec![]);
+        self.imp().text_view.grab_focus();
+    }
+
+    /// Returns the username of the bot and the query, if the text is an `@bot query`
+    /// inline query.
+    pub(crate) fn inline_query(&self) -> Option<(String, String)> {
+        let formatted_text = self.formatted_text()?;
+        let (username, query) = formatted_text.0.text.strip_prefix('@')?.split_once(' ')?;
+
+        if username.is_empty() || !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+
+        Some((username.to_owned(), query.to_owned()))
+    }
+
+    /// Sets the bot commands that are suggested when typing a `/` at the start of the
+    /// text.
+    pub(crate) fn set_bot_commands(&self, commands: Vec<BotCommand>) {
+        self.imp().bot_commands.replace(commands);
+        self.update_command_suggestions();
+    }
+
+    /// Returns the entities of the mentions of the buffer, which are marked by text tags.
+    fn mention_entities(&self) -> Vec<TextEntity> {
+        let buffer = self.imp().text_view.buffer();
+        let mut entities = vec![];
+
+        let mut iter = buffer.start_iter();
+        loop {
+            for tag in iter.toggled_tags(true) {
+                if let Some(user_id) = mention_tag_user_id(&tag) {
+                    let mut end = iter.clone();
+                    end.forward_to_tag_toggle(Some(&tag));
+
+                    let offset = utf16_len(&buffer.text(&buffer.start_iter(), &iter, true));
+                    let length = utf16_len(&buffer.text(&iter, &end, true));
+                    entities.push(TextEntity {
+                        offset,
+                        length,
+                        r#type: TextEntityType::MentionName(TextEntityTypeMentionName { user_id }),
+                    });
+                }
+            }
+
+            if !iter.forward_to_tag_toggle(gtk::TextTag::NONE) {
+                break;
+            }
+        }
+
+        entities
+    }
+
+    /// Returns the text tag marking the mentions of the user, creating it if needed.
+    fn mention_tag(&self, user_id: i64) -> gtk::TextTag {
+        let table = self.imp().text_view.buffer().tag_table();
+        let name = format!("{MENTION_TAG_PREFIX}{user_id}");
+
+        table.lookup(&name).unwrap_or_else(|| {
+            let tag = gtk::TextTag::builder()
+                .name(name)
+                .weight(600)
+                .underline(gtk::pango::Underline::Single)
+                .build();
+            table.add(&tag);
+            tag
+        })
+    }
+
+    /// Returns the `@query` being typed before the cursor, without the at sign, along
+    /// with the iterators at its bounds.
+    fn mention_query(&self) -> Option<(gtk::TextIter, gtk::TextIter, String)> {
+        let buffer = self.imp().text_view.buffer();
+        let end = buffer.iter_at_mark(&buffer.get_insert());
+
+        let mut start = end.clone();
+        for _ in 0..=MAX_MENTION_QUERY_LENGTH {
+            if !start.backward_char() {
+                return None;
+            }
+
+            let c = start.char();
+            if c == '@' {
+                break;
+            } else if !c.is_alphanumeric() && c != '_' {
+                return None;
+            }
+        }
+        if start.char() != '@' {
+            return None;
+        }
+
+        // The at sign must be at the start of a word, so that emails aren't matched
+        let mut before_start = start.clone();
+        if before_start.backward_char() && !before_start.char().is_whitespace() {
+            return None;
+        }
+
+        let mut query_start = start.clone();
+        query_start.forward_char();
+        let query = buffer.text(&query_start, &end, false).to_string();
+
+        Some((start, end, query))
+    }
+
+    /// Suggests the members of the chat matching the `@query` before the cursor.
+    fn update_mention_suggestions(&self) {
+        let imp = self.imp();
+
+        let generation = imp.mention_search_generation.next();
+
+        let Some(chat) = self.chat().filter(can_mention_members) else {
+            self.set_mention_suggestions(vec![]);
+            return;
+        };
+        let Some((_, _, query)) = self.mention_query() else {
+            self.set_mention_suggestions(vec![]);
+            return;
+        };
+
+        let session = chat.session();
+        let client_id = session.client_id();
+        let chat_id = chat.id();
+
+        spawn(clone!(@weak self as obj, @weak session => async move {
+            let result = functions::search_chat_members(
+                chat_id,
+                query,
+                MAX_MENTION_SUGGESTIONS,
+                None,
+                client_id,
+            )
+            .await;
+
+            if !obj.imp().mention_search_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(enums::ChatMembers::ChatMembers(data)) => {
+                    let users = data
+                        .members
+                        .into_iter()
+                        .filter_map(|member| match member.member_id {
+                            MessageSender::User(data) => Some(session.user(data.user_id)),
+                            MessageSender::Chat(_) => None,
+                        })
+                        .collect();
+                    obj.set_mention_suggestions(users);
+                }
+                Err(e) => {
+                    log::warn!("Error searching chat members: {e:?}");
+                    obj.set_mention_suggestions(vec![]);
+                }
+            }
+        }));
+    }
+
+    fn set_mention_suggestions(&self, users: Vec<User>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.mention_suggestions_list_box.first_child() {
+            imp.mention_suggestions_list_box.remove(&child);
+        }
+
+        if users.is_empty() {
+            imp.mention_suggestions_popover.popdown();
+            imp.mention_suggestions.replace(users);
+            return;
+        }
+
+        for user in &users {
+            let avatar = Avatar::new();
+            avatar.set_size(32);
+            avatar.set_item(Some(user.clone().upcast()));
+
+            let name_label = gtk::Label::builder()
+                .label(strings::user_display_name(user, true))
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .xalign(0.0)
+                .build();
+
+            let labels = gtk::Box::new(gtk::Orientation::Vertical, 0);
+            labels.set_valign(gtk::Align::Center);
+            labels.append(&name_label);
+
+            let username = user.username();
+            if !username.is_empty() {
+                let username_label = gtk::Label::builder()
+                    .label(format!("@{username}"))
+                    .ellipsize(gtk::pango::EllipsizeMode::End)
+                    .xalign(0.0)
+                    .css_classes(["caption", "dim-label"])
+                    .build();
+                labels.append(&username_label);
+            }
+
+            let content = gtk::Box::new(gtk::Orientation::Horizontal, 9);
+            content.append(&avatar);
+            content.append(&labels);
+
+            let row = gtk::ListBoxRow::builder()
+                .child(&content)
+                .focusable(false)
+                .build();
+            imp.mention_suggestions_list_box.append(&row);
+        }
+        imp.mention_suggestions.replace(users);
+
+        self.popup_at_cursor(&imp.mention_suggestions_popover);
+    }
+
+    /// Replaces the `@query` before the cursor with a mention of the user, which is
+    /// sent as a `TextEntityType::MentionName` entity, so that users without a
+    /// username can be mentioned as well.
+    fn pick_mention_suggestion(&self, user: &User) {
+        if let Some((mut start, mut end, _)) = self.mention_query() {
+            let buffer = self.imp().text_view.buffer();
+            let tag = self.mention_tag(user.id());
+
+            buffer.begin_user_action();
+            buffer.delete(&mut start, &mut end);
+            buffer.insert_with_tags(
+                &mut start,
+                &strings::user_display_name(user, false),
+                &[&tag],
+            );
+            buffer.insert(&mut start, " ");
+            buffer.end_user_action();
+
+            // Applying tags doesn't emit the "changed" signal of the buffer
+            self.text_buffer_changed();
+        }
+
+        self.set_mention_suggestions(vec![]);
+        self.imp().text_view.grab_focus();
+    }
+
+    /// Returns the `:keyword` being typed before the cursor, without the colon, along
+    /// with the iterators at its bounds.
+    fn emoji_keyword(&self) -> Option<(gtk::TextIter, gtk::TextIter, String)> {
+        let buffer = self.imp().text_view.buffer();
+        let end = buffer.iter_at_mark(&buffer.get_insert());
+
+        let mut start = end.clone();
+        for _ in 0..=MAX_EMOJI_KEYWORD_LENGTH {
+            if !start.backward_char() {
+                return None;
+            }
+
+            let c = start.char();
+            if c == ':' {
+                break;
+            } else if !c.is_alphanumeric() && c != '_' {
+                return None;
+            }
+        }
+        if start.char() != ':' {
+            return None;
+        }
+
+        // The colon must be at the start of a word, so that times aren't matched
+        let mut before_start = start.clone();
+        if before_start.backward_char() && !before_start.char().is_whitespace() {
+            return None;
+        }
+
+        let mut keyword_start = start.clone();
+        keyword_start.forward_char();
+        let keyword = buffer.text(&keyword_start, &end, false).to_string();
+
+        Some((start, end, keyword))
+    }
+
+    /// Suggests the emojis matching the `:keyword` before the cursor, or the recent
+    /// emojis if only the colon has been typed.
+    fn update_emoji_suggestions(&self) {
+        let imp = self.imp();
+
+        let generation = imp.emoji_search_generation.next();
+
+        let Some((_, _, keyword)) = self.emoji_keyword() else {
+            self.set_emoji_suggestions(vec![]);
+            return;
+        };
+
+        if keyword.is_empty() {
+            self.set_emoji_suggestions(recent_emojis());
+            return;
+        }
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+        let client_id = chat.session().client_id();
+        let language_codes = Locale::current()
+            .tags_for("messages")
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>();
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = functions::search_emojis(keyword, false, language_codes, client_id).await;
+
+            if !obj.imp().emoji_search_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(enums::Emojis::Emojis(data)) => obj.set_emoji_suggestions(data.emojis),
+                Err(e) => {
+                    log::warn!("Error searching emojis: {e:?}");
+                    obj.set_emoji_suggestions(vec![]);
+                }
+            }
+        }));
+    }
+
+    fn set_emoji_suggestions(&self, emojis: Vec<String>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.emoji_suggestions_box.first_child() {
+            imp.emoji_suggestions_box.remove(&child);
+        }
+
+        if emojis.is_empty() {
+            imp.emoji_suggestions_popover.popdown();
+            imp.emoji_suggestions.replace(emojis);
+            return;
+        }
+
+        for emoji in &emojis {
+            let button = gtk::Button::builder()
+                .label(emoji)
+                .focus_on_click(false)
+                .css_classes(["flat"])
+                .build();
+            let emoji = emoji.clone();
+            button.connect_clicked(clone!(@weak self as obj => move |_| {
+                obj.pick_emoji_suggestion(&emoji);
+            }));
+            imp.emoji_suggestions_box.append(&button);
+        }
+        imp.emoji_suggestions.replace(emojis);
+
+        self.popup_at_cursor(&imp.emoji_suggestions_popover);
+    }
+
+    /// Replaces the `:keyword` before the cursor with the emoji.
+    fn pick_emoji_suggestion(&self, emoji: &str) {
+        if let Some((mut start, mut end, _)) = self.emoji_keyword() {
+            let buffer = self.imp().text_view.buffer();
+            buffer.begin_user_action();
+            buffer.delete(&mut start, &mut end);
+            buffer.insert(&mut start, emoji);
+            buffer.end_user_action();
+
+            add_recent_emoji(emoji);
+        }
+
+        self.set_emoji_suggestions(vec![]);
+    }
+
+    /// Inserts an emoji at the cursor position, as with `insert_at_cursor()`, and
+    /// adds it to the recent emojis.
+    pub(crate) fn insert_emoji(&self, emoji: &str) {
+        self.insert_at_cursor(emoji);
+        add_recent_emoji(emoji);
+    }
+
     /// Insert text inside the message entry at the cursor position,
     /// deleting eventual selected text
     pub(crate) fn insert_at_cursor(&self, text: &str) {
@@ -202,6 +806,12 @@ impl MessageEntry {
         buffer.end_user_action();
     }
 
+    /// Sets whether the button to pick a sticker is shown, which should only be the case
+    /// when stickers can be sent to the chat.
+    pub(crate) fn set_sticker_button_visible(&self, visible: bool) {
+        self.imp().sticker_button.set_visible(visible);
+    }
+
     pub(crate) fn formatted_text(&self) -> Option<BoxedFormattedText> {
         self.imp().formatted_text.borrow().clone()
     }
@@ -211,8 +821,33 @@ impl MessageEntry {
             return;
         }
 
-        let text = formatted_text.map(|f| f.0.text).unwrap_or_default();
-        self.imp().text_view.buffer().set_text(&text);
+        let buffer = self.imp().text_view.buffer();
+        let Some(formatted_text) = formatted_text else {
+            buffer.set_text("");
+            return;
+        };
+        buffer.set_text(&formatted_text.0.text);
+
+        // Restore the mentions, which can't be represented as markdown
+        let mut has_mentions = false;
+        for entity in &formatted_text.0.entities {
+            if let TextEntityType::MentionName(data) = &entity.r#type {
+                let start = utf16_to_char_offset(&formatted_text.0.text, entity.offset);
+                let end =
+                    utf16_to_char_offset(&formatted_text.0.text, entity.offset + entity.length);
+                buffer.apply_tag(
+                    &self.mention_tag(data.user_id),
+                    &buffer.iter_at_offset(start),
+                    &buffer.iter_at_offset(end),
+                );
+                has_mentions = true;
+            }
+        }
+
+        // Applying tags doesn't emit the "changed" signal of the buffer
+        if has_mentions {
+            self.text_buffer_changed();
+        }
     }
 
     pub(crate) async fn as_markdown(&self) -> Option<FormattedText> {
@@ -290,6 +925,19 @@ impl MessageEntry {
             None
         })
     }
+
+    pub(crate) fn connect_sticker_button_press<F: Fn(&Self, gtk::Image) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("sticker-button-press", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let button = values[1].get::<gtk::Image>().unwrap();
+            f(&obj, button);
+
+            None
+        })
+    }
 }
 
 impl Default for MessageEntry {
@@ -297,3 +945,90 @@ impl Default for MessageEntry {
         Self::new()
     }
 }
+
+/// Creates the content of a row showing a bot command along with its description.
+pub(crate) fn bot_command_row_content(command: &BotCommand) -> gtk::Widget {
+    let command_label = gtk::Label::builder()
+        .label(format!("/{}", command.command))
+        .xalign(0.0)
+        .css_classes(["heading"])
+        .build();
+
+    let description_label = gtk::Label::builder()
+        .label(&command.description)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .xalign(0.0)
+        .css_classes(["dim-label"])
+        .build();
+
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    content.append(&command_label);
+    content.append(&description_label);
+    content.upcast()
+}
+
+/// Returns whether the members of the chat can be mentioned, which is the case for
+/// the groups.
+fn can_mention_members(chat: &Chat) -> bool {
+    match chat.type_() {
+        ChatType::BasicGroup(_) => true,
+        ChatType::Supergroup(supergroup) => !supergroup.is_channel(),
+        _ => false,
+    }
+}
+
+fn mention_tag_user_id(tag: &gtk::TextTag) -> Option<i64> {
+    tag.name()?.strip_prefix(MENTION_TAG_PREFIX)?.parse().ok()
+}
+
+fn utf16_len(text: &str) -> i32 {
+    text.encode_utf16().count() as i32
+}
+
+/// Converts an offset in UTF-16 code units, as used by the entities, to an offset in
+/// characters, as used by the text buffer.
+fn utf16_to_char_offset(text: &str, offset: i32) -> i32 {
+    let mut utf16_offset = 0;
+    let mut char_offset = 0;
+
+    for c in text.chars() {
+        if utf16_offset >= offset {
+            break;
+        }
+        utf16_offset += c.len_utf16() as i32;
+        char_offset += 1;
+    }
+
+    char_offset
+}
+
+/// Returns the emojis recently inserted in the message entries, from the most recent one.
+/// They are only stored locally, as TDLib doesn't synchronize recent emojis across
+/// clients and recent reactions are a different list.
+fn recent_emojis() -> Vec<String> {
+    let settings = gio::Settings::new(APP_ID);
+    settings
+        .strv("recent-emojis")
+        .iter()
+        .map(|emoji| emoji.to_string())
+        .collect()
+}
+
+fn add_recent_emoji(emoji: &str) {
+    let mut emojis = recent_emojis();
+    emojis.retain(|e| e != emoji);
+    emojis.insert(0, emoji.to_owned());
+    emojis.truncate(MAX_RECENT_EMOJIS);
+
+    let settings = gio::Settings::new(APP_ID);
+    if let Err(e) = settings.set_strv(
+        "recent-emojis",
+        emojis
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice(),
+    ) {
+        log::warn!("Failed to save the recent emojis: {e:?}");
+    }
+}
@@ -4,6 +4,7 @@ mod snow;
 mod sticker;
 
 pub(crate) use self::avatar::Avatar;
+pub(crate) use self::message_entry::bot_command_row_content;
 pub(crate) use self::message_entry::MessageEntry;
 pub(crate) use self::snow::Snow;
 pub(crate) use self::sticker::Sticker;
@@ -97,7 +97,17 @@ glib::wrapper! {
         @extends gtk::Widget;
 }
 
+impl Default for Sticker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Sticker {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
     pub(crate) fn update_sticker(&self, sticker: TdSticker, looped: bool, session: Session) {
         let imp = self.imp();
 
@@ -0,0 +1,245 @@
+mod preview;
+
+use std::cell::OnceCell;
+
+use glib::clone;
+use glib::subclass::Signal;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums;
+use tdlib::functions;
+use tdlib::types::Animation;
+
+pub(crate) use self::preview::Preview;
+use crate::tdlib::BoxedAnimation;
+use crate::tdlib::Chat;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+
+const PREVIEW_WIDTH: i32 = 112;
+const PREVIEW_HEIGHT: i32 = 84;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentAnimationPicker {
+        layout-manager: BinLayout {};
+
+        Stack stack {
+            StackPage {
+                name: "animations";
+                child: ScrolledWindow {
+                    hscrollbar-policy: never;
+
+                    child: GridView grid_view {
+                        max-columns: 3;
+                        single-click-activate: true;
+                        activate => $on_grid_view_activated() swapped;
+                    };
+                };
+            }
+
+            StackPage {
+                name: "loading";
+                child: Spinner {
+                    spinning: true;
+                    halign: center;
+                    valign: center;
+                };
+            }
+
+            StackPage {
+                name: "empty";
+                child: Label {
+                    label: _("No saved GIFs");
+                    wrap: true;
+                    justify: center;
+
+                    styles ["dim-label"]
+                };
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct AnimationPicker {
+        pub(super) chat: glib::WeakRef<Chat>,
+        pub(super) model: OnceCell<gio::ListStore>,
+        pub(super) request_generation: RequestGeneration,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) grid_view: TemplateChild<gtk::GridView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AnimationPicker {
+        const NAME: &'static str = "ContentAnimationPicker";
+        type Type = super::AnimationPicker;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AnimationPicker {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("animation-picked")
+                    .param_types([BoxedAnimation::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_grid_view();
+        }
+
+        fn dispose(&self) {
+            self.stack.unparent();
+        }
+    }
+
+    impl WidgetImpl for AnimationPicker {}
+}
+
+glib::wrapper! {
+    pub(crate) struct AnimationPicker(ObjectSubclass<imp::AnimationPicker>)
+        @extends gtk::Widget;
+}
+
+impl Default for AnimationPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[gtk::template_callbacks]
+impl AnimationPicker {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn setup_grid_view(&self) {
+        let imp = self.imp();
+
+        let model = gio::ListStore::new::<glib::BoxedAnyObject>();
+        imp.grid_view
+            .set_model(Some(&gtk::NoSelection::new(Some(model.clone()))));
+        imp.model.set(model).unwrap();
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+
+            let preview = Preview::new();
+            preview.set_size_request(PREVIEW_WIDTH, PREVIEW_HEIGHT);
+
+            list_item.set_child(Some(&preview));
+        });
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let item = list_item.item().and_downcast::<glib::BoxedAnyObject>().unwrap();
+            let preview = list_item.child().and_downcast::<Preview>().unwrap();
+
+            if let Some(chat) = obj.imp().chat.upgrade() {
+                preview.set_animation(item.borrow::<Animation>().clone(), &chat.session());
+            }
+        }));
+        // Stop the videos of the previews that are not visible anymore
+        factory.connect_unbind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let preview = list_item.child().and_downcast::<Preview>().unwrap();
+            preview.clear();
+        });
+        imp.grid_view.set_factory(Some(&factory));
+    }
+
+    #[template_callback]
+    fn on_grid_view_activated(&self, position: u32) {
+        let Some(item) = self
+            .imp()
+            .model
+            .get()
+            .unwrap()
+            .item(position)
+            .and_downcast::<glib::BoxedAnyObject>()
+        else {
+            return;
+        };
+
+        let animation = item.borrow::<Animation>().clone();
+        self.emit_by_name::<()>("animation-picked", &[&BoxedAnimation(animation)]);
+    }
+
+    /// Loads the saved animations, which can be sent to the specified chat.
+    pub(crate) fn load_saved_animations(&self, chat: &Chat) {
+        let imp = self.imp();
+
+        imp.chat.set(Some(chat));
+
+        let generation = imp.request_generation.next();
+        imp.stack.set_visible_child_name("loading");
+
+        let client_id = chat.session().client_id();
+        spawn(clone!(@weak self as obj => async move {
+            let result = functions::get_saved_animations(client_id).await;
+
+            if !obj.imp().request_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(enums::Animations::Animations(data)) => obj.set_animations(data.animations),
+                Err(e) => {
+                    log::warn!("Error loading the saved animations: {e:?}");
+                    obj.set_animations(vec![]);
+                }
+            }
+        }));
+    }
+
+    fn set_animations(&self, animations: Vec<Animation>) {
+        let imp = self.imp();
+
+        let items = animations
+            .into_iter()
+            .map(glib::BoxedAnyObject::new)
+            .collect::<Vec<_>>();
+
+        let model = imp.model.get().unwrap();
+        model.splice(0, model.n_items(), &items);
+
+        imp.stack.set_visible_child_name(if items.is_empty() {
+            "empty"
+        } else {
+            "animations"
+        });
+    }
+
+    pub(crate) fn connect_animation_picked<F: Fn(&Self, Animation) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("animation-picked", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let animation = values[1].get::<BoxedAnimation>().unwrap();
+            f(&obj, animation.0);
+
+            None
+        })
+    }
+}
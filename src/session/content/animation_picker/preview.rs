@@ -0,0 +1,153 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use tdlib::types::Animation;
+
+use crate::utils::decode_minithumbnail;
+use crate::utils::spawn;
+use crate::Session;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct Preview {
+        pub(super) file_id: Cell<i32>,
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
+        pub(super) picture: gtk::Picture,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Preview {
+        const NAME: &'static str = "ContentAnimationPickerPreview";
+        type Type = super::Preview;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+            klass.set_css_name("animationpreview");
+        }
+    }
+
+    impl ObjectImpl for Preview {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.set_overflow(gtk::Overflow::Hidden);
+
+            self.picture.set_content_fit(gtk::ContentFit::Cover);
+            self.picture.set_parent(&*obj);
+        }
+
+        fn dispose(&self) {
+            if let Some(media) = self.media.take() {
+                media.pause();
+            }
+            self.picture.unparent();
+        }
+    }
+
+    impl WidgetImpl for Preview {
+        fn map(&self) {
+            self.parent_map();
+
+            if let Some(media) = &*self.media.borrow() {
+                media.play();
+            }
+        }
+
+        fn unmap(&self) {
+            if let Some(media) = &*self.media.borrow() {
+                media.pause();
+            }
+
+            self.parent_unmap();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct Preview(ObjectSubclass<imp::Preview>)
+        @extends gtk::Widget;
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preview {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Shows a looping and muted preview of the animation.
+    pub(crate) fn set_animation(&self, animation: Animation, session: &Session) {
+        let imp = self.imp();
+
+        if let Some(media) = imp.media.take() {
+            media.pause();
+        }
+
+        let file_id = animation.animation.id;
+        imp.file_id.set(file_id);
+
+        imp.picture.set_paintable(
+            animation
+                .minithumbnail
+                .and_then(|m| decode_minithumbnail(&m))
+                .as_ref(),
+        );
+
+        if animation.animation.local.is_downloading_completed {
+            self.load_animation(&animation.animation.local.path);
+        } else {
+            spawn(clone!(@weak self as obj, @weak session => async move {
+                match session.download_file(file_id).await {
+                    Ok(file) => {
+                        // The preview could have been reused for another animation
+                        if obj.imp().file_id.get() == file_id {
+                            obj.load_animation(&file.local.path);
+                        }
+                    }
+                    Err(e) => log::warn!("Error downloading an animation: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    /// Stops the preview and releases its resources.
+    pub(crate) fn clear(&self) {
+        let imp = self.imp();
+
+        if let Some(media) = imp.media.take() {
+            media.pause();
+        }
+
+        imp.file_id.set(0);
+        imp.picture.set_paintable(gdk::Paintable::NONE);
+    }
+
+    fn load_animation(&self, path: &str) {
+        let imp = self.imp();
+
+        let media = gtk::MediaFile::for_filename(path);
+        media.set_muted(true);
+        media.set_loop(true);
+
+        imp.picture.set_paintable(Some(&media));
+
+        if self.is_mapped() {
+            media.play();
+        }
+
+        imp.media.replace(Some(media));
+    }
+}
@@ -1,29 +1,43 @@
 use std::cell::Cell;
 use std::cell::OnceCell;
 use std::cell::RefCell;
+use std::time::Duration;
 
+use adw::prelude::*;
 use anyhow::anyhow;
 use gettextrs::gettext;
 use glib::clone;
 use gtk::gio;
 use gtk::glib;
-use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use tdlib::enums::ChatAction;
 use tdlib::enums::ChatMemberStatus;
 use tdlib::enums::FormattedText;
+use tdlib::enums::InputFile;
 use tdlib::enums::InputMessageContent;
+use tdlib::enums::KeyboardButtonType;
 use tdlib::enums::MessageContent;
 use tdlib::enums::MessageSender as TdMessageSender;
+use tdlib::enums::ReplyMarkup;
+use tdlib::enums::StickerFormat;
+use tdlib::enums::StickerType;
+use tdlib::enums::Stickers;
 use tdlib::enums::UserType;
+use tdlib::enums::WebPage;
 use tdlib::functions;
 use tdlib::types;
 
+use crate::components::bot_command_row_content;
 use crate::components::MessageEntry;
+use crate::components::Sticker;
 use crate::expressions;
+use crate::i18n::gettext_f;
+use crate::session::content::CreatePollWindow;
+use crate::session::content::InlineBotResults;
 use crate::session::content::SendMediaWindow;
+use crate::session::content::StickerPicker;
 use crate::strings;
 use crate::tdlib::BasicGroup;
 use crate::tdlib::BoxedDraftMessage;
@@ -33,10 +47,17 @@ use crate::tdlib::ChatType;
 use crate::tdlib::SecretChatState;
 use crate::tdlib::Supergroup;
 use crate::utils::block_on;
+use crate::utils::is_single_emoji;
 use crate::utils::spawn;
 use crate::utils::temp_dir;
+use crate::utils::RequestGeneration;
 
 const PHOTO_MIME_TYPES: &[&str] = &["image/png", "image/jpeg"];
+const STICKER_SUGGESTION_SIZE: i32 = 64;
+const MAX_STICKER_SUGGESTIONS: i32 = 20;
+/// The time to wait after the last change of the message entry before looking for a
+/// link to preview.
+const WEB_PAGE_PREVIEW_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 enum ChatActionBarState {
@@ -56,10 +77,22 @@ mod imp {
         pub(super) chat_action_in_cooldown: Cell<bool>,
         pub(super) state: Cell<ChatActionBarState>,
         pub(super) emoji_chooser: RefCell<Option<gtk::EmojiChooser>>,
+        pub(super) sticker_picker: RefCell<Option<StickerPicker>>,
         pub(super) chat_signal_group: OnceCell<glib::SignalGroup>,
         pub(super) basic_group_signal_group: OnceCell<glib::SignalGroup>,
         pub(super) supergroup_signal_group: OnceCell<glib::SignalGroup>,
         pub(super) bindings: RefCell<Vec<gtk::ExpressionWatch>>,
+        pub(super) sticker_suggestions_generation: RequestGeneration,
+        /// The commands of the bots of the chat, as they should be sent
+        pub(super) bot_commands: RefCell<Vec<types::BotCommand>>,
+        pub(super) bot_commands_generation: RequestGeneration,
+        pub(super) reply_markup_generation: RequestGeneration,
+        /// Whether the keyboard of the bot should be hidden after a button is pressed
+        pub(super) is_keyboard_one_time: Cell<bool>,
+        /// Whether the preview of the link in the message entry has been disabled
+        pub(super) disable_web_page_preview: Cell<bool>,
+        pub(super) web_page_preview_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) web_page_preview_generation: RequestGeneration,
         #[template_child]
         pub(super) top_bar_revealer: TemplateChild<gtk::Revealer>,
         #[template_child]
@@ -69,17 +102,41 @@ mod imp {
         #[template_child]
         pub(super) top_bar_message_label: TemplateChild<gtk::Inscription>,
         #[template_child]
+        pub(super) web_page_preview_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) web_page_preview_title_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub(super) web_page_preview_description_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
         pub(super) message_entry: TemplateChild<MessageEntry>,
         #[template_child]
+        pub(super) keyboard_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
         pub(super) send_message_button: TemplateChild<gtk::Button>,
         #[template_child]
-        pub(super) select_file_button: TemplateChild<gtk::Button>,
+        pub(super) bot_commands_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub(super) bot_commands_list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) attach_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub(super) restriction_label: TemplateChild<gtk::Label>,
         #[template_child]
         pub(super) mute_button: TemplateChild<gtk::Button>,
         #[template_child]
+        pub(super) inline_bot_results_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) inline_bot_results: TemplateChild<InlineBotResults>,
+        #[template_child]
+        pub(super) sticker_suggestions_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) sticker_suggestions_box: TemplateChild<gtk::Box>,
+        #[template_child]
         pub(super) action_bar_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) keyboard_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) keyboard_box: TemplateChild<gtk::Box>,
     }
 
     #[glib::object_subclass]
@@ -99,6 +156,13 @@ mod imp {
                     widget.cancel_action();
                 },
             );
+            klass.install_action(
+                "chat-action-bar.disable-web-page-preview",
+                None,
+                move |widget, _, _| {
+                    widget.disable_web_page_preview();
+                },
+            );
             klass.install_action_async(
                 "chat-action-bar.select-file",
                 None,
@@ -106,6 +170,9 @@ mod imp {
                     widget.select_file().await;
                 },
             );
+            klass.install_action("chat-action-bar.create-poll", None, move |widget, _, _| {
+                widget.create_poll();
+            });
             klass.install_action_async(
                 "chat-action-bar.send-message",
                 None,
@@ -211,6 +278,10 @@ mod imp {
                         .unwrap_or_default();
                     obj.action_set_enabled("chat-action-bar.send-message", should_enable);
 
+                    obj.update_sticker_suggestions();
+                    obj.update_inline_bot_results();
+                    obj.update_web_page_preview();
+
                     // Send typing action
                     spawn(clone!(@weak obj => async move {
                         obj.send_chat_action(ChatAction::Typing).await;
@@ -228,6 +299,32 @@ mod imp {
                     obj.show_emoji_chooser(&button);
                 }));
 
+            self.message_entry
+                .connect_sticker_button_press(clone!(@weak obj => move |_, button| {
+                    obj.show_sticker_picker(&button);
+                }));
+
+            self.inline_bot_results.connect_result_picked(
+                clone!(@weak obj => move |_, query_id, result_id| {
+                    spawn(clone!(@weak obj => async move {
+                        obj.send_inline_query_result(query_id, result_id).await;
+                    }));
+                }),
+            );
+
+            self.bot_commands_list_box
+                .connect_row_activated(clone!(@weak obj => move |_, row| {
+                    let command = obj
+                        .imp()
+                        .bot_commands
+                        .borrow()
+                        .get(row.index() as usize)
+                        .cloned();
+                    if let Some(command) = command {
+                        obj.send_bot_command(&command);
+                    }
+                }));
+
             // The message entry is always empty at this point, so disable the
             // send-message action
             obj.action_set_enabled("chat-action-bar.send-message", false);
@@ -242,10 +339,20 @@ mod imp {
 
         fn dispose(&self) {
             self.top_bar_revealer.unparent();
+            if let Some(source_id) = self.web_page_preview_source_id.take() {
+                source_id.remove();
+            }
+            self.web_page_preview_revealer.unparent();
+            self.inline_bot_results_revealer.unparent();
+            self.sticker_suggestions_revealer.unparent();
             self.action_bar_stack.unparent();
+            self.keyboard_revealer.unparent();
             if let Some(emoji_chooser) = self.emoji_chooser.take() {
                 emoji_chooser.unparent();
             }
+            if let Some(sticker_picker) = self.sticker_picker.take() {
+                sticker_picker.unparent();
+            }
         }
     }
 
@@ -278,6 +385,12 @@ impl ChatActionBar {
                 obj.update_stack_page();
             }),
         );
+        chat_signal_group.connect_notify_local(
+            Some("reply-markup-message-id"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.load_reply_markup();
+            }),
+        );
         chat_signal_group.connect_notify_local(
             Some("is-blocked"),
             clone!(@weak self as obj => move |_, _| {
@@ -443,6 +556,7 @@ impl ChatActionBar {
 
     fn reset(&self) {
         self.set_state(ChatActionBarState::Composing);
+        self.imp().disable_web_page_preview.set(false);
         self.imp().message_entry.set_formatted_text(None);
     }
 
@@ -450,7 +564,7 @@ impl ChatActionBar {
         if let Some(formatted_text) = self.imp().message_entry.as_markdown().await {
             let content = types::InputMessageText {
                 text: formatted_text,
-                disable_web_page_preview: false,
+                disable_web_page_preview: self.imp().disable_web_page_preview.get(),
                 clear_draft: true,
             };
 
@@ -467,7 +581,7 @@ impl ChatActionBar {
             let chooser = gtk::EmojiChooser::new();
             chooser.set_parent(parent);
             chooser.connect_emoji_picked(clone!(@weak self as obj => move |_, emoji| {
-                obj.imp().message_entry.insert_at_cursor(emoji);
+                obj.imp().message_entry.insert_emoji(emoji);
             }));
             chooser.connect_hide(clone!(@weak self as obj => move |_| {
                 obj.imp().message_entry.grab_focus();
@@ -477,6 +591,34 @@ impl ChatActionBar {
         emoji_chooser.as_ref().unwrap().popup();
     }
 
+    fn show_sticker_picker(&self, parent: &impl IsA<gtk::Widget>) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let imp = self.imp();
+        let mut sticker_picker = imp.sticker_picker.borrow_mut();
+        if sticker_picker.is_none() {
+            let picker = StickerPicker::new();
+            picker.set_parent(parent);
+            picker.connect_sticker_picked(clone!(@weak self as obj => move |_, sticker| {
+                spawn(clone!(@weak obj => async move {
+                    obj.send_sticker(sticker).await;
+                }));
+            }));
+            picker.connect_animation_picked(clone!(@weak self as obj => move |_, animation| {
+                spawn(clone!(@weak obj => async move {
+                    obj.send_animation(animation).await;
+                }));
+            }));
+            picker.connect_hide(clone!(@weak self as obj => move |_| {
+                obj.imp().message_entry.grab_focus();
+            }));
+            *sticker_picker = Some(picker);
+        }
+        sticker_picker.as_ref().unwrap().popup_for_chat(&chat);
+    }
+
     async fn select_file(&self) {
         let dialog = gtk::FileDialog::new();
         let filter = gtk::FileFilter::new();
@@ -499,6 +641,13 @@ impl ChatActionBar {
         }
     }
 
+    fn create_poll(&self) {
+        if let Some(chat) = self.chat() {
+            let parent = self.root().and_downcast::<gtk::Window>().unwrap();
+            CreatePollWindow::new(&parent, &chat).present();
+        }
+    }
+
     async fn edit_message(&self) {
         if let Some(chat) = self.chat() {
             if let ChatActionBarState::Editing(message_id) = self.imp().state.get() {
@@ -549,6 +698,280 @@ impl ChatActionBar {
         }
     }
 
+    async fn send_sticker(&self, sticker: types::Sticker) {
+        let content = InputMessageContent::InputMessageSticker(types::InputMessageSticker {
+            sticker: InputFile::Id(types::InputFileId {
+                id: sticker.sticker.id,
+            }),
+            thumbnail: None,
+            width: sticker.width,
+            height: sticker.height,
+            emoji: sticker.emoji,
+        });
+
+        self.send_message_content(content).await;
+    }
+
+    async fn send_animation(&self, animation: types::Animation) {
+        let content = InputMessageContent::InputMessageAnimation(types::InputMessageAnimation {
+            animation: InputFile::Id(types::InputFileId {
+                id: animation.animation.id,
+            }),
+            thumbnail: None,
+            added_sticker_file_ids: vec![],
+            duration: animation.duration,
+            width: animation.width,
+            height: animation.height,
+            caption: None,
+            has_spoiler: false,
+        });
+
+        self.send_message_content(content).await;
+    }
+
+    /// Suggests the stickers matching the emoji of the message entry, if that is
+    /// all the message entry contains.
+    fn update_sticker_suggestions(&self) {
+        let imp = self.imp();
+
+        let generation = imp.sticker_suggestions_generation.next();
+
+        let Some(chat) = self.chat() else {
+            imp.sticker_suggestions_revealer.set_reveal_child(false);
+            return;
+        };
+
+        let emoji = imp
+            .message_entry
+            .formatted_text()
+            .map(|f| f.0.text.trim().to_owned())
+            .filter(|text| is_single_emoji(text));
+        let Some(emoji) = emoji.filter(|_| chat.can_send_other_messages()) else {
+            imp.sticker_suggestions_revealer.set_reveal_child(false);
+            return;
+        };
+
+        let client_id = chat.session().client_id();
+        let chat_id = chat.id();
+        spawn(clone!(@weak self as obj => async move {
+            let result = functions::get_stickers(
+                StickerType::Regular,
+                emoji,
+                MAX_STICKER_SUGGESTIONS,
+                chat_id,
+                client_id,
+            )
+            .await;
+
+            if !obj.imp().sticker_suggestions_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(Stickers::Stickers(data)) => {
+                    obj.set_sticker_suggestions(data.stickers)
+                }
+                Err(e) => {
+                    log::warn!("Error searching stickers by emoji: {e:?}");
+                    obj.set_sticker_suggestions(vec![]);
+                }
+            }
+        }));
+    }
+
+    fn set_sticker_suggestions(&self, stickers: Vec<types::Sticker>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.sticker_suggestions_box.first_child() {
+            imp.sticker_suggestions_box.remove(&child);
+        }
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        // Video stickers are not supported yet
+        let stickers = stickers
+            .into_iter()
+            .filter(|s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs))
+            .collect::<Vec<_>>();
+
+        imp.sticker_suggestions_revealer
+            .set_reveal_child(!stickers.is_empty());
+
+        for sticker in stickers {
+            let widget = Sticker::new();
+            widget.set_longer_side_size(STICKER_SUGGESTION_SIZE);
+            widget.update_sticker(sticker.clone(), false, chat.session());
+
+            let button = gtk::Button::builder()
+                .child(&widget)
+                .css_classes(["flat"])
+                .build();
+            button.connect_clicked(clone!(@weak self as obj => move |_| {
+                let sticker = sticker.clone();
+                spawn(clone!(@weak obj => async move {
+                    obj.send_sticker(sticker).await;
+                    obj.reset();
+                }));
+            }));
+
+            imp.sticker_suggestions_box.append(&button);
+        }
+    }
+
+    /// Shows a preview of the first link of the message entry, once the user stops typing.
+    fn update_web_page_preview(&self) {
+        let imp = self.imp();
+
+        let generation = imp.web_page_preview_generation.next();
+
+        if let Some(source_id) = imp.web_page_preview_source_id.take() {
+            source_id.remove();
+        }
+
+        // Texts without any dot can't contain links, so don't bother asking TDLib
+        let has_link = imp
+            .message_entry
+            .formatted_text()
+            .is_some_and(|f| f.0.text.contains('.'));
+        if !has_link || imp.disable_web_page_preview.get() || self.chat().is_none() {
+            imp.web_page_preview_revealer.set_reveal_child(false);
+            return;
+        }
+
+        let source_id = glib::timeout_add_local_once(
+            WEB_PAGE_PREVIEW_DELAY,
+            clone!(@weak self as obj => move || {
+                obj.imp().web_page_preview_source_id.replace(None);
+
+                spawn(clone!(@weak obj => async move {
+                    obj.load_web_page_preview(generation).await;
+                }));
+            }),
+        );
+        imp.web_page_preview_source_id.replace(Some(source_id));
+    }
+
+    async fn load_web_page_preview(&self, generation: u32) {
+        let imp = self.imp();
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+        let Some(formatted_text) = imp.message_entry.as_markdown().await else {
+            return;
+        };
+
+        let result =
+            functions::get_web_page_preview(formatted_text, chat.session().client_id()).await;
+
+        if !imp.web_page_preview_generation.is_current(generation) {
+            return;
+        }
+
+        // TDLib answers with an error if the text doesn't contain any link to preview
+        let Ok(WebPage::WebPage(web_page)) = result else {
+            imp.web_page_preview_revealer.set_reveal_child(false);
+            return;
+        };
+
+        let title = if web_page.site_name.is_empty() {
+            &web_page.display_url
+        } else {
+            &web_page.site_name
+        };
+        let description = if web_page.title.is_empty() {
+            &web_page.description.text
+        } else {
+            &web_page.title
+        };
+
+        imp.web_page_preview_title_label.set_text(Some(title));
+        imp.web_page_preview_description_label
+            .set_text(Some(description));
+        imp.web_page_preview_revealer.set_reveal_child(true);
+    }
+
+    /// Disables the preview of the links of the message being composed.
+    fn disable_web_page_preview(&self) {
+        let imp = self.imp();
+
+        imp.disable_web_page_preview.set(true);
+        self.update_web_page_preview();
+    }
+
+    /// Shows the results of the inline bot mentioned at the start of the message entry,
+    /// if any.
+    fn update_inline_bot_results(&self) {
+        let imp = self.imp();
+
+        let chat = self.chat().filter(|chat| chat.can_send_other_messages());
+        match (chat, imp.message_entry.inline_query()) {
+            (Some(chat), Some((bot_username, query))) => {
+                imp.inline_bot_results.search(&chat, bot_username, query)
+            }
+            _ => imp.inline_bot_results.clear(),
+        }
+    }
+
+    async fn send_inline_query_result(&self, query_id: i64, result_id: String) {
+        if let Some(chat) = self.chat() {
+            let client_id = chat.session().client_id();
+            let chat_id = chat.id();
+            let reply_to_message_id =
+                if let ChatActionBarState::Replying(id) = self.imp().state.get() {
+                    id
+                } else {
+                    0
+                };
+
+            let result = functions::send_inline_query_result_message(
+                chat_id,
+                0,
+                reply_to_message_id,
+                None,
+                query_id,
+                result_id,
+                false,
+                client_id,
+            )
+            .await;
+
+            match result {
+                Ok(_) => self.reset(),
+                Err(e) => log::warn!("Error sending an inline query result: {e:?}"),
+            }
+        }
+    }
+
+    /// Sends a message picked from a panel or a menu, like a sticker or a bot command, as a
+    /// reply if we were replying to a message.
+    async fn send_message_content(&self, content: InputMessageContent) {
+        if let Some(chat) = self.chat() {
+            let client_id = chat.session().client_id();
+            let chat_id = chat.id();
+            let reply_to_message_id =
+                if let ChatActionBarState::Replying(id) = self.imp().state.get() {
+                    id
+                } else {
+                    0
+                };
+
+            let result =
+                functions::send_message(chat_id, 0, reply_to_message_id, None, content, client_id)
+                    .await;
+            if let Err(e) = result {
+                log::warn!("Error sending a message: {:?}", e);
+            }
+
+            // Unlike text messages, the composed text is left in the message entry
+            if reply_to_message_id != 0 {
+                self.set_state(ChatActionBarState::Composing);
+            }
+        }
+    }
+
     fn is_chat_muted(&self) -> bool {
         let chat = self.chat().unwrap();
         let notifications = chat.notification_settings().0;
@@ -634,6 +1057,8 @@ impl ChatActionBar {
         }
 
         if let InputMessageContent::InputMessageText(content) = message.0.input_message_text {
+            imp.disable_web_page_preview
+                .set(content.disable_web_page_preview);
             imp.message_entry
                 .set_formatted_text(Some(BoxedFormattedText(content.text)));
         } else {
@@ -751,10 +1176,308 @@ impl ChatActionBar {
 
         self.update_stack_page();
         self.update_signal_groups();
+        self.load_bot_commands();
+        self.load_reply_markup();
+        self.update_inline_bot_results();
 
         self.notify("chat");
     }
 
+    /// Starts an inline query to the bot in the message entry, as asked by a button.
+    pub(crate) fn start_inline_query(&self, bot_username: &str, query: &str) {
+        let imp = self.imp();
+
+        let text = types::FormattedText {
+            text: format!("@{bot_username} {query}"),
+            entities: vec![],
+        };
+        imp.message_entry
+            .set_formatted_text(Some(BoxedFormattedText(text)));
+        imp.message_entry.grab_focus();
+    }
+
+    /// Loads the reply markup that the bots of the chat want to be used, which can be a
+    /// custom keyboard or a request to reply to a message.
+    fn load_reply_markup(&self) {
+        let imp = self.imp();
+
+        let generation = imp.reply_markup_generation.next();
+
+        let Some(chat) = self.chat() else {
+            self.set_reply_markup(0, None);
+            return;
+        };
+
+        let message_id = chat.reply_markup_message_id();
+        if message_id == 0 {
+            self.set_reply_markup(0, None);
+            return;
+        }
+
+        spawn(clone!(@weak self as obj, @weak chat => async move {
+            let result = chat.fetch_message(message_id).await;
+
+            if !obj.imp().reply_markup_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(message) => {
+                    obj.set_reply_markup(message_id, message.reply_markup().map(|r| r.0))
+                }
+                Err(e) => {
+                    log::warn!("Error fetching the message with the reply markup: {e:?}");
+                    obj.set_reply_markup(0, None);
+                }
+            }
+        }));
+    }
+
+    fn set_reply_markup(&self, message_id: i64, reply_markup: Option<ReplyMarkup>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.keyboard_box.first_child() {
+            imp.keyboard_box.remove(&child);
+        }
+
+        let mut placeholder = String::new();
+
+        match reply_markup {
+            Some(ReplyMarkup::ShowKeyboard(data)) => {
+                for row in data.rows {
+                    let row_box = gtk::Box::builder().spacing(3).homogeneous(true).build();
+                    for keyboard_button in row {
+                        row_box.append(&self.new_keyboard_button(keyboard_button));
+                    }
+                    imp.keyboard_box.append(&row_box);
+                }
+
+                imp.is_keyboard_one_time.set(data.one_time);
+                imp.keyboard_button.set_visible(true);
+                imp.keyboard_button.set_active(true);
+
+                placeholder = data.input_field_placeholder;
+            }
+            Some(ReplyMarkup::ForceReply(data)) => {
+                imp.keyboard_button.set_visible(false);
+                imp.keyboard_button.set_active(false);
+
+                let is_entry_empty = imp.message_entry.formatted_text().is_none();
+                if imp.state.get() == ChatActionBarState::Composing && is_entry_empty {
+                    self.set_state(ChatActionBarState::Replying(message_id));
+                }
+
+                placeholder = data.input_field_placeholder;
+            }
+            _ => {
+                imp.keyboard_button.set_visible(false);
+                imp.keyboard_button.set_active(false);
+            }
+        }
+
+        if placeholder.is_empty() {
+            placeholder = gettext("Message");
+        }
+        imp.message_entry.set_placeholder_text(Some(&placeholder));
+    }
+
+    fn new_keyboard_button(&self, keyboard_button: types::KeyboardButton) -> gtk::Button {
+        let label = gtk::Label::builder()
+            .label(&keyboard_button.text)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+        let button = gtk::Button::builder().child(&label).build();
+
+        match keyboard_button.r#type {
+            KeyboardButtonType::Text
+            | KeyboardButtonType::RequestPhoneNumber
+            | KeyboardButtonType::RequestPoll(_)
+            | KeyboardButtonType::WebApp(_) => {
+                let button_type = keyboard_button.r#type;
+                let text = keyboard_button.text;
+                button.connect_clicked(clone!(@weak self as obj => move |_| {
+                    obj.activate_keyboard_button(&text, &button_type);
+                }));
+            }
+            _ => {
+                button.set_sensitive(false);
+                button.set_tooltip_text(Some(&gettext("This button is not supported yet")));
+            }
+        }
+
+        button
+    }
+
+    fn activate_keyboard_button(&self, text: &str, button_type: &KeyboardButtonType) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        match button_type {
+            KeyboardButtonType::Text => {
+                let content = InputMessageContent::InputMessageText(types::InputMessageText {
+                    text: types::FormattedText {
+                        text: text.to_owned(),
+                        entities: vec![],
+                    },
+                    disable_web_page_preview: false,
+                    clear_draft: false,
+                });
+                spawn(clone!(@weak self as obj => async move {
+                    obj.send_message_content(content).await;
+                }));
+            }
+            KeyboardButtonType::RequestPhoneNumber => {
+                spawn(clone!(@weak self as obj, @weak chat => async move {
+                    obj.share_phone_number(&chat).await;
+                }));
+            }
+            KeyboardButtonType::RequestPoll(_) => self.create_poll(),
+            KeyboardButtonType::WebApp(data) => {
+                if let Err(e) =
+                    gio::AppInfo::launch_default_for_uri(&data.url, gio::AppLaunchContext::NONE)
+                {
+                    log::warn!("Error opening an URI: {e:?}");
+                }
+            }
+            _ => {}
+        }
+
+        if self.imp().is_keyboard_one_time.get() {
+            self.imp().keyboard_button.set_active(false);
+        }
+    }
+
+    /// Sends our contact to the bot that requested it, after asking for confirmation.
+    async fn share_phone_number(&self, chat: &Chat) {
+        let Some(parent) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        // The bot is the sender of the message with the keyboard, which is the chat itself
+        // in private chats
+        let bot_name = chat
+            .message(chat.reply_markup_message_id())
+            .map(|message| strings::message_sender(message.sender(), true))
+            .unwrap_or_else(|| chat.title());
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Share Phone Number?"))
+            .body(gettext_f(
+                "Do you want to share your phone number with {bot}?",
+                &[("bot", &bot_name)],
+            ))
+            .transient_for(&parent)
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("share", &gettext("_Share")),
+        ]);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+        dialog.set_response_appearance("share", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "share" {
+            return;
+        }
+
+        let me = chat.session().me();
+        let content = InputMessageContent::InputMessageContact(types::InputMessageContact {
+            contact: types::Contact {
+                phone_number: me.phone_number(),
+                first_name: me.first_name(),
+                last_name: me.last_name(),
+                vcard: String::new(),
+                user_id: me.id(),
+            },
+        });
+        self.send_message_content(content).await;
+    }
+
+    fn load_bot_commands(&self) {
+        let imp = self.imp();
+
+        let generation = imp.bot_commands_generation.next();
+
+        self.set_bot_commands(vec![]);
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        spawn(clone!(@weak self as obj, @weak chat => async move {
+            let result = chat.bot_commands().await;
+
+            if !obj.imp().bot_commands_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(bot_commands) => {
+                    let session = chat.session();
+                    let is_private = matches!(chat.type_(), ChatType::Private(_));
+
+                    let commands = bot_commands
+                        .into_iter()
+                        .flat_map(|bot_commands| {
+                            // In groups, the commands are addressed to their bot
+                            let username = if is_private {
+                                String::new()
+                            } else {
+                                session.user(bot_commands.bot_user_id).username()
+                            };
+
+                            bot_commands.commands.into_iter().map(move |mut command| {
+                                if !username.is_empty() {
+                                    command.command = format!("{}@{username}", command.command);
+                                }
+                                command
+                            })
+                        })
+                        .collect();
+
+                    obj.set_bot_commands(commands);
+                }
+                Err(e) => log::warn!("Error loading the bot commands: {e:?}"),
+            }
+        }));
+    }
+
+    fn set_bot_commands(&self, commands: Vec<types::BotCommand>) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.bot_commands_list_box.first_child() {
+            imp.bot_commands_list_box.remove(&child);
+        }
+
+        for command in &commands {
+            imp.bot_commands_list_box
+                .append(&bot_command_row_content(command));
+        }
+
+        imp.bot_commands_button.set_visible(!commands.is_empty());
+        imp.message_entry.set_bot_commands(commands.clone());
+        imp.bot_commands.replace(commands);
+    }
+
+    /// Sends a bot command picked from the menu of the bot commands.
+    fn send_bot_command(&self, command: &types::BotCommand) {
+        self.imp().bot_commands_button.popdown();
+
+        let content = InputMessageContent::InputMessageText(types::InputMessageText {
+            text: types::FormattedText {
+                text: format!("/{}", command.command),
+                entities: vec![],
+            },
+            disable_web_page_preview: false,
+            clear_draft: false,
+        });
+
+        spawn(clone!(@weak self as obj => async move {
+            obj.send_message_content(content).await;
+        }));
+    }
+
     pub(crate) fn reply_to_message_id(&self, message_id: i64) {
         self.set_state(ChatActionBarState::Replying(message_id));
     }
@@ -766,6 +1489,10 @@ impl ChatActionBar {
     fn update_stack_page(&self) {
         let imp = self.imp();
         if let Some(chat) = self.chat() {
+            self.action_set_enabled("chat-action-bar.create-poll", chat.can_send_polls());
+            imp.message_entry
+                .set_sticker_button_visible(chat.can_send_other_messages());
+
             match chat.type_() {
                 ChatType::Private(user) => {
                     let is_deleted = matches!(user.type_().0, UserType::Deleted);
@@ -6,6 +6,7 @@ use adw::prelude::*;
 use adw::subclass::prelude::BinImpl;
 use gettextrs::gettext;
 use glib::clone;
+use gtk::gdk;
 use gtk::gio;
 use gtk::glib;
 use gtk::subclass::prelude::*;
@@ -15,19 +16,30 @@ use tdlib::enums::ChatMemberStatus;
 use tdlib::functions;
 
 use crate::expressions;
+use crate::i18n::ngettext_f;
 use crate::session::content::Background;
 use crate::session::content::ChatActionBar;
 use crate::session::content::ChatHistoryError;
+use crate::session::content::ChatHistoryItem;
 use crate::session::content::ChatHistoryModel;
 use crate::session::content::ChatHistoryRow;
 use crate::session::content::ChatInfoWindow;
+use crate::session::content::ChatSearchBar;
+use crate::session::content::ForwardWindow;
+use crate::session::content::PinnedMessageBar;
+use crate::session::content::ReactionPicker;
+use crate::strings;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
+use crate::tdlib::Message;
 use crate::tdlib::SponsoredMessage;
 use crate::utils::spawn;
 use crate::Session;
 
 const MIN_N_ITEMS: u32 = 20;
+/// The number of frames to wait for the row of a message to be bound before giving up
+/// highlighting it
+const MAX_HIGHLIGHT_FRAMES: u32 = 60;
 
 mod imp {
     use super::*;
@@ -37,20 +49,36 @@ mod imp {
     pub(crate) struct ChatHistory {
         pub(super) chat: RefCell<Option<Chat>>,
         pub(super) chat_handler: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) deleted_message_handler: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) model: RefCell<Option<ChatHistoryModel>>,
         pub(super) message_menu: OnceCell<gtk::PopoverMenu>,
+        pub(super) reaction_picker: ReactionPicker,
         pub(super) is_auto_scrolling: Cell<bool>,
         pub(super) sticky: Cell<bool>,
+        /// The messages selected in the selection mode, in the order they have been selected
+        pub(super) selected_messages: RefCell<Vec<Message>>,
         #[template_child]
         pub(super) window_title: TemplateChild<adw::WindowTitle>,
         #[template_child]
+        pub(super) search_bar: TemplateChild<ChatSearchBar>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
         pub(super) background: TemplateChild<Background>,
         #[template_child]
         pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
         #[template_child]
         pub(super) list_view: TemplateChild<gtk::ListView>,
         #[template_child]
+        pub(super) bottom_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
         pub(super) chat_action_bar: TemplateChild<ChatActionBar>,
+        #[template_child]
+        pub(super) selection_bar: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) selection_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) delete_selected_button: TemplateChild<gtk::MenuButton>,
     }
 
     #[glib::object_subclass]
@@ -61,14 +89,40 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             ChatHistoryRow::static_type();
+            ChatSearchBar::static_type();
+            PinnedMessageBar::static_type();
             klass.bind_template();
 
+            klass.add_binding_action(
+                gdk::Key::f,
+                gdk::ModifierType::CONTROL_MASK,
+                "chat-history.search",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::Escape,
+                gdk::ModifierType::empty(),
+                "chat-history.clear-selection",
+                None,
+            );
+
             klass.install_action("chat-history.view-info", None, move |widget, _, _| {
                 widget.open_info_dialog();
             });
             klass.install_action("chat-history.scroll-down", None, move |widget, _, _| {
-                widget.scroll_down();
+                widget.scroll_to_latest();
             });
+            klass.install_action("chat-history.search", None, move |widget, _, _| {
+                widget.imp().search_bar.set_search_mode_enabled(true);
+            });
+            klass.install_action(
+                "chat-history.search-sender",
+                Some("x"),
+                move |widget, _, variant| {
+                    let message_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.search_sender(message_id);
+                },
+            );
             klass.install_action(
                 "chat-history.reply",
                 Some("x"),
@@ -81,6 +135,51 @@ mod imp {
                 let message_id = variant.and_then(|v| v.get()).unwrap();
                 widget.imp().chat_action_bar.edit_message_id(message_id);
             });
+            klass.install_action(
+                "chat-history.scroll-to-message",
+                Some("x"),
+                move |widget, _, variant| {
+                    let message_id = variant.and_then(|v| v.get()).unwrap();
+                    widget.scroll_to_message(message_id);
+                },
+            );
+            klass.install_action(
+                "chat-history.select-message",
+                Some("x"),
+                move |widget, _, variant| {
+                    let message_id = variant.and_then(|v| v.get()).unwrap();
+                    if let Some(message) = widget.chat().and_then(|chat| chat.message(message_id)) {
+                        widget.set_message_selected(&message, true);
+                    }
+                },
+            );
+            klass.install_action("chat-history.clear-selection", None, move |widget, _, _| {
+                widget.clear_selection();
+            });
+            klass.install_action("chat-history.copy-selected", None, move |widget, _, _| {
+                widget.copy_selected_messages();
+            });
+            klass.install_action(
+                "chat-history.forward-selected",
+                None,
+                move |widget, _, _| {
+                    widget.forward_selected_messages();
+                },
+            );
+            klass.install_action_async(
+                "chat-history.delete-selected",
+                None,
+                |widget, _, _| async move {
+                    widget.delete_selected_messages(false).await;
+                },
+            );
+            klass.install_action_async(
+                "chat-history.revoke-delete-selected",
+                None,
+                |widget, _, _| async move {
+                    widget.delete_selected_messages(true).await;
+                },
+            );
             klass.install_action_async(
                 "chat-history.leave-chat",
                 None,
@@ -105,6 +204,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("sticky")
                         .read_only()
                         .build(),
+                    glib::ParamSpecUInt::builder("n-selected-messages")
+                        .read_only()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -129,6 +231,7 @@ mod imp {
             match pspec.name() {
                 "chat" => obj.chat().to_value(),
                 "sticky" => obj.sticky().to_value(),
+                "n-selected-messages" => obj.n_selected_messages().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -139,6 +242,7 @@ mod imp {
             let obj = self.obj();
 
             obj.setup_expressions();
+            obj.update_selection();
 
             let adj = self.list_view.vadjustment().unwrap();
             adj.connect_value_changed(clone!(@weak obj => move |adj| {
@@ -150,8 +254,11 @@ mod imp {
                         obj.set_sticky(true);
                     }
                 } else {
-                    obj.set_sticky(adj.value() + adj.page_size() >= adj.upper());
+                    obj.set_sticky(
+                        adj.value() + adj.page_size() >= adj.upper() && !obj.has_newer_messages(),
+                    );
                     obj.load_older_messages(adj);
+                    obj.load_newer_messages(adj);
                 }
             }));
 
@@ -223,6 +330,22 @@ impl ChatHistory {
         }
     }
 
+    fn load_newer_messages(&self, adj: &gtk::Adjustment) {
+        if adj.upper() - adj.value() - adj.page_size() < adj.page_size() * 2.0 {
+            if let Some(model) = self.imp().model.borrow().as_ref() {
+                if !model.has_newer_messages() {
+                    return;
+                }
+
+                spawn(clone!(@weak model => async move {
+                    if let Err(ChatHistoryError::Tdlib(e)) = model.load_newer_messages(20).await {
+                        log::warn!("Couldn't load newer chat messages: {:?}", e);
+                    }
+                }));
+            }
+        }
+    }
+
     fn open_info_dialog(&self) {
         if let Some(chat) = self.chat() {
             ChatInfoWindow::new(&self.parent_window(), &chat).present();
@@ -286,14 +409,42 @@ impl ChatHistory {
                 gtk::Align::Start
             });
 
+            menu.add_child(&self.imp().reaction_picker, "reactions");
+
             menu
         })
     }
 
+    /// Updates the parts of the message menu that depend on the message it's shown for.
+    pub(crate) fn update_message_menu(&self, message: Option<Message>) {
+        self.imp().reaction_picker.set_message(message);
+    }
+
+    pub(crate) fn search_bar(&self) -> &ChatSearchBar {
+        &self.imp().search_bar
+    }
+
+    /// Searches the messages sent by the sender of the specified message.
+    fn search_sender(&self, message_id: i64) {
+        if let Some(message) = self.chat().and_then(|chat| chat.message(message_id)) {
+            let search_bar = &*self.imp().search_bar;
+            search_bar.set_search_mode_enabled(true);
+            search_bar.set_sender(Some(message.sender().clone()));
+        }
+    }
+
     pub(crate) fn handle_paste_action(&self) {
         self.imp().chat_action_bar.handle_paste_action();
     }
 
+    pub(crate) fn chat_action_bar(&self) -> &ChatActionBar {
+        &self.imp().chat_action_bar
+    }
+
+    pub(crate) fn add_toast(&self, toast: adw::Toast) {
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
     pub(crate) fn chat(&self) -> Option<Chat> {
         self.imp().chat.borrow().clone()
     }
@@ -305,6 +456,8 @@ impl ChatHistory {
 
         let imp = self.imp();
 
+        self.clear_selection();
+
         if let Some(ref chat) = chat {
             self.action_set_enabled(
                 "chat-history.leave-chat",
@@ -353,6 +506,14 @@ impl ChatHistory {
             let handler = chat.connect_new_message(clone!(@weak self as obj => move |_, msg| {
                 if msg.is_outgoing() {
                     obj.imp().background.animate();
+
+                    // Show the sent message if the latest messages aren't loaded. This is
+                    // done in an idle callback, so that the chat's last message is updated.
+                    if obj.has_newer_messages() {
+                        glib::idle_add_local_once(clone!(@weak obj => move || {
+                            obj.scroll_to_latest();
+                        }));
+                    }
                 }
             }));
 
@@ -362,6 +523,17 @@ impl ChatHistory {
                 }
             }
 
+            let handler =
+                chat.connect_deleted_message(clone!(@weak self as obj => move |_, message| {
+                    obj.set_message_selected(&message, false);
+                }));
+
+            if let Some(old_handler) = imp.deleted_message_handler.replace(Some(handler)) {
+                if let Some(old_chat) = &*imp.chat.borrow() {
+                    old_chat.disconnect(old_handler);
+                }
+            }
+
             let selection = gtk::NoSelection::new(Some(list_view_model));
             imp.list_view.set_model(Some(&selection));
 
@@ -372,6 +544,166 @@ impl ChatHistory {
         self.notify("chat");
     }
 
+    pub(crate) fn n_selected_messages(&self) -> u32 {
+        self.imp().selected_messages.borrow().len() as u32
+    }
+
+    pub(crate) fn is_message_selected(&self, message: &Message) -> bool {
+        self.imp().selected_messages.borrow().contains(message)
+    }
+
+    /// Selects or unselects a message. The selection mode is active as long as there's at
+    /// least a selected message.
+    pub(crate) fn set_message_selected(&self, message: &Message, selected: bool) {
+        {
+            let mut selected_messages = self.imp().selected_messages.borrow_mut();
+            let position = selected_messages.iter().position(|m| m == message);
+            match (position, selected) {
+                (None, true) => selected_messages.push(message.clone()),
+                (Some(position), false) => {
+                    selected_messages.remove(position);
+                }
+                _ => return,
+            }
+        }
+
+        self.update_selection();
+    }
+
+    pub(crate) fn clear_selection(&self) {
+        if self.imp().selected_messages.take().is_empty() {
+            return;
+        }
+
+        self.update_selection();
+    }
+
+    /// Returns the selected messages, in the order they have been sent.
+    fn selected_messages(&self) -> Vec<Message> {
+        let mut messages = self.imp().selected_messages.borrow().clone();
+        messages.sort_by_key(Message::id);
+        messages
+    }
+
+    fn update_selection(&self) {
+        let imp = self.imp();
+        let selected_messages = imp.selected_messages.borrow();
+        let n_selected = selected_messages.len() as u32;
+
+        if n_selected > 0 {
+            imp.selection_label.set_label(&ngettext_f(
+                "{num} Selected Message",
+                "{num} Selected Messages",
+                n_selected,
+                &[("num", &n_selected.to_string())],
+            ));
+            imp.bottom_stack.set_visible_child(&*imp.selection_bar);
+        } else {
+            imp.bottom_stack.set_visible_child(&*imp.chat_action_bar);
+        }
+
+        // Bulk actions are available only if they can be done on every selected message
+        let all = |f: fn(&Message) -> bool| n_selected > 0 && selected_messages.iter().all(f);
+        let can_delete = all(Message::can_be_deleted_only_for_self);
+        let can_revoke_delete = all(Message::can_be_deleted_for_all_users);
+
+        self.action_set_enabled("chat-history.clear-selection", n_selected > 0);
+        self.action_set_enabled("chat-history.copy-selected", n_selected > 0);
+        self.action_set_enabled(
+            "chat-history.forward-selected",
+            all(Message::can_be_forwarded),
+        );
+        self.action_set_enabled("chat-history.delete-selected", can_delete);
+        self.action_set_enabled("chat-history.revoke-delete-selected", can_revoke_delete);
+        imp.delete_selected_button
+            .set_sensitive(can_delete || can_revoke_delete);
+
+        drop(selected_messages);
+        self.notify("n-selected-messages");
+    }
+
+    fn copy_selected_messages(&self) {
+        let text = self
+            .selected_messages()
+            .iter()
+            .map(|message| {
+                let sender = strings::message_sender(message.sender(), true);
+                let datetime = glib::DateTime::from_unix_local(message.date() as i64)
+                    // Translators: This is a date and time representation, without seconds.
+                    // Here you may want to change to a 24-hours representation, based on your locale.
+                    // You can use this site to learn more: https://www.strfti.me/
+                    .and_then(|t| t.format(&gettext("%x %l:%M %p")));
+
+                let header = match datetime {
+                    Ok(datetime) => format!("{sender}, [{}]", datetime.trim()),
+                    Err(e) => {
+                        log::warn!("Error formatting the date of a message: {e:?}");
+                        sender
+                    }
+                };
+
+                format!("{header}\n{}", strings::message_content(message))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        self.clipboard().set_text(&text);
+        self.clear_selection();
+    }
+
+    fn forward_selected_messages(&self) {
+        if let Some(window) = self.parent_window() {
+            ForwardWindow::new(&window, self.selected_messages()).present();
+            self.clear_selection();
+        }
+    }
+
+    async fn delete_selected_messages(&self, revoke: bool) {
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let message_ids: Vec<i64> = self.selected_messages().iter().map(Message::id).collect();
+        let n_messages = message_ids.len() as u32;
+        let n_messages_str = n_messages.to_string();
+
+        let body = if revoke {
+            ngettext_f(
+                "Do you want to delete this message for <b>everyone</b>?",
+                "Do you want to delete {num} messages for <b>everyone</b>?",
+                n_messages,
+                &[("num", &n_messages_str)],
+            )
+        } else {
+            ngettext_f(
+                "Do you want to delete this message?",
+                "Do you want to delete {num} messages?",
+                n_messages,
+                &[("num", &n_messages_str)],
+            )
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Confirm Message Deletion"))
+            .body_use_markup(true)
+            .body(body)
+            .transient_for(&self.parent_window().unwrap())
+            .build();
+
+        dialog.add_responses(&[("no", &gettext("_No")), ("yes", &gettext("_Yes"))]);
+        dialog.set_default_response(Some("no"));
+        dialog.set_close_response("no");
+        dialog.set_response_appearance("yes", adw::ResponseAppearance::Destructive);
+
+        if dialog.choose_future().await == "yes" {
+            self.clear_selection();
+
+            if let Err(e) = chat.delete_messages(message_ids, revoke).await {
+                log::warn!("Error deleting messages (revoke = {}): {:?}", revoke, e);
+            }
+        }
+    }
+
     pub(crate) fn sticky(&self) -> bool {
         self.imp().sticky.get()
     }
@@ -385,6 +717,133 @@ impl ChatHistory {
         self.notify("sticky");
     }
 
+    /// Scrolls to the message of the specified id and highlights it, loading the history
+    /// around it if needed.
+    pub(crate) fn scroll_to_message(&self, message_id: i64) {
+        spawn(clone!(@weak self as obj => async move {
+            obj.load_and_scroll_to_message(message_id).await;
+        }));
+    }
+
+    async fn load_and_scroll_to_message(&self, message_id: i64) {
+        let imp = self.imp();
+
+        if self.message_position(message_id).is_none() {
+            let Some(model) = imp.model.borrow().clone() else {
+                return;
+            };
+
+            loop {
+                match model
+                    .load_messages_around(message_id, MIN_N_ITEMS as i32 * 2)
+                    .await
+                {
+                    Ok(()) => break,
+                    Err(ChatHistoryError::AlreadyLoading) => {
+                        glib::timeout_future(std::time::Duration::from_millis(100)).await;
+                    }
+                    Err(ChatHistoryError::Tdlib(e)) => {
+                        log::warn!("Couldn't load the history around a message: {:?}", e);
+                        return;
+                    }
+                }
+            }
+        }
+
+        if let Some(position) = self.message_position(message_id) {
+            // Stop following the end of the history
+            imp.is_auto_scrolling.set(false);
+            self.set_sticky(false);
+
+            imp.list_view
+                .activate_action("list.scroll-to-item", Some(&position.to_variant()))
+                .unwrap();
+
+            self.highlight_message_row(message_id);
+        }
+    }
+
+    /// Highlights the row of the specified message, waiting for the list view to bind it
+    /// after scrolling to it.
+    fn highlight_message_row(&self, message_id: i64) {
+        let mut n_frames = 0;
+        self.imp().list_view.add_tick_callback(
+            clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move |_, _| {
+                n_frames += 1;
+
+                if obj.try_highlight_message_row(message_id) || n_frames >= MAX_HIGHLIGHT_FRAMES {
+                    glib::ControlFlow::Break
+                } else {
+                    glib::ControlFlow::Continue
+                }
+            }),
+        );
+    }
+
+    /// Highlights the row of the specified message, returning whether it has been found
+    /// among the rows bound by the list view.
+    fn try_highlight_message_row(&self, message_id: i64) -> bool {
+        let mut child = self.imp().list_view.first_child();
+
+        while let Some(widget) = child {
+            if let Some(row) = widget.first_child().and_downcast::<ChatHistoryRow>() {
+                let is_message_row = row
+                    .item()
+                    .and_downcast::<ChatHistoryItem>()
+                    .and_then(|item| item.message().map(|message| message.id() == message_id))
+                    .unwrap_or_default();
+
+                if is_message_row {
+                    row.add_css_class("highlighted");
+                    glib::timeout_add_local_once(
+                        std::time::Duration::from_secs(1),
+                        clone!(@weak row => move || row.remove_css_class("highlighted")),
+                    );
+                    return true;
+                }
+            }
+
+            child = widget.next_sibling();
+        }
+
+        false
+    }
+
+    /// Returns the position of the message of the specified id in the list view, if loaded.
+    fn message_position(&self, message_id: i64) -> Option<u32> {
+        let model = self.imp().list_view.model()?;
+
+        (0..model.n_items()).find(|position| {
+            model
+                .item(*position)
+                .and_downcast::<ChatHistoryItem>()
+                .and_then(|item| item.message().map(|message| message.id() == message_id))
+                .unwrap_or_default()
+        })
+    }
+
+    fn has_newer_messages(&self) -> bool {
+        self.imp()
+            .model
+            .borrow()
+            .as_ref()
+            .map(ChatHistoryModel::has_newer_messages)
+            .unwrap_or_default()
+    }
+
+    /// Scrolls to the end of the history, jumping back to the latest messages if they
+    /// aren't loaded.
+    fn scroll_to_latest(&self) {
+        if self.has_newer_messages() {
+            if let Some(message) = self.chat().and_then(|chat| chat.last_message()) {
+                self.scroll_to_message(message.id());
+                return;
+            }
+        }
+
+        self.scroll_down();
+    }
+
     fn scroll_down(&self) {
         let imp = self.imp();
 
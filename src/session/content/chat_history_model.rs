@@ -32,6 +32,7 @@ mod imp {
     pub(crate) struct ChatHistoryModel {
         pub(super) chat: WeakRef<Chat>,
         pub(super) is_loading: Cell<bool>,
+        pub(super) has_newer_messages: Cell<bool>,
         pub(super) list: RefCell<VecDeque<ChatHistoryItem>>,
     }
 
@@ -123,7 +124,10 @@ impl ChatHistoryModel {
 
         imp.is_loading.set(true);
 
-        let result = self.chat().get_chat_history(oldest_message_id, limit).await;
+        let result = self
+            .chat()
+            .get_chat_history(oldest_message_id, 0, limit)
+            .await;
 
         imp.is_loading.set(false);
 
@@ -137,6 +141,123 @@ impl ChatHistoryModel {
         Ok(true)
     }
 
+    /// Loads newer messages from this chat history, if the loaded history doesn't already
+    /// end with the latest message.
+    ///
+    /// Returns `true` when more messages can be loaded.
+    pub(crate) async fn load_newer_messages(&self, limit: i32) -> Result<bool, ChatHistoryError> {
+        let imp = self.imp();
+
+        if !self.has_newer_messages() {
+            return Ok(false);
+        }
+
+        if imp.is_loading.get() {
+            return Err(ChatHistoryError::AlreadyLoading);
+        }
+
+        let Some(newest_message_id) = imp
+            .list
+            .borrow()
+            .iter()
+            .find_map(|item| item.message())
+            .map(|m| m.id())
+        else {
+            return Ok(false);
+        };
+
+        imp.is_loading.set(true);
+
+        // With a negative offset TDLib returns the messages newer than the specified one,
+        // together with the specified one itself
+        let result = self
+            .chat()
+            .get_chat_history(newest_message_id, -limit, limit + 1)
+            .await;
+
+        imp.is_loading.set(false);
+
+        let messages: Vec<Message> = result
+            .map_err(ChatHistoryError::Tdlib)?
+            .into_iter()
+            .filter(|m| m.id() > newest_message_id)
+            .collect();
+
+        // TDLib can return an empty or a short batch before reaching the last message, so
+        // only stop once the last message of the chat has actually been loaded
+        let newest_loaded_message_id = messages
+            .iter()
+            .map(Message::id)
+            .max()
+            .unwrap_or(newest_message_id)
+            .max(newest_message_id);
+        let has_newer_messages = self
+            .chat()
+            .last_message()
+            .is_some_and(|m| m.id() > newest_loaded_message_id);
+        imp.has_newer_messages.set(has_newer_messages);
+
+        // Add them from the oldest to the newest, so that the day dividers are inserted
+        // like for new messages
+        for message in messages.into_iter().rev() {
+            self.prepend(message);
+        }
+
+        Ok(has_newer_messages)
+    }
+
+    /// Replaces the loaded history with a window of messages around the specified message.
+    pub(crate) async fn load_messages_around(
+        &self,
+        message_id: i64,
+        limit: i32,
+    ) -> Result<(), ChatHistoryError> {
+        let imp = self.imp();
+
+        if imp.is_loading.get() {
+            return Err(ChatHistoryError::AlreadyLoading);
+        }
+
+        imp.is_loading.set(true);
+
+        // A negative offset makes TDLib also return messages newer than the specified one
+        let result = self
+            .chat()
+            .get_chat_history(message_id, -limit / 2, limit)
+            .await;
+
+        imp.is_loading.set(false);
+
+        let messages = result.map_err(ChatHistoryError::Tdlib)?;
+
+        let newest_loaded_message_id = messages.first().map(Message::id).unwrap_or(message_id);
+        imp.has_newer_messages.set(
+            self.chat()
+                .last_message()
+                .is_some_and(|m| m.id() > newest_loaded_message_id),
+        );
+
+        let removed = {
+            let mut list = imp.list.borrow_mut();
+            let removed = list.len();
+            list.clear();
+            removed as u32
+        };
+        self.upcast_ref::<gio::ListModel>()
+            .items_changed(0, removed, 0);
+
+        if !messages.is_empty() {
+            self.append(messages);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the loaded history is missing messages newer than the loaded ones.
+    pub(crate) fn has_newer_messages(&self) -> bool {
+        self.imp().has_newer_messages.get()
+    }
+
     fn items_changed(&self, position: u32, removed: u32, added: u32) {
         let imp = self.imp();
 
@@ -238,6 +359,15 @@ impl ChatHistoryModel {
     }
 
     fn push_front(&self, message: Message) {
+        // The message isn't adjacent to the loaded ones, it will be loaded with the newer ones
+        if self.has_newer_messages() {
+            return;
+        }
+
+        self.prepend(message);
+    }
+
+    fn prepend(&self, message: Message) {
         self.imp()
             .list
             .borrow_mut()
@@ -273,25 +403,26 @@ impl ChatHistoryModel {
             // The elements in this list are ordered. While the day dividers are ordered
             // only by their date time, the messages are additionally sorted by their id. We
             // can exploit this by applying a binary search.
-            let index = list
-                .binary_search_by(|m| match m.type_() {
-                    ChatHistoryItemType::Message(other_message) => {
-                        message.id().cmp(&other_message.id())
-                    }
-                    ChatHistoryItemType::DayDivider(date_time) => {
-                        let ordering = glib::DateTime::from_unix_utc(message.date() as i64)
-                            .unwrap()
-                            .cmp(date_time);
-                        if let Ordering::Equal = ordering {
-                            // We found the day divider of the message. Therefore, the message
-                            // must be among the following elements.
-                            Ordering::Greater
-                        } else {
-                            ordering
-                        }
+            let Ok(index) = list.binary_search_by(|m| match m.type_() {
+                ChatHistoryItemType::Message(other_message) => {
+                    message.id().cmp(&other_message.id())
+                }
+                ChatHistoryItemType::DayDivider(date_time) => {
+                    let ordering = glib::DateTime::from_unix_utc(message.date() as i64)
+                        .unwrap()
+                        .cmp(date_time);
+                    if let Ordering::Equal = ordering {
+                        // We found the day divider of the message. Therefore, the message
+                        // must be among the following elements.
+                        Ordering::Greater
+                    } else {
+                        ordering
                     }
-                })
-                .unwrap();
+                }
+            }) else {
+                // The message could be outside of the loaded window
+                return;
+            };
 
             list.remove(index);
             index as u32
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use adw::prelude::BinExt;
 use adw::subclass::prelude::BinImpl;
 use gettextrs::gettext;
+use glib::clone;
 use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -15,6 +16,7 @@ use crate::session::content::EventRow;
 use crate::session::content::MessageRow;
 use crate::strings;
 use crate::tdlib::SponsoredMessage;
+use crate::utils::spawn;
 
 mod imp {
     use super::*;
@@ -81,6 +83,21 @@ impl ChatHistoryRow {
         glib::Object::new()
     }
 
+    fn update_pin_message_event(&self, item: &glib::Object) {
+        // The row could have been recycled in the meantime
+        if self.item().as_ref() != Some(item) {
+            return;
+        }
+
+        if let Some(ChatHistoryItemType::Message(message)) = item
+            .downcast_ref::<ChatHistoryItem>()
+            .map(ChatHistoryItem::type_)
+        {
+            self.get_or_create_event_row()
+                .set_label(&strings::message_content(message));
+        }
+    }
+
     pub(crate) fn item(&self) -> Option<glib::Object> {
         self.imp().item.borrow().to_owned()
     }
@@ -109,13 +126,30 @@ impl ChatHistoryRow {
                             | MessageChatJoinByLink
                             | MessageChatJoinByRequest
                             | MessageChatDeleteMember(_)
-                            | MessagePinMessage(_)
                             | MessageScreenshotTaken
                             | MessageGameScore(_)
                             | MessageContactRegistered => {
                                 self.get_or_create_event_row()
                                     .set_label(&strings::message_content(message));
                             }
+                            MessagePinMessage(data) => {
+                                self.get_or_create_event_row()
+                                    .set_label(&strings::message_content(message));
+
+                                // Fetch the pinned message to show its content if needed
+                                let chat = message.chat();
+                                if chat.message(data.message_id).is_none() {
+                                    let item = item.clone().upcast::<glib::Object>();
+                                    spawn(clone!(@weak self as obj => async move {
+                                        match chat.fetch_message(data.message_id).await {
+                                            Ok(_) => obj.update_pin_message_event(&item),
+                                            Err(e) => log::warn!(
+                                                "Error fetching a pinned message: {e:?}"
+                                            ),
+                                        }
+                                    }));
+                                }
+                            }
                             _ => self.update_or_create_message_row(message.to_owned().upcast()),
                         }
                     }
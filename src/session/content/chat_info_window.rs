@@ -9,13 +9,17 @@ use gtk::glib;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
+use tdlib::enums::ChatMemberStatus;
 use tdlib::enums::UserType;
 use tdlib::functions;
 use tdlib::types::BasicGroupFullInfo;
 use tdlib::types::SupergroupFullInfo;
 
 use crate::expressions;
+use crate::i18n::gettext_f;
 use crate::i18n::ngettext_f;
+use crate::session::content::MemberList;
+use crate::session::content::SharedMediaPage;
 use crate::strings;
 use crate::tdlib::BasicGroup;
 use crate::tdlib::BoxedUserStatus;
@@ -40,6 +44,10 @@ mod imp {
         pub(super) subtitle_label: TemplateChild<gtk::Inscription>,
         #[template_child]
         pub(super) info_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) shared_media_stack: TemplateChild<adw::ViewStack>,
+        #[template_child]
+        pub(super) members_page: TemplateChild<adw::ViewStackPage>,
     }
 
     #[glib::object_subclass]
@@ -49,7 +57,10 @@ mod imp {
         type ParentType = adw::Window;
 
         fn class_init(klass: &mut Self::Class) {
+            MemberList::static_type();
+            SharedMediaPage::static_type();
             klass.bind_template();
+            klass.bind_template_instance_callbacks();
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -90,7 +101,21 @@ mod imp {
     }
 
     impl WidgetImpl for ChatInfoWindow {}
-    impl WindowImpl for ChatInfoWindow {}
+
+    impl WindowImpl for ChatInfoWindow {
+        fn close_request(&self) -> glib::Propagation {
+            if let Some(chat) = self.chat.get().cloned() {
+                spawn(async move {
+                    if let Err(e) = chat.close().await {
+                        log::warn!("Error closing a chat: {e:?}");
+                    }
+                });
+            }
+
+            self.parent_close_request()
+        }
+    }
+
     impl AdwWindowImpl for ChatInfoWindow {}
 }
 
@@ -99,7 +124,26 @@ glib::wrapper! {
         @extends gtk::Widget, gtk::Window, adw::Window;
 }
 
+#[gtk::template_callbacks]
 impl ChatInfoWindow {
+    /// Loads more items of the visible page once its end has been reached.
+    #[template_callback]
+    fn on_edge_reached(&self, pos: gtk::PositionType) {
+        if pos != gtk::PositionType::Bottom {
+            return;
+        }
+
+        let Some(child) = self.imp().shared_media_stack.visible_child() else {
+            return;
+        };
+
+        if let Some(page) = child.downcast_ref::<SharedMediaPage>() {
+            page.load_more();
+        } else if let Some(member_list) = child.downcast_ref::<MemberList>() {
+            member_list.load_more();
+        }
+    }
+
     pub(crate) fn new(parent_window: &Option<gtk::Window>, chat: &Chat) -> Self {
         glib::Object::builder()
             .property("transient-for", parent_window)
@@ -118,6 +162,14 @@ impl ChatInfoWindow {
             Some(self),
         );
 
+        // Opening the chat makes TDLib send updates about its online members
+        let chat = self.chat().unwrap().clone();
+        spawn(async move {
+            if let Err(e) = chat.open().await {
+                log::warn!("Error opening a chat: {e:?}");
+            }
+        });
+
         match self.chat().unwrap().type_() {
             ChatType::Private(user) => {
                 self.setup_user_info(user);
@@ -170,19 +222,9 @@ impl ChatInfoWindow {
     fn setup_basic_group_info(&self, basic_group: &BasicGroup) {
         let client_id = self.chat().unwrap().session().client_id();
         let basic_group_id = basic_group.id();
-        let imp = self.imp();
 
-        // Members number
-        BasicGroup::this_expression("member-count")
-            .chain_closure::<String>(closure!(|_: Option<glib::Object>, member_count: i32| {
-                ngettext_f(
-                    "{num} member",
-                    "{num} members",
-                    member_count as u32,
-                    &[("num", &member_count.to_string())],
-                )
-            }))
-            .bind(&*imp.subtitle_label, "text", Some(basic_group));
+        self.bind_member_count(basic_group);
+        self.show_members_page();
 
         self.update_info_list_visibility();
 
@@ -218,17 +260,17 @@ impl ChatInfoWindow {
         let supergroup_id = supergroup.id();
         let imp = self.imp();
 
-        // Members number
-        Supergroup::this_expression("member-count")
-            .chain_closure::<String>(closure!(|_: Option<glib::Object>, member_count: i32| {
-                ngettext_f(
-                    "{num} member",
-                    "{num} members",
-                    member_count as u32,
-                    &[("num", &member_count.to_string())],
-                )
-            }))
-            .bind(&*imp.subtitle_label, "text", Some(supergroup));
+        self.bind_member_count(supergroup);
+
+        // Only admins can see the subscribers of a channel
+        if !supergroup.is_channel()
+            || matches!(
+                supergroup.status().0,
+                ChatMemberStatus::Creator(_) | ChatMemberStatus::Administrator(_)
+            )
+        {
+            self.show_members_page();
+        }
 
         // Link
         if !supergroup.username().is_empty() {
@@ -269,6 +311,53 @@ impl ChatInfoWindow {
         self.update_info_list_visibility();
     }
 
+    /// Binds the subtitle to the member count of the group, along with the number of its
+    /// online members when it's known.
+    fn bind_member_count<T: IsA<glib::Object>>(&self, group: &T) {
+        let member_count_expression = gtk::PropertyExpression::new(
+            T::static_type(),
+            Some(gtk::ConstantExpression::new(group)),
+            "member-count",
+        );
+        let online_member_count_expression =
+            Self::this_expression("chat").chain_property::<Chat>("online-member-count");
+
+        gtk::ClosureExpression::new::<String>(
+            &[member_count_expression, online_member_count_expression],
+            closure!(
+                |_: Option<glib::Object>, member_count: i32, online_member_count: i32| {
+                    let members = ngettext_f(
+                        "{num} member",
+                        "{num} members",
+                        member_count as u32,
+                        &[("num", &member_count.to_string())],
+                    );
+
+                    // TDLib only counts the other members, so a single online member is
+                    // usually the user themselves
+                    if online_member_count > 1 {
+                        gettext_f(
+                            "{members}, {num} online",
+                            &[
+                                ("members", &members),
+                                ("num", &online_member_count.to_string()),
+                            ],
+                        )
+                    } else {
+                        members
+                    }
+                }
+            ),
+        )
+        .bind(&*self.imp().subtitle_label, "text", Some(self));
+    }
+
+    fn show_members_page(&self) {
+        let imp = self.imp();
+        imp.members_page.set_visible(true);
+        imp.shared_media_stack.set_visible_child_name("members");
+    }
+
     fn update_info_list_visibility(&self) {
         let info_list = &self.imp().info_list;
         info_list.set_visible(info_list.first_child().is_some());
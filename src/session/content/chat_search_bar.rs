@@ -0,0 +1,465 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::i18n::gettext_f;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::FoundChatMessages;
+use crate::tdlib::Message;
+use crate::tdlib::MessageSender;
+use crate::utils::spawn;
+
+const SEARCH_LIMIT: i32 = 100;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentChatSearchBar {
+        layout-manager: BinLayout {};
+
+        SearchBar search_bar {
+            search-mode-enabled: bind-property template.search-mode-enabled bidirectional;
+
+            Box {
+                spacing: 6;
+
+                SearchEntry search_entry {
+                    hexpand: true;
+                    placeholder-text: _("Search Messages");
+                    search-changed => $on_search_changed() swapped;
+                    activate => $on_previous_match() swapped;
+                    previous-match => $on_previous_match() swapped;
+                    next-match => $on_next_match() swapped;
+                }
+
+                Box sender_box {
+                    visible: false;
+                    spacing: 3;
+
+                    styles ["sender-filter"]
+
+                    Label sender_label {
+                        ellipsize: end;
+                        max-width-chars: 16;
+                    }
+
+                    Button {
+                        icon-name: "window-close-symbolic";
+                        tooltip-text: _("Remove Sender Filter");
+                        clicked => $on_clear_sender_clicked() swapped;
+
+                        styles ["flat", "circular"]
+                    }
+                }
+
+                Label results_label {
+                    styles ["dim-label", "numeric"]
+                }
+
+                Button previous_button {
+                    icon-name: "go-up-symbolic";
+                    tooltip-text: _("Previous Result");
+                    sensitive: false;
+                    clicked => $on_previous_match() swapped;
+                }
+
+                Button next_button {
+                    icon-name: "go-down-symbolic";
+                    tooltip-text: _("Next Result");
+                    sensitive: false;
+                    clicked => $on_next_match() swapped;
+                }
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct ChatSearchBar {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) search_mode_enabled: Cell<bool>,
+        pub(super) sender: RefCell<Option<MessageSender>>,
+        pub(super) query: RefCell<String>,
+        /// The found messages, from the newest to the oldest
+        pub(super) results: RefCell<Vec<Message>>,
+        /// The approximate number of messages found in total, including the ones that
+        /// are not loaded yet
+        pub(super) total_count: Cell<i32>,
+        /// The message to search the next page of results from, or 0 if all the results
+        /// have been loaded
+        pub(super) next_from_message_id: Cell<i64>,
+        pub(super) is_loading_more: Cell<bool>,
+        pub(super) current_index: Cell<Option<usize>>,
+        #[template_child]
+        pub(super) search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) sender_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) sender_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) results_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) previous_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) next_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ChatSearchBar {
+        const NAME: &'static str = "ContentChatSearchBar";
+        type Type = super::ChatSearchBar;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ChatSearchBar {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("search-mode-enabled")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecString::builder("query").read_only().build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                "search-mode-enabled" => obj.set_search_mode_enabled(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "search-mode-enabled" => obj.search_mode_enabled().to_value(),
+                "query" => obj.query().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.search_bar.unparent();
+        }
+    }
+
+    impl WidgetImpl for ChatSearchBar {}
+}
+
+glib::wrapper! {
+    pub(crate) struct ChatSearchBar(ObjectSubclass<imp::ChatSearchBar>)
+        @extends gtk::Widget;
+}
+
+#[gtk::template_callbacks]
+impl ChatSearchBar {
+    #[template_callback]
+    fn on_search_changed(&self) {
+        self.search();
+    }
+
+    /// Goes to the next older result, loading the next page of results if needed.
+    #[template_callback]
+    fn on_previous_match(&self) {
+        let imp = self.imp();
+        let n_results = imp.results.borrow().len();
+
+        if let Some(index) = imp.current_index.get() {
+            if index + 1 < n_results {
+                self.select_result(index + 1);
+            } else if imp.next_from_message_id.get() != 0 {
+                self.load_more_results();
+            }
+        }
+    }
+
+    /// Goes to the next newer result.
+    #[template_callback]
+    fn on_next_match(&self) {
+        if let Some(index) = self.imp().current_index.get() {
+            if index > 0 {
+                self.select_result(index - 1);
+            }
+        }
+    }
+
+    #[template_callback]
+    fn on_clear_sender_clicked(&self) {
+        self.set_sender(None);
+    }
+
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        self.imp().chat.replace(chat);
+        self.set_search_mode_enabled(false);
+
+        self.notify("chat");
+    }
+
+    pub(crate) fn search_mode_enabled(&self) -> bool {
+        self.imp().search_mode_enabled.get()
+    }
+
+    pub(crate) fn set_search_mode_enabled(&self, search_mode_enabled: bool) {
+        if self.search_mode_enabled() == search_mode_enabled {
+            return;
+        }
+
+        let imp = self.imp();
+        imp.search_mode_enabled.set(search_mode_enabled);
+
+        if search_mode_enabled {
+            imp.search_entry.grab_focus();
+        } else {
+            imp.search_entry.set_text("");
+            self.set_sender(None);
+        }
+
+        self.notify("search-mode-enabled");
+    }
+
+    /// Filters the search results to the messages sent by the specified sender.
+    pub(crate) fn set_sender(&self, sender: Option<MessageSender>) {
+        let imp = self.imp();
+
+        match &sender {
+            Some(sender) => {
+                imp.sender_label.set_label(&gettext_f(
+                    "From: {sender}",
+                    &[("sender", &strings::message_sender(sender, false))],
+                ));
+                imp.sender_box.set_visible(true);
+            }
+            None => imp.sender_box.set_visible(false),
+        }
+
+        imp.sender.replace(sender);
+
+        if self.search_mode_enabled() {
+            self.search();
+        }
+    }
+
+    /// The text of the currently shown search results.
+    pub(crate) fn query(&self) -> String {
+        self.imp().query.borrow().clone()
+    }
+
+    fn set_query(&self, query: String) {
+        if self.query() == query {
+            return;
+        }
+
+        self.imp().query.replace(query);
+        self.notify("query");
+    }
+
+    fn search(&self) {
+        let imp = self.imp();
+        let query = imp.search_entry.text().to_string();
+        let sender = imp.sender.borrow().clone();
+
+        imp.results.borrow_mut().clear();
+        imp.total_count.set(0);
+        imp.next_from_message_id.set(0);
+        imp.is_loading_more.set(false);
+        imp.current_index.set(None);
+        imp.results_label.set_label("");
+        self.update_navigation();
+
+        if query.is_empty() && sender.is_none() {
+            self.set_query(String::new());
+            return;
+        }
+
+        if let Some(chat) = self.chat() {
+            spawn(clone!(@weak self as obj => async move {
+                let result = chat
+                    .search_messages_page(query.clone(), sender.as_ref(), 0, None, SEARCH_LIMIT)
+                    .await;
+
+                // Ignore the results if the search has changed in the meantime
+                if !obj.is_current_search(&chat, &query, sender.as_ref()) {
+                    return;
+                }
+
+                match result {
+                    Ok(found) => {
+                        obj.set_query(query);
+                        obj.set_results(found);
+                    }
+                    Err(e) => log::warn!("Error searching chat messages: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    /// Loads the next page of results and goes to its first result.
+    fn load_more_results(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading_more.get() {
+            return;
+        }
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        imp.is_loading_more.set(true);
+        self.update_navigation();
+
+        let query = imp.search_entry.text().to_string();
+        let sender = imp.sender.borrow().clone();
+        let from_message_id = imp.next_from_message_id.get();
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_messages_page(
+                    query.clone(),
+                    sender.as_ref(),
+                    from_message_id,
+                    None,
+                    SEARCH_LIMIT,
+                )
+                .await;
+
+            let imp = obj.imp();
+            if !obj.is_current_search(&chat, &query, sender.as_ref())
+                || imp.next_from_message_id.get() != from_message_id
+            {
+                return;
+            }
+
+            imp.is_loading_more.set(false);
+
+            match result {
+                Ok(found) => {
+                    let index = imp.results.borrow().len();
+
+                    imp.total_count.set(found.total_count);
+                    imp.next_from_message_id.set(found.next_from_message_id);
+                    imp.results.borrow_mut().extend(found.messages);
+
+                    if index < imp.results.borrow().len() {
+                        obj.select_result(index);
+                    } else {
+                        obj.update_navigation();
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error loading more chat messages: {e:?}");
+                    obj.update_navigation();
+                }
+            }
+        }));
+    }
+
+    /// Returns whether the search bar is still showing the results of the specified
+    /// search.
+    fn is_current_search(&self, chat: &Chat, query: &str, sender: Option<&MessageSender>) -> bool {
+        let imp = self.imp();
+
+        self.chat().as_ref() == Some(chat)
+            && imp.search_entry.text() == query
+            && imp.sender.borrow().as_ref().map(MessageSender::id) == sender.map(MessageSender::id)
+    }
+
+    fn set_results(&self, found: FoundChatMessages) {
+        let imp = self.imp();
+        let is_empty = found.messages.is_empty();
+
+        imp.results.replace(found.messages);
+        imp.total_count.set(found.total_count);
+        imp.next_from_message_id.set(found.next_from_message_id);
+        imp.current_index.set(None);
+
+        if is_empty {
+            imp.results_label.set_label(&gettext("No Results"));
+            self.update_navigation();
+        } else {
+            self.select_result(0);
+        }
+    }
+
+    fn select_result(&self, index: usize) {
+        let imp = self.imp();
+        let results = imp.results.borrow();
+
+        if let Some(message) = results.get(index) {
+            // The total count is approximate, so it can't be less than the loaded results
+            let count = (imp.total_count.get().max(0) as usize).max(results.len());
+
+            imp.current_index.set(Some(index));
+            imp.results_label.set_label(&gettext_f(
+                "{index} of {count}",
+                &[
+                    ("index", &(index + 1).to_string()),
+                    ("count", &count.to_string()),
+                ],
+            ));
+
+            self.activate_action(
+                "chat-history.scroll-to-message",
+                Some(&message.id().to_variant()),
+            )
+            .unwrap();
+        }
+
+        drop(results);
+        self.update_navigation();
+    }
+
+    fn update_navigation(&self) {
+        let imp = self.imp();
+        let n_results = imp.results.borrow().len();
+
+        match imp.current_index.get() {
+            Some(index) => {
+                let has_older_results = index + 1 < n_results
+                    || (imp.next_from_message_id.get() != 0 && !imp.is_loading_more.get());
+                imp.previous_button.set_sensitive(has_older_results);
+                imp.next_button.set_sensitive(index > 0);
+            }
+            None => {
+                imp.previous_button.set_sensitive(false);
+                imp.next_button.set_sensitive(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::enums::InputMessageContent;
+use tdlib::enums::PollType;
+use tdlib::functions;
+use tdlib::types::FormattedText;
+use tdlib::types::InputMessagePoll;
+use tdlib::types::PollTypeQuiz;
+use tdlib::types::PollTypeRegular;
+
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::utils::spawn;
+
+/// The maximum number of options supported by Telegram.
+const MAX_OPTIONS: usize = 10;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $ContentCreatePollWindow : Adw.Window {
+        modal: true;
+        title: _("New Poll");
+        default-width: 400;
+        default-height: 640;
+
+        content: Adw.ToolbarView {
+            [top]
+            Adw.HeaderBar {
+                show-start-title-buttons: false;
+                show-end-title-buttons: false;
+
+                [start]
+                Button {
+                    label: _("_Cancel");
+                    use-underline: true;
+                    action-name: "window.close";
+                }
+
+                [end]
+                Button create_button {
+                    label: _("C_reate");
+                    use-underline: true;
+                    sensitive: false;
+                    clicked => $on_create_clicked() swapped;
+
+                    styles ["suggested-action"]
+                }
+            }
+
+            content: Adw.PreferencesPage {
+                Adw.PreferencesGroup {
+                    title: _("Question");
+
+                    Adw.EntryRow question_row {
+                        title: _("Ask a Question");
+                        changed => $update_create_button() swapped;
+                    }
+                }
+
+                Adw.PreferencesGroup options_group {
+                    title: _("Options");
+
+                    header-suffix: Button add_option_button {
+                        icon-name: "list-add-symbolic";
+                        tooltip-text: _("Add Option");
+                        valign: center;
+                        clicked => $on_add_option_clicked() swapped;
+
+                        styles ["flat"]
+                    };
+                }
+
+                Adw.PreferencesGroup {
+                    title: _("Settings");
+
+                    Adw.SwitchRow anonymous_row {
+                        title: _("Anonymous Voting");
+                        active: true;
+                    }
+
+                    Adw.SwitchRow multiple_answers_row {
+                        title: _("Multiple Answers");
+                        sensitive: bind quiz_row.active inverted;
+                    }
+
+                    Adw.SwitchRow quiz_row {
+                        title: _("Quiz Mode");
+                        subtitle: _("Quizzes have one correct answer");
+                        notify::active => $on_quiz_mode_changed() swapped;
+                    }
+                }
+
+                Adw.PreferencesGroup {
+                    title: _("Explanation");
+                    visible: bind quiz_row.active;
+
+                    Adw.EntryRow explanation_row {
+                        title: _("Shown After Answering");
+                    }
+                }
+            };
+        };
+    }
+    "#)]
+    pub(crate) struct CreatePollWindow {
+        pub(super) chat: OnceCell<Chat>,
+        /// The rows of the options, with the check buttons marking the correct answer of
+        /// a quiz and the buttons removing them
+        pub(super) option_rows: RefCell<Vec<(adw::EntryRow, gtk::CheckButton, gtk::Button)>>,
+        #[template_child]
+        pub(super) create_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) question_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) options_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) add_option_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) anonymous_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) multiple_answers_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) quiz_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) explanation_row: TemplateChild<adw::EntryRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CreatePollWindow {
+        const NAME: &'static str = "ContentCreatePollWindow";
+        type Type = super::CreatePollWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CreatePollWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // Polls need at least two options
+            let obj = self.obj();
+            obj.add_option();
+            obj.add_option();
+        }
+    }
+
+    impl WidgetImpl for CreatePollWindow {}
+    impl WindowImpl for CreatePollWindow {}
+    impl AdwWindowImpl for CreatePollWindow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct CreatePollWindow(ObjectSubclass<imp::CreatePollWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+#[gtk::template_callbacks]
+impl CreatePollWindow {
+    pub(crate) fn new(parent: &gtk::Window, chat: &Chat) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+
+        obj.imp().chat.set(chat.clone()).unwrap();
+
+        // Only anonymous polls are allowed in channels
+        if matches!(chat.type_(), ChatType::Supergroup(supergroup) if supergroup.is_channel()) {
+            obj.imp().anonymous_row.set_sensitive(false);
+        }
+
+        obj
+    }
+
+    fn add_option(&self) {
+        let imp = self.imp();
+
+        let correct_check = gtk::CheckButton::builder()
+            .valign(gtk::Align::Center)
+            .tooltip_text(gettext("Correct Answer"))
+            .visible(imp.quiz_row.is_active())
+            .build();
+        if let Some((_, first_check, _)) = imp.option_rows.borrow().first() {
+            correct_check.set_group(Some(first_check));
+        }
+        correct_check.connect_toggled(clone!(@weak self as obj => move |_| {
+            obj.update_create_button();
+        }));
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(gettext("Remove Option"))
+            .valign(gtk::Align::Center)
+            .build();
+        remove_button.add_css_class("flat");
+
+        let row = adw::EntryRow::builder().title(gettext("Option")).build();
+        row.add_prefix(&correct_check);
+        row.add_suffix(&remove_button);
+        row.connect_changed(clone!(@weak self as obj => move |_| {
+            obj.update_create_button();
+        }));
+
+        remove_button.connect_clicked(clone!(@weak self as obj, @weak row => move |_| {
+            obj.remove_option(&row);
+        }));
+
+        imp.options_group.add(&row);
+        imp.option_rows
+            .borrow_mut()
+            .push((row.clone(), correct_check, remove_button));
+
+        self.update_options();
+        row.grab_focus();
+    }
+
+    fn remove_option(&self, row: &adw::EntryRow) {
+        let imp = self.imp();
+
+        imp.option_rows.borrow_mut().retain(|(r, _, _)| r != row);
+        imp.options_group.remove(row);
+
+        self.update_options();
+    }
+
+    fn update_options(&self) {
+        let imp = self.imp();
+        let option_rows = imp.option_rows.borrow();
+
+        imp.add_option_button
+            .set_sensitive(option_rows.len() < MAX_OPTIONS);
+
+        // Polls need at least two options
+        let can_remove = option_rows.len() > 2;
+        for (_, _, remove_button) in option_rows.iter() {
+            remove_button.set_visible(can_remove);
+        }
+        drop(option_rows);
+
+        self.update_create_button();
+    }
+
+    #[template_callback]
+    fn on_add_option_clicked(&self) {
+        self.add_option();
+    }
+
+    #[template_callback]
+    fn on_quiz_mode_changed(&self) {
+        let imp = self.imp();
+        let is_quiz = imp.quiz_row.is_active();
+
+        // Quizzes have a single correct answer
+        if is_quiz {
+            imp.multiple_answers_row.set_active(false);
+        }
+
+        for (_, correct_check, _) in imp.option_rows.borrow().iter() {
+            correct_check.set_visible(is_quiz);
+        }
+
+        self.update_create_button();
+    }
+
+    #[template_callback]
+    fn update_create_button(&self) {
+        self.imp()
+            .create_button
+            .set_sensitive(self.input_message_poll().is_some());
+    }
+
+    /// Returns the poll to send, if everything needed has been filled in.
+    fn input_message_poll(&self) -> Option<InputMessagePoll> {
+        let imp = self.imp();
+
+        let question = imp.question_row.text().trim().to_string();
+        if question.is_empty() {
+            return None;
+        }
+
+        let mut options = Vec::new();
+        let mut correct_option_id = None;
+        for (row, correct_check, _) in imp.option_rows.borrow().iter() {
+            let text = row.text().trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+
+            if correct_check.is_active() {
+                correct_option_id = Some(options.len() as i32);
+            }
+            options.push(text);
+        }
+
+        if options.len() < 2 {
+            return None;
+        }
+
+        let r#type = if imp.quiz_row.is_active() {
+            PollType::Quiz(PollTypeQuiz {
+                correct_option_id: correct_option_id?,
+                explanation: FormattedText {
+                    text: imp.explanation_row.text().trim().to_string(),
+                    ..Default::default()
+                },
+            })
+        } else {
+            PollType::Regular(PollTypeRegular {
+                allow_multiple_answers: imp.multiple_answers_row.is_active(),
+            })
+        };
+
+        Some(InputMessagePoll {
+            question,
+            options,
+            is_anonymous: imp.anonymous_row.is_active(),
+            r#type,
+            open_period: 0,
+            close_date: 0,
+            is_closed: false,
+        })
+    }
+
+    #[template_callback]
+    fn on_create_clicked(&self) {
+        let Some(poll) = self.input_message_poll() else {
+            return;
+        };
+
+        let chat = self.imp().chat.get().unwrap().clone();
+        self.close();
+
+        spawn(async move {
+            let result = functions::send_message(
+                chat.id(),
+                0,
+                0,
+                None,
+                InputMessageContent::InputMessagePoll(poll),
+                chat.session().client_id(),
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::warn!("Error sending a poll: {e:?}");
+            }
+        });
+    }
+}
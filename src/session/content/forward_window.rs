@@ -0,0 +1,324 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::enums::MessageContent;
+
+use crate::expressions;
+use crate::i18n::ngettext_f;
+use crate::session::sidebar::Row as SidebarRow;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatList;
+use crate::tdlib::ChatListItem;
+use crate::tdlib::Message;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $ContentForwardWindow : Adw.Window {
+        modal: true;
+        title: _("Forward Messages");
+        default-width: 400;
+        default-height: 640;
+
+        content: Adw.ToolbarView {
+            [top]
+            Adw.HeaderBar {
+                show-start-title-buttons: false;
+                show-end-title-buttons: false;
+
+                title-widget: SearchEntry search_entry {
+                    placeholder-text: _("Search chats");
+                    search-changed => $on_search_changed() swapped;
+                };
+
+                [start]
+                Button {
+                    label: _("_Cancel");
+                    use-underline: true;
+                    action-name: "window.close";
+                }
+
+                [end]
+                Button forward_button {
+                    label: _("_Forward");
+                    use-underline: true;
+                    sensitive: false;
+                    clicked => $on_forward_clicked() swapped;
+
+                    styles ["suggested-action"]
+                }
+            }
+
+            content: ScrolledWindow {
+                vexpand: true;
+                hscrollbar-policy: never;
+
+                ListView list_view {
+                    styles ["navigation-sidebar"]
+                }
+            };
+
+            [bottom]
+            ListBox {
+                selection-mode: none;
+                margin-top: 12;
+                margin-bottom: 12;
+                margin-start: 12;
+                margin-end: 12;
+
+                Adw.SwitchRow hide_sender_row {
+                    title: _("Hide Sender Name");
+                }
+
+                Adw.SwitchRow hide_captions_row {
+                    title: _("Hide Captions");
+                    visible: false;
+                    sensitive: bind hide_sender_row.active;
+                }
+
+                styles ["boxed-list"]
+            }
+        };
+    }
+    "#)]
+    pub(crate) struct ForwardWindow {
+        pub(super) messages: OnceCell<Vec<Message>>,
+        pub(super) filter: OnceCell<gtk::StringFilter>,
+        /// The chats to forward the messages to, in the order they have been selected
+        pub(super) selected_chats: RefCell<Vec<Chat>>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) forward_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
+        pub(super) hide_sender_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub(super) hide_captions_row: TemplateChild<adw::SwitchRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForwardWindow {
+        const NAME: &'static str = "ContentForwardWindow";
+        type Type = super::ForwardWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ForwardWindow {}
+    impl WidgetImpl for ForwardWindow {}
+    impl WindowImpl for ForwardWindow {}
+    impl AdwWindowImpl for ForwardWindow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct ForwardWindow(ObjectSubclass<imp::ForwardWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+#[gtk::template_callbacks]
+impl ForwardWindow {
+    /// Creates a new window to forward the specified messages, which must all belong to
+    /// the same chat.
+    pub(crate) fn new(parent: &gtk::Window, messages: Vec<Message>) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        // Captions can only be removed when the sender name is hidden too, so only
+        // show the option if there's something to remove
+        imp.hide_captions_row
+            .set_visible(messages.iter().any(has_caption));
+
+        let session = messages[0].chat().session();
+        imp.messages.set(messages).unwrap();
+
+        // Every chat is either in the main chat list or in the archive, including the ones
+        // of the chat folders
+        let chat_lists = gio::ListStore::new::<ChatList>();
+        chat_lists.append(session.main_chat_list());
+        chat_lists.append(session.archive_chat_list());
+        obj.setup_list(gtk::FlattenListModel::new(Some(chat_lists)).upcast_ref());
+
+        obj
+    }
+
+    fn setup_list(&self, chat_list: &gio::ListModel) {
+        let imp = self.imp();
+
+        let chat_expression = gtk::PropertyExpression::new(
+            ChatListItem::static_type(),
+            gtk::Expression::NONE,
+            "chat",
+        );
+        let filter = gtk::StringFilter::builder()
+            .expression(expressions::chat_display_name(chat_expression.upcast_ref()))
+            .match_mode(gtk::StringFilterMatchMode::Substring)
+            .ignore_case(true)
+            .build();
+
+        // Hide the chats in which we can't send messages
+        let writable_filter = gtk::CustomFilter::new(|item| {
+            item.downcast_ref::<ChatListItem>()
+                .unwrap()
+                .chat()
+                .can_send_messages()
+        });
+
+        let every_filter = gtk::EveryFilter::new();
+        every_filter.append(writable_filter);
+        every_filter.append(filter.clone());
+
+        let model = gtk::FilterListModel::new(Some(chat_list.clone()), Some(every_filter));
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+
+            // The whole row acts as the label of the check button, so that it can be
+            // toggled by clicking anywhere on it
+            let check_button = gtk::CheckButton::builder()
+                .child(&SidebarRow::new())
+                .build();
+            check_button.connect_toggled(clone!(@weak obj, @weak list_item => move |check_button| {
+                if let Some(item) = list_item.item().and_downcast::<ChatListItem>() {
+                    obj.set_chat_selected(&item.chat(), check_button.is_active());
+                }
+            }));
+
+            list_item.set_child(Some(&check_button));
+            list_item.set_activatable(false);
+        }));
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let item = list_item.item().and_downcast::<ChatListItem>().unwrap();
+            let check_button = list_item.child().and_downcast::<gtk::CheckButton>().unwrap();
+
+            check_button
+                .child()
+                .and_downcast::<SidebarRow>()
+                .unwrap()
+                .set_item(Some(item.clone()));
+            check_button.set_active(obj.imp().selected_chats.borrow().contains(&item.chat()));
+        }));
+        factory.connect_unbind(|_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let check_button = list_item
+                .child()
+                .and_downcast::<gtk::CheckButton>()
+                .unwrap();
+
+            check_button
+                .child()
+                .and_downcast::<SidebarRow>()
+                .unwrap()
+                .set_item(None);
+        });
+
+        imp.list_view
+            .set_model(Some(&gtk::NoSelection::new(Some(model))));
+        imp.list_view.set_factory(Some(&factory));
+        imp.filter.set(filter).unwrap();
+    }
+
+    fn set_chat_selected(&self, chat: &Chat, selected: bool) {
+        let imp = self.imp();
+
+        {
+            let mut selected_chats = imp.selected_chats.borrow_mut();
+            let position = selected_chats.iter().position(|c| c == chat);
+            match (position, selected) {
+                (None, true) => selected_chats.push(chat.clone()),
+                (Some(position), false) => {
+                    selected_chats.remove(position);
+                }
+                _ => return,
+            }
+        }
+
+        let n_chats = imp.selected_chats.borrow().len();
+        imp.forward_button.set_sensitive(n_chats > 0);
+        imp.forward_button.set_label(&if n_chats > 1 {
+            ngettext_f(
+                "_Forward to {num} Chat",
+                "_Forward to {num} Chats",
+                n_chats as u32,
+                &[("num", &n_chats.to_string())],
+            )
+        } else {
+            gettext("_Forward")
+        });
+    }
+
+    #[template_callback]
+    fn on_search_changed(&self) {
+        let imp = self.imp();
+        imp.filter
+            .get()
+            .unwrap()
+            .set_search(Some(imp.search_entry.text().as_str()));
+    }
+
+    #[template_callback]
+    fn on_forward_clicked(&self) {
+        let imp = self.imp();
+        let messages = imp.messages.get().unwrap();
+        let from_chat = messages[0].chat();
+        let message_ids: Vec<i64> = messages.iter().map(Message::id).collect();
+
+        let send_copy = imp.hide_sender_row.is_active();
+        let remove_caption = send_copy && imp.hide_captions_row.is_active();
+
+        for chat in imp.selected_chats.take() {
+            let from_chat = from_chat.clone();
+            let message_ids = message_ids.clone();
+            spawn(async move {
+                let result = chat
+                    .forward_messages(&from_chat, message_ids, send_copy, remove_caption)
+                    .await;
+                if let Err(e) = result {
+                    log::warn!("Error forwarding messages: {e:?}");
+                }
+            });
+        }
+
+        self.close();
+    }
+}
+
+fn has_caption(message: &Message) -> bool {
+    let caption = match message.content().0 {
+        MessageContent::MessageAnimation(data) => data.caption,
+        MessageContent::MessageAudio(data) => data.caption,
+        MessageContent::MessageDocument(data) => data.caption,
+        MessageContent::MessagePhoto(data) => data.caption,
+        MessageContent::MessageVideo(data) => data.caption,
+        MessageContent::MessageVoiceNote(data) => data.caption,
+        _ => return false,
+    };
+
+    !caption.text.is_empty()
+}
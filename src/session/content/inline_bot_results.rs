@@ -0,0 +1,647 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use glib::clone;
+use glib::subclass::Signal;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums;
+use tdlib::enums::InlineQueryResult;
+use tdlib::enums::StickerFormat;
+use tdlib::enums::UserType;
+use tdlib::functions;
+use tdlib::types::File;
+
+use crate::components::Sticker;
+use crate::session::content::AnimationPreview;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+use crate::Session;
+
+/// The time to wait after the last change of the query before sending it to the bot.
+const QUERY_DELAY: Duration = Duration::from_millis(400);
+const GALLERY_ITEM_SIZE: i32 = 96;
+const THUMBNAIL_SIZE: i32 = 40;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentInlineBotResults {
+        layout-manager: BinLayout {};
+
+        ScrolledWindow scrolled_window {
+            hscrollbar-policy: never;
+            propagate-natural-height: true;
+            max-content-height: 280;
+            edge-reached => $on_scrolled_window_edge_reached() swapped;
+
+            child: Box {
+                orientation: vertical;
+
+                FlowBox gallery_flow_box {
+                    selection-mode: none;
+                    homogeneous: true;
+                    max-children-per-line: 8;
+                    child-activated => $on_gallery_flow_box_child_activated() swapped;
+                }
+
+                ListBox list_box {
+                    selection-mode: none;
+                    row-activated => $on_list_box_row_activated() swapped;
+                }
+            };
+        }
+    }
+    "#)]
+    pub(crate) struct InlineBotResults {
+        pub(super) chat: glib::WeakRef<Chat>,
+        /// The username of the last bot resolved, along with its user id
+        pub(super) bot: RefCell<Option<(String, i64)>>,
+        pub(super) query: RefCell<String>,
+        pub(super) next_offset: RefCell<String>,
+        pub(super) is_loading: Cell<bool>,
+        /// The ids of the results shown in the gallery, in the same order, along with the
+        /// id of the query of their page
+        pub(super) gallery_result_ids: RefCell<Vec<(i64, String)>>,
+        /// The ids of the results shown in the list, in the same order, along with the id
+        /// of the query of their page
+        pub(super) list_result_ids: RefCell<Vec<(i64, String)>>,
+        pub(super) query_source_id: RefCell<Option<glib::SourceId>>,
+        pub(super) request_generation: RequestGeneration,
+        #[template_child]
+        pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) gallery_flow_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for InlineBotResults {
+        const NAME: &'static str = "ContentInlineBotResults";
+        type Type = super::InlineBotResults;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.set_css_name("inlinebotresults");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for InlineBotResults {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("result-picked")
+                    .param_types([i64::static_type(), String::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecBoolean::builder("has-results")
+                    .read_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "has-results" => obj.has_results().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.query_source_id.take() {
+                source_id.remove();
+            }
+            self.scrolled_window.unparent();
+        }
+    }
+
+    impl WidgetImpl for InlineBotResults {}
+}
+
+glib::wrapper! {
+    pub(crate) struct InlineBotResults(ObjectSubclass<imp::InlineBotResults>)
+        @extends gtk::Widget;
+}
+
+impl Default for InlineBotResults {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[gtk::template_callbacks]
+impl InlineBotResults {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Sends the query to the inline bot with the specified username, once the user
+    /// stops typing it.
+    pub(crate) fn search(&self, chat: &Chat, bot_username: String, query: String) {
+        let imp = self.imp();
+
+        if imp.chat.upgrade().as_ref() == Some(chat)
+            && imp
+                .bot
+                .borrow()
+                .as_ref()
+                .is_some_and(|(username, _)| *username == bot_username)
+            && *imp.query.borrow() == query
+        {
+            return;
+        }
+
+        imp.chat.set(Some(chat));
+        imp.query.replace(query);
+
+        let generation = imp.request_generation.next();
+
+        if let Some(source_id) = imp.query_source_id.take() {
+            source_id.remove();
+        }
+
+        let source_id = glib::timeout_add_local_once(
+            QUERY_DELAY,
+            clone!(@weak self as obj => move || {
+                obj.imp().query_source_id.replace(None);
+
+                spawn(clone!(@weak obj => async move {
+                    obj.send_query(bot_username, generation).await;
+                }));
+            }),
+        );
+        imp.query_source_id.replace(Some(source_id));
+    }
+
+    /// Cancels the pending query and removes the results.
+    pub(crate) fn clear(&self) {
+        let imp = self.imp();
+
+        imp.request_generation.next();
+        imp.query.replace(String::new());
+        imp.is_loading.set(false);
+
+        if let Some(source_id) = imp.query_source_id.take() {
+            source_id.remove();
+        }
+
+        self.set_results(0, String::new(), false, vec![]);
+    }
+
+    pub(crate) fn has_results(&self) -> bool {
+        let imp = self.imp();
+        !imp.gallery_result_ids.borrow().is_empty() || !imp.list_result_ids.borrow().is_empty()
+    }
+
+    async fn send_query(&self, bot_username: String, generation: u32) {
+        let imp = self.imp();
+
+        let Some(chat) = imp.chat.upgrade() else {
+            return;
+        };
+
+        let Some(bot_user_id) = self
+            .resolve_inline_bot(&chat.session(), &bot_username)
+            .await
+        else {
+            if imp.request_generation.is_current(generation) {
+                self.set_results(0, String::new(), false, vec![]);
+            }
+            return;
+        };
+
+        if !imp.request_generation.is_current(generation) {
+            return;
+        }
+
+        imp.is_loading.set(true);
+
+        let query = imp.query.borrow().clone();
+        let result = functions::get_inline_query_results(
+            bot_user_id,
+            chat.id(),
+            None,
+            query,
+            String::new(),
+            chat.session().client_id(),
+        )
+        .await;
+
+        if !imp.request_generation.is_current(generation) {
+            return;
+        }
+
+        imp.is_loading.set(false);
+
+        match result {
+            Ok(enums::InlineQueryResults::InlineQueryResults(data)) => self.set_results(
+                data.inline_query_id,
+                data.next_offset,
+                data.is_gallery,
+                data.results,
+            ),
+            Err(e) => {
+                log::warn!("Error getting the results of an inline query: {e:?}");
+                self.set_results(0, String::new(), false, vec![]);
+            }
+        }
+    }
+
+    /// Returns the user id of the bot with the specified username, if it supports
+    /// inline queries.
+    async fn resolve_inline_bot(&self, session: &Session, bot_username: &str) -> Option<i64> {
+        let imp = self.imp();
+
+        if let Some((username, user_id)) = &*imp.bot.borrow() {
+            if username == bot_username {
+                return Some(*user_id);
+            }
+        }
+
+        let result =
+            functions::search_public_chat(bot_username.to_owned(), session.client_id()).await;
+        let chat = match result {
+            Ok(enums::Chat::Chat(data)) => session.chat(data.id),
+            Err(_) => return None,
+        };
+
+        let ChatType::Private(user) = chat.type_() else {
+            return None;
+        };
+        match user.type_().0 {
+            UserType::Bot(data) if data.is_inline => {
+                imp.bot.replace(Some((bot_username.to_owned(), user.id())));
+                Some(user.id())
+            }
+            _ => None,
+        }
+    }
+
+    /// Loads the next page of results, if any.
+    fn load_more(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading.get() || imp.next_offset.borrow().is_empty() {
+            return;
+        }
+        let Some(chat) = imp.chat.upgrade() else {
+            return;
+        };
+        let Some(bot_user_id) = imp.bot.borrow().as_ref().map(|(_, user_id)| *user_id) else {
+            return;
+        };
+
+        imp.is_loading.set(true);
+
+        let generation = imp.request_generation.current();
+        let query = imp.query.borrow().clone();
+        let offset = imp.next_offset.borrow().clone();
+        let client_id = chat.session().client_id();
+        let chat_id = chat.id();
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = functions::get_inline_query_results(
+                bot_user_id,
+                chat_id,
+                None,
+                query,
+                offset,
+                client_id,
+            )
+            .await;
+
+            let imp = obj.imp();
+            if !imp.request_generation.is_current(generation) {
+                return;
+            }
+
+            imp.is_loading.set(false);
+
+            match result {
+                Ok(enums::InlineQueryResults::InlineQueryResults(data)) => {
+                    imp.next_offset.replace(data.next_offset);
+                    obj.append_results(data.inline_query_id, data.is_gallery, data.results);
+                }
+                Err(e) => log::warn!("Error getting more results of an inline query: {e:?}"),
+            }
+        }));
+    }
+
+    fn set_results(
+        &self,
+        query_id: i64,
+        next_offset: String,
+        is_gallery: bool,
+        results: Vec<InlineQueryResult>,
+    ) {
+        let imp = self.imp();
+
+        imp.next_offset.replace(next_offset);
+
+        while let Some(child) = imp.gallery_flow_box.first_child() {
+            imp.gallery_flow_box.remove(&child);
+        }
+        while let Some(child) = imp.list_box.first_child() {
+            imp.list_box.remove(&child);
+        }
+        imp.gallery_result_ids.borrow_mut().clear();
+        imp.list_result_ids.borrow_mut().clear();
+
+        imp.scrolled_window.vadjustment().set_value(0.0);
+
+        self.append_results(query_id, is_gallery, results);
+    }
+
+    fn append_results(&self, query_id: i64, is_gallery: bool, results: Vec<InlineQueryResult>) {
+        let imp = self.imp();
+
+        if let Some(session) = imp.chat.upgrade().map(|chat| chat.session()) {
+            for result in results {
+                let gallery_item = if is_gallery {
+                    gallery_item(&result, &session)
+                } else {
+                    None
+                };
+
+                if let Some(gallery_item) = gallery_item {
+                    imp.gallery_flow_box.append(&gallery_item);
+                    imp.gallery_result_ids
+                        .borrow_mut()
+                        .push((query_id, result_id(&result).to_owned()));
+                } else {
+                    imp.list_box.append(&list_row(&result, &session));
+                    imp.list_result_ids
+                        .borrow_mut()
+                        .push((query_id, result_id(&result).to_owned()));
+                }
+            }
+        }
+
+        imp.gallery_flow_box
+            .set_visible(!imp.gallery_result_ids.borrow().is_empty());
+        imp.list_box
+            .set_visible(!imp.list_result_ids.borrow().is_empty());
+
+        self.notify("has-results");
+    }
+
+    #[template_callback]
+    fn on_scrolled_window_edge_reached(&self, position: gtk::PositionType) {
+        if position == gtk::PositionType::Bottom {
+            self.load_more();
+        }
+    }
+
+    #[template_callback]
+    fn on_gallery_flow_box_child_activated(&self, child: &gtk::FlowBoxChild) {
+        let result = self
+            .imp()
+            .gallery_result_ids
+            .borrow()
+            .get(child.index() as usize)
+            .cloned();
+        if let Some((query_id, result_id)) = result {
+            self.emit_result_picked(query_id, result_id);
+        }
+    }
+
+    #[template_callback]
+    fn on_list_box_row_activated(&self, row: &gtk::ListBoxRow) {
+        let result = self
+            .imp()
+            .list_result_ids
+            .borrow()
+            .get(row.index() as usize)
+            .cloned();
+        if let Some((query_id, result_id)) = result {
+            self.emit_result_picked(query_id, result_id);
+        }
+    }
+
+    fn emit_result_picked(&self, query_id: i64, result_id: String) {
+        self.emit_by_name::<()>("result-picked", &[&query_id, &result_id]);
+    }
+
+    pub(crate) fn connect_result_picked<F: Fn(&Self, i64, String) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("result-picked", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let query_id = values[1].get::<i64>().unwrap();
+            let result_id = values[2].get::<String>().unwrap();
+            f(&obj, query_id, result_id);
+
+            None
+        })
+    }
+}
+
+fn result_id(result: &InlineQueryResult) -> &str {
+    match result {
+        InlineQueryResult::Article(data) => &data.id,
+        InlineQueryResult::Contact(data) => &data.id,
+        InlineQueryResult::Location(data) => &data.id,
+        InlineQueryResult::Venue(data) => &data.id,
+        InlineQueryResult::Game(data) => &data.id,
+        InlineQueryResult::Animation(data) => &data.id,
+        InlineQueryResult::Audio(data) => &data.id,
+        InlineQueryResult::Document(data) => &data.id,
+        InlineQueryResult::Photo(data) => &data.id,
+        InlineQueryResult::Sticker(data) => &data.id,
+        InlineQueryResult::Video(data) => &data.id,
+        InlineQueryResult::VoiceNote(data) => &data.id,
+    }
+}
+
+/// Returns the title, the description and the file of the thumbnail of a result, as
+/// shown in the list of results.
+fn result_info(result: &InlineQueryResult) -> (String, String, Option<File>) {
+    match result {
+        InlineQueryResult::Article(data) => (
+            data.title.clone(),
+            data.description.clone(),
+            data.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Contact(data) => (
+            format!("{} {}", data.contact.first_name, data.contact.last_name)
+                .trim()
+                .to_owned(),
+            data.contact.phone_number.clone(),
+            data.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Location(data) => (
+            data.title.clone(),
+            format!("{}, {}", data.location.latitude, data.location.longitude),
+            data.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Venue(data) => (
+            data.venue.title.clone(),
+            data.venue.address.clone(),
+            data.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Game(data) => {
+            (data.game.title.clone(), data.game.description.clone(), None)
+        }
+        InlineQueryResult::Animation(data) => (
+            data.title.clone(),
+            String::new(),
+            data.animation.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Audio(data) => (
+            data.audio.title.clone(),
+            data.audio.performer.clone(),
+            data.audio
+                .album_cover_thumbnail
+                .as_ref()
+                .map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Document(data) => (
+            data.title.clone(),
+            data.description.clone(),
+            data.document.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Photo(data) => (
+            data.title.clone(),
+            data.description.clone(),
+            data.photo.sizes.first().map(|s| s.photo.clone()),
+        ),
+        InlineQueryResult::Sticker(data) => (
+            data.sticker.emoji.clone(),
+            String::new(),
+            data.sticker.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::Video(data) => (
+            data.title.clone(),
+            data.description.clone(),
+            data.video.thumbnail.as_ref().map(|t| t.file.clone()),
+        ),
+        InlineQueryResult::VoiceNote(data) => (data.title.clone(), String::new(), None),
+    }
+}
+
+/// Creates the widget of a result shown in the gallery, if the result is a media that
+/// can be previewed.
+fn gallery_item(result: &InlineQueryResult, session: &Session) -> Option<gtk::Widget> {
+    let widget = match result {
+        InlineQueryResult::Animation(data) => {
+            let preview = AnimationPreview::new();
+            preview.set_animation(data.animation.clone(), session);
+            preview.upcast()
+        }
+        InlineQueryResult::Sticker(data)
+            if matches!(
+                data.sticker.format,
+                StickerFormat::Webp | StickerFormat::Tgs
+            ) =>
+        {
+            let sticker = Sticker::new();
+            sticker.set_longer_side_size(GALLERY_ITEM_SIZE);
+            sticker.update_sticker(data.sticker.clone(), false, session.clone());
+            sticker.upcast()
+        }
+        InlineQueryResult::Photo(data) => {
+            // Pick the smallest size that still fills the item
+            let size = data
+                .photo
+                .sizes
+                .iter()
+                .find(|s| s.width.min(s.height) >= GALLERY_ITEM_SIZE)
+                .or(data.photo.sizes.last())?;
+            thumbnail_picture(size.photo.clone(), session).upcast()
+        }
+        InlineQueryResult::Video(data) => {
+            thumbnail_picture(data.video.thumbnail.as_ref()?.file.clone(), session).upcast()
+        }
+        _ => return None,
+    };
+
+    widget.set_size_request(GALLERY_ITEM_SIZE, GALLERY_ITEM_SIZE);
+    widget.set_overflow(gtk::Overflow::Hidden);
+
+    Some(widget)
+}
+
+fn list_row(result: &InlineQueryResult, session: &Session) -> gtk::ListBoxRow {
+    let (title, description, thumbnail) = result_info(result);
+
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 9);
+
+    if let Some(thumbnail) = thumbnail {
+        let picture = thumbnail_picture(thumbnail, session);
+        picture.set_size_request(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+        picture.set_overflow(gtk::Overflow::Hidden);
+        picture.set_valign(gtk::Align::Center);
+        picture.add_css_class("thumbnail");
+        content.append(&picture);
+    }
+
+    let labels = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    labels.set_valign(gtk::Align::Center);
+
+    let title_label = gtk::Label::builder()
+        .label(title)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .xalign(0.0)
+        .css_classes(["heading"])
+        .build();
+    labels.append(&title_label);
+
+    if !description.is_empty() {
+        let description_label = gtk::Label::builder()
+            .label(description)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .lines(2)
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["caption", "dim-label"])
+            .build();
+        labels.append(&description_label);
+    }
+
+    content.append(&labels);
+
+    gtk::ListBoxRow::builder().child(&content).build()
+}
+
+/// Creates a picture showing the specified file, downloading it if needed.
+fn thumbnail_picture(file: File, session: &Session) -> gtk::Picture {
+    let picture = gtk::Picture::builder()
+        .content_fit(gtk::ContentFit::Cover)
+        .build();
+
+    if file.local.is_downloading_completed {
+        picture.set_filename(Some(&file.local.path));
+    } else {
+        spawn(clone!(@weak picture, @weak session => async move {
+            match session.download_file(file.id).await {
+                Ok(file) => picture.set_filename(Some(&file.local.path)),
+                Err(e) => log::warn!("Error downloading a thumbnail: {e:?}"),
+            }
+        }));
+    }
+
+    picture
+}
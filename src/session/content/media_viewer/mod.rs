@@ -0,0 +1,601 @@
+mod zoomable_picture;
+
+use std::cell::Cell;
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::enums::MessageContent;
+use tdlib::enums::SearchMessagesFilter;
+use tdlib::types::File;
+use tdlib::types::FormattedText;
+use tdlib::types::Minithumbnail;
+
+use self::zoomable_picture::ZoomablePicture;
+use super::shared_media::save_message_file;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::utils::decode_image_from_path;
+use crate::utils::decode_minithumbnail;
+use crate::utils::spawn;
+
+const PAGE_SIZE: i32 = 20;
+/// How close to the first or the last loaded message we have to get before loading more.
+const LOAD_THRESHOLD: usize = 5;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $ContentMediaViewer : Adw.Window {
+        default-width: 960;
+        default-height: 720;
+
+        styles ["media-viewer"]
+
+        content: Adw.ToolbarView {
+            extend-content-to-top-edge: true;
+
+            [top]
+            Adw.HeaderBar {
+                title-widget: Adw.WindowTitle window_title {};
+
+                [start]
+                Button {
+                    icon-name: "object-rotate-left-symbolic";
+                    tooltip-text: _("Rotate Left");
+                    action-name: "media-viewer.rotate-left";
+                }
+
+                [start]
+                Button {
+                    icon-name: "object-rotate-right-symbolic";
+                    tooltip-text: _("Rotate Right");
+                    action-name: "media-viewer.rotate-right";
+                }
+
+                [end]
+                MenuButton {
+                    icon-name: "view-more-symbolic";
+                    menu-model: menu;
+                    primary: true;
+                }
+
+                [end]
+                Button {
+                    icon-name: "zoom-in-symbolic";
+                    tooltip-text: _("Zoom In");
+                    action-name: "media-viewer.zoom-in";
+                }
+
+                [end]
+                Button {
+                    icon-name: "zoom-out-symbolic";
+                    tooltip-text: _("Zoom Out");
+                    action-name: "media-viewer.zoom-out";
+                }
+
+                styles ["osd"]
+            }
+
+            content: Overlay {
+                $ContentMediaViewerZoomablePicture picture {
+                    hexpand: true;
+                    vexpand: true;
+                }
+
+                [overlay]
+                Spinner spinner {
+                    halign: center;
+                    valign: center;
+                    width-request: 32;
+                    height-request: 32;
+                }
+
+                [overlay]
+                Button {
+                    halign: start;
+                    valign: center;
+                    margin-start: 12;
+                    icon-name: "go-previous-symbolic";
+                    tooltip-text: _("Previous");
+                    action-name: "media-viewer.previous";
+
+                    styles ["osd", "circular"]
+                }
+
+                [overlay]
+                Button {
+                    halign: end;
+                    valign: center;
+                    margin-end: 12;
+                    icon-name: "go-next-symbolic";
+                    tooltip-text: _("Next");
+                    action-name: "media-viewer.next";
+
+                    styles ["osd", "circular"]
+                }
+
+                [overlay]
+                Box {
+                    orientation: vertical;
+                    valign: end;
+                    spacing: 6;
+                    margin-start: 12;
+                    margin-end: 12;
+                    margin-bottom: 12;
+
+                    Label caption_label {
+                        halign: center;
+                        wrap: true;
+                        wrap-mode: word_char;
+                        selectable: true;
+
+                        styles ["osd", "caption-overlay"]
+                    }
+
+                    MediaControls media_controls {
+                        halign: center;
+                        width-request: 360;
+
+                        styles ["osd", "toolbar"]
+                    }
+                }
+            };
+        };
+    }
+
+    menu menu {
+        section {
+            item {
+                label: _("_Save As…");
+                action: "media-viewer.save-as";
+            }
+
+            item {
+                label: _("_Copy");
+                action: "media-viewer.copy";
+                hidden-when: "action-disabled";
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MediaViewer {
+        pub(super) chat: OnceCell<Chat>,
+        /// The loaded photos and videos, from the oldest to the newest
+        pub(super) messages: RefCell<Vec<Message>>,
+        pub(super) position: Cell<usize>,
+        pub(super) is_loading_older: Cell<bool>,
+        pub(super) is_loading_newer: Cell<bool>,
+        pub(super) is_older_exhausted: Cell<bool>,
+        pub(super) is_newer_exhausted: Cell<bool>,
+        #[template_child]
+        pub(super) window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub(super) picture: TemplateChild<ZoomablePicture>,
+        #[template_child]
+        pub(super) spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        pub(super) caption_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) media_controls: TemplateChild<gtk::MediaControls>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MediaViewer {
+        const NAME: &'static str = "ContentMediaViewer";
+        type Type = super::MediaViewer;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            ZoomablePicture::static_type();
+            klass.bind_template();
+
+            klass.install_action("media-viewer.previous", None, move |widget, _, _| {
+                widget.navigate(-1);
+            });
+            klass.install_action("media-viewer.next", None, move |widget, _, _| {
+                widget.navigate(1);
+            });
+            klass.install_action("media-viewer.zoom-in", None, move |widget, _, _| {
+                widget.imp().picture.zoom_in();
+            });
+            klass.install_action("media-viewer.zoom-out", None, move |widget, _, _| {
+                widget.imp().picture.zoom_out();
+            });
+            klass.install_action("media-viewer.rotate-left", None, move |widget, _, _| {
+                widget.imp().picture.rotate(-90);
+            });
+            klass.install_action("media-viewer.rotate-right", None, move |widget, _, _| {
+                widget.imp().picture.rotate(90);
+            });
+            klass.install_action_async("media-viewer.save-as", None, |widget, _, _| async move {
+                let message = widget.current_message();
+                save_message_file(&message, Some(widget.upcast_ref::<gtk::Window>())).await;
+            });
+            klass.install_action("media-viewer.copy", None, move |widget, _, _| {
+                widget.copy_photo();
+            });
+
+            klass.add_binding_action(
+                gdk::Key::Left,
+                gdk::ModifierType::empty(),
+                "media-viewer.previous",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::Right,
+                gdk::ModifierType::empty(),
+                "media-viewer.next",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::plus,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-in",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::minus,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-out",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::r,
+                gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK,
+                "media-viewer.rotate-left",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::r,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.rotate-right",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::s,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.save-as",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::c,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.copy",
+                None,
+            );
+            klass.add_binding_action(
+                gdk::Key::Escape,
+                gdk::ModifierType::empty(),
+                "window.close",
+                None,
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MediaViewer {
+        fn dispose(&self) {
+            self.obj().stop_video();
+        }
+    }
+
+    impl WidgetImpl for MediaViewer {}
+    impl WindowImpl for MediaViewer {}
+    impl AdwWindowImpl for MediaViewer {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MediaViewer(ObjectSubclass<imp::MediaViewer>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl MediaViewer {
+    /// Creates a new viewer showing the photo or the video of the specified message,
+    /// which allows to browse the other photos and videos of the chat.
+    pub(crate) fn new(parent: &gtk::Window, message: &Message) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        imp.chat.set(message.chat()).unwrap();
+        imp.messages.replace(vec![message.clone()]);
+
+        obj.show_current_message();
+        obj.load_older();
+        obj.load_newer();
+
+        obj
+    }
+
+    fn current_message(&self) -> Message {
+        let imp = self.imp();
+        imp.messages.borrow()[imp.position.get()].clone()
+    }
+
+    fn navigate(&self, step: isize) {
+        let imp = self.imp();
+
+        let Some(position) = imp.position.get().checked_add_signed(step) else {
+            return;
+        };
+        if position >= imp.messages.borrow().len() {
+            return;
+        }
+
+        imp.position.set(position);
+        self.show_current_message();
+
+        if position < LOAD_THRESHOLD {
+            self.load_older();
+        }
+        if position + LOAD_THRESHOLD >= imp.messages.borrow().len() {
+            self.load_newer();
+        }
+    }
+
+    fn load_older(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading_older.get() || imp.is_older_exhausted.get() {
+            return;
+        }
+
+        let chat = imp.chat.get().unwrap().clone();
+        let from_message_id = imp.messages.borrow().first().unwrap().id();
+
+        imp.is_loading_older.set(true);
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_messages(
+                    String::new(),
+                    None,
+                    from_message_id,
+                    Some(SearchMessagesFilter::PhotoAndVideo),
+                    PAGE_SIZE,
+                )
+                .await;
+
+            let imp = obj.imp();
+            imp.is_loading_older.set(false);
+
+            match result {
+                Ok(messages) => {
+                    let messages: Vec<Message> = messages
+                        .into_iter()
+                        .filter(|m| m.id() < from_message_id)
+                        .rev()
+                        .collect();
+
+                    if messages.is_empty() {
+                        imp.is_older_exhausted.set(true);
+                    }
+
+                    imp.position.set(imp.position.get() + messages.len());
+                    imp.messages.borrow_mut().splice(0..0, messages);
+                }
+                Err(e) => {
+                    log::warn!("Error loading older media: {e:?}");
+                    imp.is_older_exhausted.set(true);
+                }
+            }
+
+            obj.update_actions();
+        }));
+    }
+
+    fn load_newer(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading_newer.get() || imp.is_newer_exhausted.get() {
+            return;
+        }
+
+        let chat = imp.chat.get().unwrap().clone();
+        let from_message_id = imp.messages.borrow().last().unwrap().id();
+
+        imp.is_loading_newer.set(true);
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_newer_messages(
+                    from_message_id,
+                    Some(SearchMessagesFilter::PhotoAndVideo),
+                    PAGE_SIZE,
+                )
+                .await;
+
+            let imp = obj.imp();
+            imp.is_loading_newer.set(false);
+
+            match result {
+                Ok(messages) => {
+                    if messages.is_empty() {
+                        imp.is_newer_exhausted.set(true);
+                    }
+
+                    imp.messages
+                        .borrow_mut()
+                        .extend(messages.into_iter().rev());
+                }
+                Err(e) => {
+                    log::warn!("Error loading newer media: {e:?}");
+                    imp.is_newer_exhausted.set(true);
+                }
+            }
+
+            obj.update_actions();
+        }));
+    }
+
+    fn update_actions(&self) {
+        let imp = self.imp();
+        let position = imp.position.get();
+        let n_messages = imp.messages.borrow().len();
+
+        self.action_set_enabled("media-viewer.previous", position > 0);
+        self.action_set_enabled("media-viewer.next", position + 1 < n_messages);
+
+        // Only photos can be copied as images
+        let is_photo = matches!(
+            self.current_message().content().0,
+            MessageContent::MessagePhoto(_)
+        );
+        let is_loaded = !imp.spinner.is_spinning()
+            && imp
+                .picture
+                .paintable()
+                .map(|p| p.is::<gdk::Texture>())
+                .unwrap_or_default();
+        self.action_set_enabled("media-viewer.copy", is_photo && is_loaded);
+    }
+
+    fn show_current_message(&self) {
+        let imp = self.imp();
+        let message = self.current_message();
+
+        self.stop_video();
+
+        let date = glib::DateTime::from_unix_local(message.date() as i64)
+            // Translators: This is a date and time representation, without seconds.
+            // Here you may want to change to a 24-hours representation, based on your locale.
+            // You can use this site to learn more: https://www.strfti.me/
+            .and_then(|t| t.format(&gettext("%x %l:%M %p")))
+            .map(|t| t.trim().to_string())
+            .unwrap_or_default();
+        imp.window_title
+            .set_title(&strings::message_sender(message.sender(), true));
+        imp.window_title.set_subtitle(&date);
+
+        let Some((file, caption, minithumbnail)) = media_file(&message) else {
+            return;
+        };
+
+        imp.caption_label.set_label(&caption.text);
+        imp.caption_label.set_visible(!caption.text.is_empty());
+
+        let is_video = matches!(message.content().0, MessageContent::MessageVideo(_));
+        imp.media_controls.set_visible(is_video);
+
+        if file.local.is_downloading_completed {
+            self.load_media(&message, &file.local.path);
+        } else {
+            imp.picture.set_paintable(
+                minithumbnail
+                    .and_then(|m| decode_minithumbnail(&m))
+                    .as_ref()
+                    .map(|t| t.upcast_ref()),
+            );
+            imp.spinner.set_spinning(true);
+
+            let session = message.chat().session();
+            spawn(clone!(@weak self as obj => async move {
+                match session.download_file(file.id).await {
+                    Ok(file) => {
+                        // The user could have moved to another message in the meantime
+                        if obj.current_message() == message {
+                            obj.load_media(&message, &file.local.path);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to download a media file: {e:?}");
+                        obj.imp().spinner.set_spinning(false);
+                    }
+                }
+            }));
+        }
+
+        self.update_actions();
+    }
+
+    fn load_media(&self, message: &Message, path: &str) {
+        let imp = self.imp();
+
+        if let MessageContent::MessageVideo(_) = message.content().0 {
+            imp.spinner.set_spinning(false);
+
+            let media = gtk::MediaFile::for_filename(path);
+            imp.picture.set_paintable(Some(media.upcast_ref()));
+            imp.media_controls.set_media_stream(Some(&media));
+            media.play();
+
+            self.update_actions();
+            return;
+        }
+
+        let path = path.to_owned();
+        spawn(clone!(@weak self as obj, @weak message => async move {
+            let result = gio::spawn_blocking(move || decode_image_from_path(&path))
+                .await
+                .unwrap();
+
+            if obj.current_message() != message {
+                return;
+            }
+
+            let imp = obj.imp();
+            imp.spinner.set_spinning(false);
+
+            match result {
+                Ok(texture) => imp.picture.set_paintable(Some(texture.upcast_ref())),
+                Err(e) => log::warn!("Error decoding a photo: {e:?}"),
+            }
+
+            obj.update_actions();
+        }));
+    }
+
+    fn stop_video(&self) {
+        let media_controls = &self.imp().media_controls;
+
+        if let Some(media) = media_controls.media_stream() {
+            media.pause();
+        }
+        media_controls.set_media_stream(gtk::MediaStream::NONE);
+    }
+
+    fn copy_photo(&self) {
+        if let Some(texture) = self
+            .imp()
+            .picture
+            .paintable()
+            .and_downcast::<gdk::Texture>()
+        {
+            self.clipboard().set_texture(&texture);
+        }
+    }
+}
+
+/// Returns the file of the photo or the video of a message, along with its caption and
+/// its minithumbnail.
+fn media_file(message: &Message) -> Option<(File, FormattedText, Option<Minithumbnail>)> {
+    match message.content().0 {
+        MessageContent::MessagePhoto(mut data) => data
+            .photo
+            .sizes
+            .pop()
+            .map(|s| (s.photo, data.caption, data.photo.minithumbnail)),
+        MessageContent::MessageVideo(data) => {
+            Some((data.video.video, data.caption, data.video.minithumbnail))
+        }
+        _ => None,
+    }
+}
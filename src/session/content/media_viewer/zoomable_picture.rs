@@ -0,0 +1,260 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+use gtk::graphene;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+const MIN_ZOOM: f64 = 1.0;
+const MAX_ZOOM: f64 = 8.0;
+const ZOOM_STEP: f64 = 1.25;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug)]
+    pub(crate) struct ZoomablePicture {
+        pub(super) paintable: RefCell<Option<gdk::Paintable>>,
+        pub(super) paintable_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+        /// The zoom level, where 1.0 means that the whole paintable fits the widget
+        pub(super) zoom: Cell<f64>,
+        /// The zoom level when a zoom gesture has begun
+        pub(super) gesture_zoom: Cell<f64>,
+        /// The rotation in degrees, always a multiple of 90
+        pub(super) rotation: Cell<i32>,
+        /// The offset of the center of the paintable from the center of the widget
+        pub(super) offset: Cell<(f64, f64)>,
+        /// The offset when a drag gesture has begun
+        pub(super) drag_offset: Cell<(f64, f64)>,
+    }
+
+    impl Default for ZoomablePicture {
+        fn default() -> Self {
+            Self {
+                paintable: Default::default(),
+                paintable_handlers: Default::default(),
+                zoom: Cell::new(MIN_ZOOM),
+                gesture_zoom: Cell::new(MIN_ZOOM),
+                rotation: Default::default(),
+                offset: Default::default(),
+                drag_offset: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ZoomablePicture {
+        const NAME: &'static str = "ContentMediaViewerZoomablePicture";
+        type Type = super::ZoomablePicture;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("zoomablepicture");
+        }
+    }
+
+    impl ObjectImpl for ZoomablePicture {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.set_overflow(gtk::Overflow::Hidden);
+
+            let zoom_gesture = gtk::GestureZoom::new();
+            zoom_gesture.connect_begin(clone!(@weak obj => move |_, _| {
+                let imp = obj.imp();
+                imp.gesture_zoom.set(imp.zoom.get());
+            }));
+            zoom_gesture.connect_scale_changed(clone!(@weak obj => move |_, scale| {
+                obj.set_zoom(obj.imp().gesture_zoom.get() * scale);
+            }));
+            obj.add_controller(zoom_gesture);
+
+            let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+            scroll.connect_scroll(
+                clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, _, dy| {
+                    // Touchpads send smaller deltas, which allow a smoother zoom
+                    obj.set_zoom(obj.imp().zoom.get() * ZOOM_STEP.powf(-dy));
+                    glib::Propagation::Stop
+                }),
+            );
+            obj.add_controller(scroll);
+
+            let drag = gtk::GestureDrag::new();
+            drag.connect_drag_begin(clone!(@weak obj => move |_, _, _| {
+                let imp = obj.imp();
+                imp.drag_offset.set(imp.offset.get());
+            }));
+            drag.connect_drag_update(clone!(@weak obj => move |_, dx, dy| {
+                let (x, y) = obj.imp().drag_offset.get();
+                obj.set_offset(x + dx, y + dy);
+            }));
+            obj.add_controller(drag);
+
+            let click = gtk::GestureClick::new();
+            click.connect_pressed(clone!(@weak obj => move |_, n_press, _, _| {
+                // Toggle the zoom on double click
+                if n_press == 2 {
+                    if obj.imp().zoom.get() > MIN_ZOOM {
+                        obj.set_zoom(MIN_ZOOM);
+                    } else {
+                        obj.set_zoom(MIN_ZOOM * ZOOM_STEP * ZOOM_STEP);
+                    }
+                }
+            }));
+            obj.add_controller(click);
+        }
+
+        fn dispose(&self) {
+            self.obj().set_paintable(None);
+        }
+    }
+
+    impl WidgetImpl for ZoomablePicture {
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            self.parent_size_allocate(width, height, baseline);
+
+            // The paintable could now be out of bounds
+            let (x, y) = self.offset.get();
+            self.obj().set_offset(x, y);
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let Some(paintable) = self.paintable.borrow().clone() else {
+                return;
+            };
+
+            let widget = self.obj();
+            let (width, height) = widget.paintable_size();
+            let (x, y) = self.offset.get();
+
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(
+                (widget.width() as f64 / 2.0 + x) as f32,
+                (widget.height() as f64 / 2.0 + y) as f32,
+            ));
+            snapshot.rotate(self.rotation.get() as f32);
+            snapshot.translate(&graphene::Point::new(
+                (-width / 2.0) as f32,
+                (-height / 2.0) as f32,
+            ));
+            paintable.snapshot(snapshot, width, height);
+            snapshot.restore();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct ZoomablePicture(ObjectSubclass<imp::ZoomablePicture>)
+        @extends gtk::Widget;
+}
+
+impl ZoomablePicture {
+    pub(crate) fn paintable(&self) -> Option<gdk::Paintable> {
+        self.imp().paintable.borrow().clone()
+    }
+
+    /// Sets the paintable to show, resetting the zoom and the rotation.
+    pub(crate) fn set_paintable(&self, paintable: Option<&gdk::Paintable>) {
+        let imp = self.imp();
+
+        if let Some(old_paintable) = imp.paintable.take() {
+            for handler_id in imp.paintable_handlers.take() {
+                old_paintable.disconnect(handler_id);
+            }
+        }
+
+        if let Some(paintable) = paintable {
+            // Videos and animations change their contents over time
+            let contents_handler = paintable.connect_invalidate_contents(
+                clone!(@weak self as obj => move |_| obj.queue_draw()),
+            );
+            let size_handler = paintable
+                .connect_invalidate_size(clone!(@weak self as obj => move |_| obj.queue_draw()));
+            imp.paintable_handlers
+                .replace(vec![contents_handler, size_handler]);
+        }
+
+        imp.paintable.replace(paintable.cloned());
+        imp.rotation.set(0);
+        self.set_zoom(MIN_ZOOM);
+    }
+
+    pub(crate) fn zoom_in(&self) {
+        self.set_zoom(self.imp().zoom.get() * ZOOM_STEP);
+    }
+
+    pub(crate) fn zoom_out(&self) {
+        self.set_zoom(self.imp().zoom.get() / ZOOM_STEP);
+    }
+
+    /// Rotates the paintable clockwise by the specified degrees, which must be a
+    /// multiple of 90.
+    pub(crate) fn rotate(&self, degrees: i32) {
+        let imp = self.imp();
+        imp.rotation
+            .set((imp.rotation.get() + degrees).rem_euclid(360));
+        self.set_zoom(MIN_ZOOM);
+    }
+
+    fn set_zoom(&self, zoom: f64) {
+        let imp = self.imp();
+        imp.zoom.set(zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+
+        let (x, y) = imp.offset.get();
+        self.set_offset(x, y);
+    }
+
+    /// Moves the paintable by the specified offset, as far as it can be moved without
+    /// leaving empty space in the visible area.
+    fn set_offset(&self, x: f64, y: f64) {
+        let (width, height) = self.paintable_size();
+        let (width, height) = if self.imp().rotation.get() % 180 == 0 {
+            (width, height)
+        } else {
+            (height, width)
+        };
+
+        let max_x = ((width - self.width() as f64) / 2.0).max(0.0);
+        let max_y = ((height - self.height() as f64) / 2.0).max(0.0);
+
+        self.imp()
+            .offset
+            .set((x.clamp(-max_x, max_x), y.clamp(-max_y, max_y)));
+        self.queue_draw();
+    }
+
+    /// Returns the size at which the paintable is drawn, before being rotated.
+    fn paintable_size(&self) -> (f64, f64) {
+        let imp = self.imp();
+
+        let Some(paintable) = imp.paintable.borrow().clone() else {
+            return (0.0, 0.0);
+        };
+
+        let widget_width = self.width() as f64;
+        let widget_height = self.height() as f64;
+
+        let mut width = paintable.intrinsic_width() as f64;
+        let mut height = paintable.intrinsic_height() as f64;
+        if width <= 0.0 || height <= 0.0 {
+            width = widget_width;
+            height = widget_height;
+        }
+
+        let (rotated_width, rotated_height) = if imp.rotation.get() % 180 == 0 {
+            (width, height)
+        } else {
+            (height, width)
+        };
+
+        // Fit the paintable in the widget and then apply the zoom
+        let scale =
+            (widget_width / rotated_width).min(widget_height / rotated_height) * imp.zoom.get();
+
+        (width * scale, height * scale)
+    }
+}
@@ -0,0 +1,554 @@
+mod row;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::enums::MessageSender;
+use tdlib::enums::SupergroupMembersFilter;
+use tdlib::enums::UserType;
+use tdlib::functions;
+use tdlib::types::ChatMember;
+use tdlib::types::SupergroupMembersFilterSearch;
+
+use self::row::MemberRow;
+use crate::session::content::moderation;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::User;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+
+const PAGE_SIZE: i32 = 50;
+/// The time to wait after the last change of the search query before reloading the members.
+const SEARCH_DELAY: Duration = Duration::from_millis(400);
+
+/// The index of the "Administrators" item of the filter drop down.
+const ADMINISTRATORS_FILTER: u32 = 1;
+/// The index of the "Bots" item of the filter drop down.
+const BOTS_FILTER: u32 = 2;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentMemberList {
+        layout-manager: BinLayout {};
+
+        Box box_ {
+            orientation: vertical;
+            spacing: 6;
+
+            Box {
+                spacing: 6;
+
+                SearchEntry search_entry {
+                    hexpand: true;
+                    placeholder-text: _("Search Members");
+                    search-changed => $on_search_changed() swapped;
+                }
+
+                DropDown filter_drop_down {
+                    model: StringList {
+                        strings [
+                            _("Recent"),
+                            _("Administrators"),
+                            _("Bots"),
+                        ]
+                    };
+                    notify::selected => $on_filter_changed() swapped;
+                }
+            }
+
+            Stack stack {
+                vhomogeneous: false;
+
+                StackPage {
+                    name: "loading";
+                    child: Spinner {
+                        spinning: true;
+                        halign: center;
+                        margin-top: 24;
+                        margin-bottom: 24;
+                        width-request: 24;
+                        height-request: 24;
+                    };
+                }
+
+                StackPage {
+                    name: "empty";
+                    child: Label {
+                        label: _("No Members");
+                        margin-top: 24;
+                        margin-bottom: 24;
+
+                        styles ["dim-label"]
+                    };
+                }
+
+                StackPage {
+                    name: "items";
+                    child: ListBox list_box {
+                        selection-mode: none;
+                        row-activated => $on_row_activated() swapped;
+
+                        styles ["boxed-list"]
+                    };
+                }
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MemberList {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) request_generation: RequestGeneration,
+        pub(super) offset: Cell<i32>,
+        pub(super) is_loading: Cell<bool>,
+        pub(super) is_exhausted: Cell<bool>,
+        pub(super) search_source_id: RefCell<Option<glib::SourceId>>,
+        #[template_child]
+        pub(super) box_: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) filter_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MemberList {
+        const NAME: &'static str = "ContentMemberList";
+        type Type = super::MemberList;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+
+            klass.install_action_async(
+                "member-list.promote",
+                Some("x"),
+                |widget, _, data| async move {
+                    if let Some((window, chat, user)) = widget.member_context(data) {
+                        moderation::promote_member(&window, &chat, &user).await;
+                    }
+                },
+            );
+            klass.install_action_async(
+                "member-list.restrict",
+                Some("x"),
+                |widget, _, data| async move {
+                    if let Some((window, chat, user)) = widget.member_context(data) {
+                        moderation::restrict_member(&window, &chat, &user).await;
+                    }
+                },
+            );
+            klass.install_action_async(
+                "member-list.remove",
+                Some("x"),
+                |widget, _, data| async move {
+                    if let Some((window, chat, user)) = widget.member_context(data) {
+                        if moderation::remove_member(&window, &chat, &user).await {
+                            widget.reload();
+                        }
+                    }
+                },
+            );
+            klass.install_action_async(
+                "member-list.ban",
+                Some("x"),
+                |widget, _, data| async move {
+                    if let Some((window, chat, user)) = widget.member_context(data) {
+                        if moderation::ban_member(&window, &chat, &user).await {
+                            widget.reload();
+                        }
+                    }
+                },
+            );
+            klass.install_action_async(
+                "member-list.delete-messages",
+                Some("x"),
+                |widget, _, data| async move {
+                    if let Some((window, chat, user)) = widget.member_context(data) {
+                        moderation::delete_member_messages(&window, &chat, &user).await;
+                    }
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MemberList {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Chat>("chat")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.search_source_id.take() {
+                source_id.remove();
+            }
+
+            self.box_.unparent();
+        }
+    }
+
+    impl WidgetImpl for MemberList {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MemberList(ObjectSubclass<imp::MemberList>)
+        @extends gtk::Widget;
+}
+
+#[gtk::template_callbacks]
+impl MemberList {
+    #[template_callback]
+    fn on_search_changed(&self) {
+        let imp = self.imp();
+
+        if let Some(source_id) = imp.search_source_id.take() {
+            source_id.remove();
+        }
+
+        let source_id = glib::timeout_add_local_once(
+            SEARCH_DELAY,
+            clone!(@weak self as obj => move || {
+                obj.imp().search_source_id.replace(None);
+                obj.reload();
+            }),
+        );
+        imp.search_source_id.replace(Some(source_id));
+    }
+
+    #[template_callback]
+    fn on_filter_changed(&self) {
+        self.reload();
+    }
+
+    #[template_callback]
+    fn on_row_activated(&self, row: &gtk::ListBoxRow) {
+        if let Some(row) = row.child().and_downcast::<MemberRow>() {
+            let user = row.user();
+
+            if let Some(window) = self.root().and_downcast::<gtk::Window>() {
+                window.close();
+            }
+
+            user.session().select_chat(user.id());
+        }
+    }
+
+    /// Returns what's needed by the moderation actions, given their user id parameter.
+    fn member_context(&self, data: Option<glib::Variant>) -> Option<(gtk::Window, Chat, User)> {
+        let user_id = data.and_then(|v| v.get::<i64>())?;
+        let window = self.root().and_downcast::<gtk::Window>()?;
+        let chat = self.chat()?;
+        let user = chat.session().user(user_id);
+
+        Some((window, chat, user))
+    }
+
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        self.imp().chat.replace(chat);
+        self.reload();
+
+        self.notify("chat");
+    }
+
+    fn reload(&self) {
+        let imp = self.imp();
+
+        imp.request_generation.next();
+        imp.offset.set(0);
+        imp.is_loading.set(false);
+        imp.is_exhausted.set(false);
+
+        while let Some(child) = imp.list_box.first_child() {
+            imp.list_box.remove(&child);
+        }
+
+        self.update_stack();
+        self.load_more();
+    }
+
+    /// Loads the next page of members, if there are any left.
+    pub(crate) fn load_more(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading.get() || imp.is_exhausted.get() {
+            return;
+        }
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let client_id = chat.session().client_id();
+        let generation = imp.request_generation.current();
+
+        match chat.type_() {
+            ChatType::BasicGroup(basic_group) => {
+                let basic_group_id = basic_group.id();
+                imp.is_loading.set(true);
+
+                // Basic groups always return all their members at once
+                spawn(clone!(@weak self as obj => async move {
+                    let result =
+                        functions::get_basic_group_full_info(basic_group_id, client_id).await;
+
+                    if !obj.imp().request_generation.is_current(generation) {
+                        return;
+                    }
+
+                    match result {
+                        Ok(tdlib::enums::BasicGroupFullInfo::BasicGroupFullInfo(full_info)) => {
+                            obj.append_members(full_info.members);
+                        }
+                        Err(e) => log::warn!("Failed to get basic group full info: {e:?}"),
+                    }
+
+                    let imp = obj.imp();
+                    imp.is_loading.set(false);
+                    imp.is_exhausted.set(true);
+                    obj.update_stack();
+                }));
+            }
+            ChatType::Supergroup(supergroup) => {
+                let supergroup_id = supergroup.id();
+                let offset = imp.offset.get();
+                let filter = self.supergroup_members_filter();
+                imp.is_loading.set(true);
+
+                spawn(clone!(@weak self as obj => async move {
+                    let result = functions::get_supergroup_members(
+                        supergroup_id,
+                        Some(filter),
+                        offset,
+                        PAGE_SIZE,
+                        client_id,
+                    )
+                    .await;
+
+                    let imp = obj.imp();
+                    if !imp.request_generation.is_current(generation) {
+                        return;
+                    }
+
+                    match result {
+                        Ok(tdlib::enums::ChatMembers::ChatMembers(data)) => {
+                            let offset = offset + data.members.len() as i32;
+                            imp.offset.set(offset);
+                            imp.is_exhausted
+                                .set(data.members.is_empty() || offset >= data.total_count);
+
+                            obj.append_members(data.members);
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to get supergroup members: {e:?}");
+                            imp.is_exhausted.set(true);
+                        }
+                    }
+
+                    imp.is_loading.set(false);
+                    obj.update_stack();
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    fn supergroup_members_filter(&self) -> SupergroupMembersFilter {
+        let imp = self.imp();
+        let query = imp.search_entry.text();
+
+        // The search filter doesn't support filtering by role, so in that case the role
+        // is filtered afterwards
+        if !query.is_empty() {
+            return SupergroupMembersFilter::Search(SupergroupMembersFilterSearch {
+                query: query.into(),
+            });
+        }
+
+        match imp.filter_drop_down.selected() {
+            ADMINISTRATORS_FILTER => SupergroupMembersFilter::Administrators,
+            BOTS_FILTER => SupergroupMembersFilter::Bots,
+            _ => SupergroupMembersFilter::Recent,
+        }
+    }
+
+    fn append_members(&self, members: Vec<ChatMember>) {
+        let imp = self.imp();
+        let Some(chat) = self.chat() else {
+            return;
+        };
+        let session = chat.session();
+        // Supergroup members are searched by the server, which also matches usernames and
+        // transliterated names, so the query is only matched here for basic groups
+        let match_query = matches!(chat.type_(), ChatType::BasicGroup(_));
+
+        for member in members {
+            // Chats can be members of supergroups too, but only users are listed here
+            let MessageSender::User(sender) = member.member_id else {
+                continue;
+            };
+
+            let user = session.user(sender.user_id);
+            if self.matches(&user, &member.status, match_query) {
+                let row = MemberRow::new(&user, &member_role(&member.status));
+
+                if moderation::can_moderate(&chat, &user, &member.status) {
+                    let menu = member_menu(&chat, &user, &member.status);
+                    if menu.n_items() > 0 {
+                        row.set_menu_model(Some(menu.upcast_ref()));
+                    }
+                }
+
+                imp.list_box.append(&row);
+            }
+        }
+    }
+
+    fn matches(&self, user: &User, status: &ChatMemberStatus, match_query: bool) -> bool {
+        let imp = self.imp();
+
+        let matches_filter = match imp.filter_drop_down.selected() {
+            ADMINISTRATORS_FILTER => matches!(
+                status,
+                ChatMemberStatus::Creator(_) | ChatMemberStatus::Administrator(_)
+            ),
+            BOTS_FILTER => matches!(user.type_().0, UserType::Bot(_)),
+            _ => true,
+        };
+
+        if !match_query {
+            return matches_filter;
+        }
+
+        let query = imp.search_entry.text().to_lowercase();
+        let matches_query = query.is_empty()
+            || strings::user_display_name(user, true)
+                .to_lowercase()
+                .contains(&query)
+            || user.username().to_lowercase().contains(&query);
+
+        matches_filter && matches_query
+    }
+
+    fn update_stack(&self) {
+        let imp = self.imp();
+
+        let name = if imp.list_box.first_child().is_some() {
+            "items"
+        } else if imp.is_exhausted.get() {
+            "empty"
+        } else {
+            "loading"
+        };
+
+        imp.stack.set_visible_child_name(name);
+    }
+}
+
+/// Returns the menu with the moderation actions that we can do on a member.
+fn member_menu(chat: &Chat, user: &User, status: &ChatMemberStatus) -> gio::Menu {
+    let menu = gio::Menu::new();
+    let is_supergroup = matches!(chat.type_(), ChatType::Supergroup(_));
+    let is_administrator = matches!(status, ChatMemberStatus::Administrator(_));
+
+    let append_item = |label: &str, action: &str| {
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some(action), Some(&user.id().to_variant()));
+        menu.append_item(&item);
+    };
+
+    if chat.can_promote_members() {
+        if is_administrator {
+            append_item(&gettext("Edit Admin Rights…"), "member-list.promote");
+        } else {
+            append_item(&gettext("Promote to Admin…"), "member-list.promote");
+        }
+    }
+
+    if chat.can_restrict_members() && moderation::supports_restrictions(chat) && !is_administrator {
+        append_item(&gettext("Restrict…"), "member-list.restrict");
+    }
+
+    if moderation::can_remove(chat, status) {
+        append_item(&gettext("Remove From Chat"), "member-list.remove");
+        if is_supergroup {
+            append_item(&gettext("Ban"), "member-list.ban");
+        }
+    }
+
+    // Deleting all the messages of a member is only supported in supergroups
+    if is_supergroup && chat.can_delete_messages() {
+        append_item(
+            &gettext("Delete All Messages"),
+            "member-list.delete-messages",
+        );
+    }
+
+    menu
+}
+
+fn member_role(status: &ChatMemberStatus) -> String {
+    match status {
+        ChatMemberStatus::Creator(data) if !data.custom_title.is_empty() => {
+            data.custom_title.clone()
+        }
+        ChatMemberStatus::Creator(_) => gettext("owner"),
+        ChatMemberStatus::Administrator(data) if !data.custom_title.is_empty() => {
+            data.custom_title.clone()
+        }
+        ChatMemberStatus::Administrator(_) => gettext("admin"),
+        _ => String::new(),
+    }
+}
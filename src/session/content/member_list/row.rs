@@ -0,0 +1,184 @@
+use std::cell::OnceCell;
+
+use glib::closure;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::expressions;
+use crate::strings;
+use crate::tdlib::BoxedUserStatus;
+use crate::tdlib::User;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentMemberRow {
+        $ComponentsAvatar avatar {
+            size: 32;
+            item: bind template.user;
+        }
+
+        Box labels_box {
+            orientation: vertical;
+            homogeneous: true;
+            hexpand: true;
+
+            Inscription name_label {
+                text-overflow: ellipsize_end;
+            }
+
+            Inscription status_label {
+                text-overflow: ellipsize_end;
+
+                styles [
+                    "dim-label",
+                    "small-body",
+                ]
+            }
+        }
+
+        Label role_label {
+            valign: center;
+            ellipsize: end;
+            max-width-chars: 16;
+
+            styles [
+                "dim-label",
+                "caption",
+            ]
+        }
+
+        MenuButton menu_button {
+            visible: false;
+            icon-name: "view-more-symbolic";
+            valign: center;
+
+            styles ["flat"]
+        }
+    }
+    "#)]
+    pub(crate) struct MemberRow {
+        pub(super) user: OnceCell<User>,
+        #[template_child]
+        pub(super) name_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub(super) status_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub(super) role_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) menu_button: TemplateChild<gtk::MenuButton>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MemberRow {
+        const NAME: &'static str = "ContentMemberRow";
+        type Type = super::MemberRow;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+            klass.set_css_name("memberrow");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MemberRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<User>("user")
+                        .construct_only()
+                        .build(),
+                    glib::ParamSpecString::builder("role")
+                        .construct_only()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "user" => self.user.set(value.get().unwrap()).unwrap(),
+                "role" => {
+                    let role = value.get::<Option<&str>>().unwrap().unwrap_or_default();
+                    self.role_label.set_label(role);
+                    self.role_label.set_visible(!role.is_empty());
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "user" => obj.user().to_value(),
+                "role" => self.role_label.label().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            let user_expression = super::MemberRow::this_expression("user");
+
+            expressions::user_display_name(&user_expression).bind(
+                &*self.name_label,
+                "text",
+                Some(&*obj),
+            );
+            user_expression
+                .chain_property::<User>("status")
+                .chain_closure::<String>(closure!(
+                    |_: Option<glib::Object>, status: BoxedUserStatus| {
+                        strings::user_status(&status.0)
+                    }
+                ))
+                .bind(&*self.status_label, "text", Some(&*obj));
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MemberRow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MemberRow(ObjectSubclass<imp::MemberRow>)
+        @extends gtk::Widget;
+}
+
+impl MemberRow {
+    pub(crate) fn new(user: &User, role: &str) -> Self {
+        glib::Object::builder()
+            .property("user", user)
+            .property("role", role)
+            .build()
+    }
+
+    pub(crate) fn user(&self) -> &User {
+        self.imp().user.get().unwrap()
+    }
+
+    /// Sets the menu with the actions that can be done on the member.
+    pub(crate) fn set_menu_model(&self, menu_model: Option<&gio::MenuModel>) {
+        let menu_button = &self.imp().menu_button;
+        menu_button.set_menu_model(menu_model);
+        menu_button.set_visible(menu_model.is_some());
+    }
+}
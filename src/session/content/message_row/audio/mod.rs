@@ -0,0 +1,426 @@
+mod waveform;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::types::File;
+
+use self::waveform::Waveform;
+use super::base::MessageBaseExt;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::tdlib::Message;
+use crate::utils::format_playback_time;
+use crate::utils::parse_formatted_text;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageAudio : $MessageBase {
+        $MessageBubble message_bubble {
+            styles ["audio"]
+
+            prefix: Box {
+                spacing: 12;
+
+                Button play_button {
+                    valign: center;
+                    icon-name: "media-playback-start-symbolic";
+                    tooltip-text: _("Play");
+                    clicked => $on_play_button_clicked() swapped;
+
+                    styles ["circular", "play-button"]
+                }
+
+                Box {
+                    orientation: vertical;
+                    valign: center;
+                    hexpand: true;
+                    spacing: 3;
+
+                    Label title_label {
+                        xalign: 0;
+                        ellipsize: end;
+
+                        styles ["heading"]
+                    }
+
+                    Label performer_label {
+                        xalign: 0;
+                        ellipsize: end;
+
+                        styles ["dim-label", "caption"]
+                    }
+
+                    $MessageAudioWaveform waveform {}
+
+                    Scale seek_scale {
+                        adjustment: Adjustment {
+                            upper: 1;
+                        };
+                        value-changed => $on_seek_scale_value_changed() swapped;
+                    }
+
+                    Box {
+                        spacing: 6;
+
+                        Label time_label {
+                            xalign: 0;
+
+                            styles ["dim-label", "caption", "numeric"]
+                        }
+
+                        Box unlistened_indicator {
+                            valign: center;
+
+                            styles ["unlistened-indicator"]
+                        }
+                    }
+                }
+            };
+        }
+    }
+    "#)]
+    pub(crate) struct MessageAudio {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
+        /// The duration reported by TDLib, in seconds
+        pub(super) duration: Cell<i32>,
+        /// Whether the seek scale is being moved by the playback, rather than by the user
+        pub(super) is_updating_progress: Cell<bool>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) play_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) performer_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) waveform: TemplateChild<Waveform>,
+        #[template_child]
+        pub(super) seek_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        pub(super) time_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) unlistened_indicator: TemplateChild<gtk::Box>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageAudio {
+        const NAME: &'static str = "MessageAudio";
+        type Type = super::MessageAudio;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            Waveform::static_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageAudio {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            self.waveform
+                .connect_seek(clone!(@weak obj => move |_, progress| {
+                    obj.seek(progress);
+                }));
+        }
+
+        fn dispose(&self) {
+            if let Some(media) = self.media.take() {
+                media.pause();
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageAudio {}
+    impl MessageBaseImpl for MessageAudio {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageAudio(ObjectSubclass<imp::MessageAudio>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageAudio {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        self.stop();
+        imp.message_bubble.update_from_message(&message, false);
+
+        // Voice notes are marked as listened through an update of the content
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_content(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+        self.update_content(&message);
+
+        imp.message.replace(Some(message));
+        self.notify("message");
+    }
+}
+
+#[gtk::template_callbacks]
+impl MessageAudio {
+    fn update_content(&self, message: &Message) {
+        let imp = self.imp();
+
+        let (caption, duration) = match message.content().0 {
+            MessageContent::MessageVoiceNote(data) => {
+                imp.title_label.set_visible(false);
+                imp.performer_label.set_visible(false);
+                imp.seek_scale.set_visible(false);
+
+                imp.waveform.set_visible(true);
+                imp.waveform.set_waveform(&data.voice_note.waveform);
+
+                imp.unlistened_indicator
+                    .set_visible(!message.is_outgoing() && !data.is_listened);
+
+                (data.caption, data.voice_note.duration)
+            }
+            MessageContent::MessageAudio(data) => {
+                let title = if data.audio.title.is_empty() {
+                    data.audio.file_name
+                } else {
+                    data.audio.title
+                };
+                imp.title_label.set_label(&title);
+                imp.title_label.set_visible(true);
+
+                imp.performer_label.set_label(&data.audio.performer);
+                imp.performer_label
+                    .set_visible(!data.audio.performer.is_empty());
+
+                imp.waveform.set_visible(false);
+                imp.seek_scale.set_visible(true);
+                imp.unlistened_indicator.set_visible(false);
+
+                (data.caption, data.audio.duration)
+            }
+            _ => return,
+        };
+
+        imp.message_bubble.set_label(parse_formatted_text(caption));
+
+        imp.duration.set(duration);
+        if imp.media.borrow().is_none() {
+            imp.time_label
+                .set_label(&format_playback_time(duration as i64));
+        }
+    }
+
+    #[template_callback]
+    fn on_play_button_clicked(&self) {
+        let imp = self.imp();
+
+        if let Some(media) = &*imp.media.borrow() {
+            if media.is_playing() {
+                media.pause();
+            } else {
+                if media.is_ended() {
+                    media.seek(0);
+                }
+                media.play();
+            }
+            return;
+        }
+
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let Some(file) = audio_file(&message) else {
+            return;
+        };
+
+        if file.local.is_downloading_completed {
+            self.play(&message, &file.local.path);
+        } else {
+            imp.play_button.set_sensitive(false);
+
+            let session = message.chat().session();
+            spawn(clone!(@weak self as obj => async move {
+                let result = session.download_file(file.id).await;
+                obj.imp().play_button.set_sensitive(true);
+
+                match result {
+                    Ok(file) => {
+                        // Don't start playing if the row has been reused meanwhile
+                        if obj.imp().message.borrow().as_ref() == Some(&message) {
+                            obj.play(&message, &file.local.path);
+                        }
+                    }
+                    Err(e) => log::warn!("Failed to download an audio file: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    #[template_callback]
+    fn on_seek_scale_value_changed(&self) {
+        let imp = self.imp();
+        if !imp.is_updating_progress.get() {
+            self.seek(imp.seek_scale.value());
+        }
+    }
+
+    fn play(&self, message: &Message, path: &str) {
+        let imp = self.imp();
+
+        let media = gtk::MediaFile::for_filename(path);
+        media.connect_playing_notify(clone!(@weak self as obj => move |media| {
+            obj.update_play_button(media.is_playing());
+        }));
+        media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
+            obj.update_progress(media);
+        }));
+        media.connect_error_notify(|media| {
+            if let Some(e) = media.error() {
+                log::warn!("Error playing an audio file: {e:?}");
+            }
+        });
+        media.play();
+
+        imp.media.replace(Some(media));
+
+        if let MessageContent::MessageVoiceNote(data) = message.content().0 {
+            if !message.is_outgoing() && !data.is_listened {
+                spawn(clone!(@weak message => async move {
+                    if let Err(e) = message.open_content().await {
+                        log::warn!("Error marking a voice note as listened: {e:?}");
+                    }
+                }));
+            }
+        }
+    }
+
+    fn stop(&self) {
+        let imp = self.imp();
+
+        if let Some(media) = imp.media.take() {
+            media.pause();
+        }
+
+        self.update_play_button(false);
+        self.set_progress(0.0);
+        imp.time_label
+            .set_label(&format_playback_time(imp.duration.get() as i64));
+    }
+
+    fn seek(&self, progress: f64) {
+        if let Some(media) = &*self.imp().media.borrow() {
+            if media.is_seekable() {
+                media.seek((media.duration() as f64 * progress) as i64);
+            }
+        }
+    }
+
+    fn update_play_button(&self, is_playing: bool) {
+        let play_button = &*self.imp().play_button;
+        if is_playing {
+            play_button.set_icon_name("media-playback-pause-symbolic");
+            play_button.set_tooltip_text(Some(&gettext("Pause")));
+        } else {
+            play_button.set_icon_name("media-playback-start-symbolic");
+            play_button.set_tooltip_text(Some(&gettext("Play")));
+        }
+    }
+
+    fn update_progress(&self, media: &gtk::MediaFile) {
+        // Timestamps are in microseconds
+        let timestamp = media.timestamp() / i64::pow(10, 6);
+        let duration = if media.duration() > 0 {
+            media.duration() / i64::pow(10, 6)
+        } else {
+            self.imp().duration.get() as i64
+        };
+
+        if media.duration() > 0 {
+            self.set_progress(media.timestamp() as f64 / media.duration() as f64);
+        }
+
+        self.imp().time_label.set_label(&format!(
+            "{} / {}",
+            format_playback_time(timestamp),
+            format_playback_time(duration)
+        ));
+    }
+
+    fn set_progress(&self, progress: f64) {
+        let imp = self.imp();
+
+        imp.waveform.set_progress(progress);
+
+        imp.is_updating_progress.set(true);
+        imp.seek_scale.set_value(progress);
+        imp.is_updating_progress.set(false);
+    }
+}
+
+fn audio_file(message: &Message) -> Option<File> {
+    match message.content().0 {
+        MessageContent::MessageVoiceNote(data) => Some(data.voice_note.voice),
+        MessageContent::MessageAudio(data) => Some(data.audio.audio),
+        _ => None,
+    }
+}
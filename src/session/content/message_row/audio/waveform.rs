@@ -0,0 +1,176 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::graphene;
+use gtk::gsk;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+
+const BAR_WIDTH: f32 = 2.0;
+const BAR_SPACING: f32 = 1.0;
+const MIN_BAR_HEIGHT: f32 = 2.0;
+/// The maximum value of a sample, as samples are encoded in 5 bits.
+const MAX_SAMPLE: u8 = 31;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct Waveform {
+        pub(super) samples: RefCell<Vec<u8>>,
+        pub(super) progress: Cell<f64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Waveform {
+        const NAME: &'static str = "MessageAudioWaveform";
+        type Type = super::Waveform;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("waveform");
+        }
+    }
+
+    impl ObjectImpl for Waveform {
+        fn signals() -> &'static [glib::subclass::Signal] {
+            static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+                vec![glib::subclass::Signal::builder("seek")
+                    .param_types([f64::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.set_cursor_from_name(Some("pointer"));
+
+            let click = gtk::GestureClick::new();
+            click.connect_released(clone!(@weak obj => move |_, _, x, _| {
+                let progress = (x / obj.width() as f64).clamp(0.0, 1.0);
+                obj.emit_by_name::<()>("seek", &[&progress]);
+            }));
+            obj.add_controller(click);
+        }
+    }
+
+    impl WidgetImpl for Waveform {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Horizontal => (60, 160, -1, -1),
+                _ => (24, 24, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let width = widget.width() as f32;
+            let height = widget.height() as f32;
+
+            let n_bars = ((width + BAR_SPACING) / (BAR_WIDTH + BAR_SPACING)) as usize;
+            if n_bars == 0 {
+                return;
+            }
+
+            let samples = self.samples.borrow();
+            let played_bars = (n_bars as f64 * self.progress.get()).round() as usize;
+
+            let color = widget.color();
+            let dimmed_color = color.with_alpha(color.alpha() * 0.4);
+
+            for i in 0..n_bars {
+                // Pick the loudest sample among the ones covered by the bar
+                let value = if samples.is_empty() {
+                    0
+                } else {
+                    let start = i * samples.len() / n_bars;
+                    let end = ((i + 1) * samples.len() / n_bars).max(start + 1);
+                    samples[start..end.min(samples.len())]
+                        .iter()
+                        .copied()
+                        .max()
+                        .unwrap_or_default()
+                };
+
+                let bar_height = (value as f32 / MAX_SAMPLE as f32 * height).max(MIN_BAR_HEIGHT);
+                let rect = graphene::Rect::new(
+                    i as f32 * (BAR_WIDTH + BAR_SPACING),
+                    (height - bar_height) / 2.0,
+                    BAR_WIDTH,
+                    bar_height,
+                );
+                let radius = graphene::Size::new(BAR_WIDTH / 2.0, BAR_WIDTH / 2.0);
+
+                snapshot.push_rounded_clip(&gsk::RoundedRect::new(
+                    rect, radius, radius, radius, radius,
+                ));
+                snapshot.append_color(
+                    if i < played_bars {
+                        &color
+                    } else {
+                        &dimmed_color
+                    },
+                    &rect,
+                );
+                snapshot.pop();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct Waveform(ObjectSubclass<imp::Waveform>)
+        @extends gtk::Widget;
+}
+
+impl Default for Waveform {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl Waveform {
+    /// Sets the samples to draw from a waveform encoded by Telegram, which packs
+    /// 5-bit samples in a base64 encoded string.
+    pub(crate) fn set_waveform(&self, waveform: &str) {
+        let bytes = glib::base64_decode(waveform);
+        let n_samples = bytes.len() * 8 / 5;
+
+        let samples = (0..n_samples)
+            .map(|i| {
+                let byte_index = i * 5 / 8;
+                let bit_shift = i * 5 % 8;
+                let next_byte = bytes.get(byte_index + 1).copied().unwrap_or_default();
+                let value = bytes[byte_index] as u16 | (next_byte as u16) << 8;
+                ((value >> bit_shift) as u8) & MAX_SAMPLE
+            })
+            .collect();
+
+        self.imp().samples.replace(samples);
+        self.queue_draw();
+    }
+
+    /// Sets the fraction of the waveform that has already been played.
+    pub(crate) fn set_progress(&self, progress: f64) {
+        let imp = self.imp();
+        if imp.progress.get() != progress {
+            imp.progress.set(progress);
+            self.queue_draw();
+        }
+    }
+
+    pub(crate) fn connect_seek<F: Fn(&Self, f64) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("seek", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let progress = values[1].get::<f64>().unwrap();
+            f(&obj, progress);
+            None
+        })
+    }
+}
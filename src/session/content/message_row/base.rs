@@ -57,10 +57,11 @@ mod imp {
         fn show_message_menu(&self, x: i32, y: i32) {
             let obj = self.obj();
             let chat_history = obj.ancestor(ChatHistory::static_type()).unwrap();
-            let menu = chat_history
-                .downcast_ref::<ChatHistory>()
-                .unwrap()
-                .message_menu();
+            let chat_history = chat_history.downcast_ref::<ChatHistory>().unwrap();
+            let menu = chat_history.message_menu();
+
+            chat_history
+                .update_message_menu(obj.property::<glib::Object>("message").downcast().ok());
 
             menu.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 0, 0)));
             menu.unparent();
@@ -11,6 +11,7 @@ use once_cell::sync::Lazy;
 
 use crate::session::content::message_row::MessageIndicators;
 use crate::session::content::message_row::MessageLabel;
+use crate::session::content::message_row::MessageReactions;
 use crate::session::content::message_row::MessageReply;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
@@ -56,6 +57,12 @@ mod imp {
                 $MessageLabel message_label {
                     visible: false;
                 }
+
+                Adw.Bin suffix_bin {}
+
+                $MessageReactions reactions {
+                    visible: false;
+                }
             }
 
             [overlay]
@@ -80,6 +87,10 @@ mod imp {
         #[template_child]
         pub(super) message_label: TemplateChild<MessageLabel>,
         #[template_child]
+        pub(super) suffix_bin: TemplateChild<adw::Bin>,
+        #[template_child]
+        pub(super) reactions: TemplateChild<MessageReactions>,
+        #[template_child]
         pub(super) indicators: TemplateChild<MessageIndicators>,
     }
 
@@ -106,6 +117,9 @@ mod imp {
                     glib::ParamSpecObject::builder::<gtk::Widget>("prefix")
                         .write_only()
                         .build(),
+                    glib::ParamSpecObject::builder::<gtk::Widget>("suffix")
+                        .write_only()
+                        .build(),
                     glib::ParamSpecString::builder("label").write_only().build(),
                 ]
             });
@@ -117,6 +131,7 @@ mod imp {
 
             match pspec.name() {
                 "prefix" => obj.set_prefix(value.get().unwrap()),
+                "suffix" => obj.set_suffix(value.get().unwrap()),
                 "label" => obj.set_label(value.get().unwrap()),
                 _ => unimplemented!(),
             }
@@ -166,6 +181,7 @@ impl MessageBubble {
         let imp = self.imp();
 
         imp.indicators.set_message(message.clone().upcast());
+        imp.reactions.set_message(Some(message.clone()));
 
         let is_channel = if let ChatType::Supergroup(data) = message.chat().type_() {
             data.is_channel()
@@ -245,6 +261,7 @@ impl MessageBubble {
 
         imp.indicators
             .set_message(sponsored_message.clone().upcast());
+        imp.reactions.set_message(None);
 
         self.remove_css_class("outgoing");
 
@@ -268,6 +285,10 @@ impl MessageBubble {
         self.imp().prefix_bin.set_child(prefix);
     }
 
+    pub(crate) fn set_suffix(&self, suffix: Option<&gtk::Widget>) {
+        self.imp().suffix_bin.set_child(suffix);
+    }
+
     pub(crate) fn set_label(&self, label: String) {
         let imp = self.imp();
 
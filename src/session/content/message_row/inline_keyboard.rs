@@ -0,0 +1,322 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use once_cell::sync::Lazy;
+use tdlib::enums::CallbackQueryAnswer;
+use tdlib::enums::CallbackQueryPayload;
+use tdlib::enums::InlineKeyboardButtonType;
+use tdlib::enums::MessageContent;
+use tdlib::enums::ReplyMarkup;
+use tdlib::functions;
+use tdlib::types;
+
+use crate::session::content::ChatHistory;
+use crate::tdlib::Message;
+use crate::tdlib::MessageSender;
+use crate::utils::spawn;
+
+const SPACING: i32 = 3;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct MessageInlineKeyboard {
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) message_signal_group: OnceCell<glib::SignalGroup>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageInlineKeyboard {
+        const NAME: &'static str = "MessageInlineKeyboard";
+        type Type = super::MessageInlineKeyboard;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+            klass.set_css_name("messageinlinekeyboard");
+        }
+    }
+
+    impl ObjectImpl for MessageInlineKeyboard {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let layout = obj
+                .layout_manager()
+                .and_downcast::<gtk::BoxLayout>()
+                .unwrap();
+            layout.set_orientation(gtk::Orientation::Vertical);
+            layout.set_spacing(SPACING);
+
+            let message_signal_group = glib::SignalGroup::new::<Message>();
+            message_signal_group.connect_notify_local(
+                Some("reply-markup"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_buttons();
+                }),
+            );
+            self.message_signal_group.set(message_signal_group).unwrap();
+        }
+
+        fn dispose(&self) {
+            let obj = self.obj();
+            while let Some(child) = obj.first_child() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageInlineKeyboard {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageInlineKeyboard(ObjectSubclass<imp::MessageInlineKeyboard>)
+        @extends gtk::Widget;
+}
+
+impl Default for MessageInlineKeyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageInlineKeyboard {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub(crate) fn message(&self) -> Option<Message> {
+        self.imp().message.borrow().clone()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<Message>) {
+        if self.message() == message {
+            return;
+        }
+
+        let imp = self.imp();
+
+        imp.message_signal_group
+            .get()
+            .unwrap()
+            .set_target(message.as_ref());
+        imp.message.replace(message);
+
+        self.update_buttons();
+
+        self.notify("message");
+    }
+
+    fn update_buttons(&self) {
+        while let Some(child) = self.first_child() {
+            child.unparent();
+        }
+
+        let rows = self
+            .message()
+            .and_then(|message| message.reply_markup())
+            .and_then(|reply_markup| match reply_markup.0 {
+                ReplyMarkup::InlineKeyboard(data) => Some(data.rows),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        for row in rows {
+            let row_box = gtk::Box::builder()
+                .spacing(SPACING)
+                .homogeneous(true)
+                .build();
+
+            for keyboard_button in row {
+                let label = gtk::Label::builder()
+                    .label(&keyboard_button.text)
+                    .ellipsize(gtk::pango::EllipsizeMode::End)
+                    .build();
+                let button = gtk::Button::builder().child(&label).build();
+
+                if let InlineKeyboardButtonType::Url(ref data) = keyboard_button.r#type {
+                    button.set_tooltip_text(Some(&data.url));
+                }
+
+                let button_type = keyboard_button.r#type;
+                button.connect_clicked(clone!(@weak self as obj => move |button| {
+                    obj.activate_button(button, button_type.clone());
+                }));
+
+                row_box.append(&button);
+            }
+
+            row_box.set_parent(self);
+        }
+
+        self.set_visible(self.first_child().is_some());
+    }
+
+    fn activate_button(&self, button: &gtk::Button, button_type: InlineKeyboardButtonType) {
+        let Some(message) = self.message() else {
+            return;
+        };
+
+        match button_type {
+            InlineKeyboardButtonType::Url(data) => launch_uri(&data.url),
+            InlineKeyboardButtonType::LoginUrl(data) => launch_uri(&data.url),
+            InlineKeyboardButtonType::WebApp(data) => launch_uri(&data.url),
+            InlineKeyboardButtonType::Callback(data) => {
+                let payload =
+                    CallbackQueryPayload::Data(types::CallbackQueryPayloadData { data: data.data });
+                self.send_callback_query(button, &message, payload);
+            }
+            InlineKeyboardButtonType::CallbackGame => {
+                if let MessageContent::MessageGame(data) = message.content().0 {
+                    let payload = CallbackQueryPayload::Game(types::CallbackQueryPayloadGame {
+                        game_short_name: data.game.short_name,
+                    });
+                    self.send_callback_query(button, &message, payload);
+                }
+            }
+            InlineKeyboardButtonType::SwitchInline(data) => {
+                // The buttons of messages sent via an inline bot belong to that bot
+                let bot = match message.via_bot_user_id() {
+                    0 => message.sender().as_user().cloned(),
+                    user_id => Some(message.chat().session().user(user_id)),
+                };
+                let Some(bot) = bot else {
+                    return;
+                };
+
+                // Choosing another chat for the inline query is not supported yet, so it's
+                // always started in the current chat
+                if let Some(chat_history) = self.chat_history() {
+                    chat_history
+                        .chat_action_bar()
+                        .start_inline_query(&bot.username(), &data.query);
+                }
+            }
+            InlineKeyboardButtonType::User(data) => {
+                message.chat().session().select_chat(data.user_id);
+            }
+            InlineKeyboardButtonType::CallbackWithPassword(_) | InlineKeyboardButtonType::Buy => {
+                self.show_toast(&gettext("This button is not supported yet"));
+            }
+        }
+    }
+
+    /// Sends the callback query of a button to the bot, showing its answer as a toast or
+    /// as a dialog, depending on what the bot asked for.
+    fn send_callback_query(
+        &self,
+        button: &gtk::Button,
+        message: &Message,
+        payload: CallbackQueryPayload,
+    ) {
+        let chat_id = message.chat().id();
+        let message_id = message.id();
+        let client_id = message.chat().session().client_id();
+
+        button.set_sensitive(false);
+
+        spawn(clone!(@weak self as obj, @weak button => async move {
+            let result =
+                functions::get_callback_query_answer(chat_id, message_id, payload, client_id)
+                    .await;
+
+            button.set_sensitive(true);
+
+            match result {
+                Ok(CallbackQueryAnswer::CallbackQueryAnswer(answer)) => {
+                    if !answer.text.is_empty() {
+                        if answer.show_alert {
+                            obj.show_alert(&answer.text);
+                        } else {
+                            obj.show_toast(&answer.text);
+                        }
+                    }
+
+                    if !answer.url.is_empty() {
+                        launch_uri(&answer.url);
+                    }
+                }
+                Err(e) => {
+                    // The bot could simply not have answered in time
+                    log::warn!("Error getting the answer of a callback query: {e:?}");
+                    obj.show_toast(&gettext("The bot did not answer"));
+                }
+            }
+        }));
+    }
+
+    fn chat_history(&self) -> Option<ChatHistory> {
+        self.ancestor(ChatHistory::static_type())
+            .and_downcast::<ChatHistory>()
+    }
+
+    fn show_toast(&self, text: &str) {
+        if let Some(chat_history) = self.chat_history() {
+            chat_history.add_toast(adw::Toast::new(text));
+        }
+    }
+
+    fn show_alert(&self, text: &str) {
+        // The row could have been removed while waiting for the answer of the bot
+        let Some(window) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        let heading = match self.message().as_ref().map(Message::sender) {
+            Some(MessageSender::User(user)) => user.first_name(),
+            Some(MessageSender::Chat(chat)) => chat.title(),
+            None => String::new(),
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .body(text)
+            .transient_for(&window)
+            .build();
+
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.present();
+    }
+}
+
+fn launch_uri(uri: &str) {
+    if let Err(e) = gio::AppInfo::launch_default_for_uri(uri, gio::AppLaunchContext::NONE) {
+        log::warn!("Error opening an URI: {e:?}");
+    }
+}
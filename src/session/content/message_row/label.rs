@@ -8,9 +8,12 @@ use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 
 use crate::session::content::message_row::MessageIndicators;
+use crate::session::content::ChatHistory;
 
 const OBJECT_REPLACEMENT_CHARACTER: char = '\u{FFFC}';
 const INDICATORS_SPACING: i32 = 6;
+/// The opacity of the background of the highlighted text, from 0 to `u16::MAX`.
+const HIGHLIGHT_ALPHA: u16 = 0x6666;
 
 mod imp {
     use super::*;
@@ -31,6 +34,8 @@ mod imp {
         pub(super) text: RefCell<String>,
         pub(super) indicators: RefCell<Option<MessageIndicators>>,
         pub(super) indicators_size: RefCell<Option<(i32, i32)>>,
+        pub(super) highlight: RefCell<String>,
+        pub(super) highlight_binding: RefCell<Option<glib::Binding>>,
         #[template_child]
         pub(super) label: TemplateChild<gtk::Label>,
     }
@@ -61,6 +66,9 @@ mod imp {
                     glib::ParamSpecObject::builder::<MessageIndicators>("indicators")
                         .construct_only()
                         .build(),
+                    glib::ParamSpecString::builder("highlight")
+                        .explicit_notify()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -72,6 +80,7 @@ mod imp {
             match pspec.name() {
                 "label" => obj.set_label(value.get().unwrap()),
                 "indicators" => obj.set_indicators(value.get().unwrap()),
+                "highlight" => obj.set_highlight(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -82,6 +91,7 @@ mod imp {
             match pspec.name() {
                 "label" => obj.label().to_value(),
                 "indicators" => obj.indicators().to_value(),
+                "highlight" => obj.highlight().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -108,10 +118,10 @@ mod imp {
                     if indicators_size.width() != old_indicators_size.0
                         || indicators_size.height() != old_indicators_size.1
                     {
-                        obj.update_label_attributes(&indicators_size);
+                        obj.update_label_attributes(Some(&indicators_size));
                     }
                 } else {
-                    obj.update_label_attributes(&indicators_size);
+                    obj.update_label_attributes(Some(&indicators_size));
                 }
 
                 let (mut minimum, mut natural, minimum_baseline, natural_baseline) =
@@ -147,6 +157,41 @@ mod imp {
         fn direction_changed(&self, _previous_direction: gtk::TextDirection) {
             self.obj().update_label();
         }
+
+        fn css_changed(&self, change: &gtk::CssStyleChange) {
+            self.parent_css_changed(change);
+
+            // The color of the highlight depends on the style
+            if !self.highlight.borrow().is_empty() {
+                self.obj().update_label();
+            }
+        }
+
+        fn root(&self) {
+            self.parent_root();
+
+            // Highlight the text searched in the chat history
+            let obj = self.obj();
+            if let Some(chat_history) = obj
+                .ancestor(ChatHistory::static_type())
+                .and_downcast::<ChatHistory>()
+            {
+                let binding = chat_history
+                    .search_bar()
+                    .bind_property("query", &*obj, "highlight")
+                    .sync_create()
+                    .build();
+                self.highlight_binding.replace(Some(binding));
+            }
+        }
+
+        fn unroot(&self) {
+            if let Some(binding) = self.highlight_binding.take() {
+                binding.unbind();
+            }
+
+            self.parent_unroot();
+        }
     }
 }
 
@@ -156,10 +201,15 @@ glib::wrapper! {
 }
 
 impl MessageLabel {
-    fn update_label_attributes(&self, indicators_size: &gtk::Requisition) {
+    fn update_label_attributes(&self, indicators_size: Option<&gtk::Requisition>) {
         let imp = self.imp();
-        if let Some(start_index) = imp.label.text().find(OBJECT_REPLACEMENT_CHARACTER) {
-            let attrs = pango::AttrList::new();
+        let text = imp.label.text();
+        let attrs = pango::AttrList::new();
+        let mut has_attrs = false;
+
+        if let Some((start_index, indicators_size)) =
+            text.find(OBJECT_REPLACEMENT_CHARACTER).zip(indicators_size)
+        {
             let width = indicators_size.width() + INDICATORS_SPACING;
             let height = indicators_size.height();
             let logical_rect = pango::Rectangle::new(
@@ -173,11 +223,42 @@ impl MessageLabel {
             shape.set_start_index(start_index as u32);
             shape.set_end_index((start_index + OBJECT_REPLACEMENT_CHARACTER.len_utf8()) as u32);
             attrs.insert(shape);
+            has_attrs = true;
+        }
 
-            imp.label.set_attributes(Some(&attrs));
-        } else {
-            imp.label.set_attributes(None);
+        let highlight = imp.highlight.borrow();
+        let matches = find_matches(&text, &highlight);
+        if !matches.is_empty() {
+            // Use a translucent accent color, so that the text keeps its own color and
+            // stays readable with both the light and the dark style
+            #[allow(deprecated)]
+            let color = self
+                .style_context()
+                .lookup_color("accent_bg_color")
+                .unwrap_or_else(|| self.color());
+            let (red, green, blue) = (
+                (color.red() * u16::MAX as f32) as u16,
+                (color.green() * u16::MAX as f32) as u16,
+                (color.blue() * u16::MAX as f32) as u16,
+            );
+
+            for (start_index, end_index) in matches {
+                let mut background = pango::AttrColor::new_background(red, green, blue);
+                background.set_start_index(start_index as u32);
+                background.set_end_index(end_index as u32);
+                attrs.insert(background);
+
+                let mut alpha = pango::AttrInt::new_background_alpha(HIGHLIGHT_ALPHA);
+                alpha.set_start_index(start_index as u32);
+                alpha.set_end_index(end_index as u32);
+                attrs.insert(alpha);
+            }
+
+            has_attrs = true;
         }
+
+        imp.label
+            .set_attributes(if has_attrs { Some(&attrs) } else { None });
     }
 
     fn is_opposite_text_direction(&self) -> bool {
@@ -202,9 +283,10 @@ impl MessageLabel {
             }
 
             let (_, indicators_size) = indicators.preferred_size();
-            self.update_label_attributes(&indicators_size);
+            self.update_label_attributes(Some(&indicators_size));
         } else {
             imp.label.set_label(&text);
+            self.update_label_attributes(None);
         }
     }
 
@@ -221,6 +303,21 @@ impl MessageLabel {
         }
     }
 
+    /// The text to highlight in the label, case insensitively.
+    pub(crate) fn highlight(&self) -> String {
+        self.imp().highlight.borrow().clone()
+    }
+
+    pub(crate) fn set_highlight(&self, highlight: String) {
+        if self.highlight() == highlight {
+            return;
+        }
+
+        self.imp().highlight.replace(highlight);
+        self.update_label();
+        self.notify("highlight");
+    }
+
     pub(crate) fn indicators(&self) -> Option<MessageIndicators> {
         self.imp().indicators.borrow().clone()
     }
@@ -242,3 +339,25 @@ impl MessageLabel {
         }
     }
 }
+
+/// Returns the byte ranges of the case insensitive occurrences of `pattern` in `text`.
+fn find_matches(text: &str, pattern: &str) -> Vec<(usize, usize)> {
+    if pattern.is_empty() {
+        return vec![];
+    }
+
+    let lowercase_text = text.to_lowercase();
+    let lowercase_pattern = pattern.to_lowercase();
+
+    // Lowercasing can change the length of some characters, in which case the indices
+    // would not match anymore, so fall back to case sensitive matching
+    let (text, pattern) = if lowercase_text.len() == text.len() {
+        (lowercase_text.as_str(), lowercase_pattern.as_str())
+    } else {
+        (text, pattern)
+    };
+
+    text.match_indices(pattern)
+        .map(|(index, matched)| (index, index + matched.len()))
+        .collect()
+}
@@ -1,47 +1,70 @@
+mod audio;
 mod base;
 mod bubble;
 mod document;
 mod indicators;
+mod inline_keyboard;
 mod label;
 mod media_picture;
 mod photo;
+mod poll;
+mod reaction_picker;
+mod reactions;
 mod reply;
 mod sticker;
 mod text;
 mod video;
+mod video_note;
+mod web_page;
 
 use std::cell::RefCell;
 
 use adw::prelude::*;
 use gettextrs::gettext;
 use glib::clone;
+use gtk::gdk;
 use gtk::gio;
 use gtk::glib;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
+use tdlib::enums::InputFile;
 use tdlib::enums::MessageContent;
 use tdlib::enums::StickerFormat;
+use tdlib::functions;
+use tdlib::types::InputFileId;
 
+use self::audio::MessageAudio;
 use self::base::MessageBase;
 use self::base::MessageBaseExt;
 use self::base::MessageBaseImpl;
 use self::bubble::MessageBubble;
 use self::document::MessageDocument;
 use self::indicators::MessageIndicators;
+use self::inline_keyboard::MessageInlineKeyboard;
 use self::label::MessageLabel;
 use self::media_picture::MediaPicture;
 use self::photo::MessagePhoto;
+use self::poll::MessagePoll;
+pub(crate) use self::reaction_picker::ReactionPicker;
+use self::reactions::MessageReactions;
 use self::reply::MessageReply;
 use self::sticker::MessageSticker;
 use self::text::MessageText;
 use self::video::MessageVideo;
+use self::video_note::MessageVideoNote;
+use self::web_page::MessageWebPage;
 use crate::components::Avatar;
+use crate::session::content::moderation;
+use crate::session::content::ChatHistory;
+use crate::session::content::ForwardWindow;
+use crate::session::Session;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatType;
 use crate::tdlib::Message;
 use crate::tdlib::MessageForwardOrigin;
 use crate::tdlib::MessageSender;
+use crate::tdlib::User;
 use crate::utils::spawn;
 
 const AVATAR_SIZE: i32 = 32;
@@ -57,13 +80,34 @@ mod imp {
             button: 1;
             released => $on_released() swapped;
         }
+
+        GestureClick {
+            button: 1;
+            propagation-phase: capture;
+            pressed => $on_selection_pressed() swapped;
+        }
+
+        GestureLongPress {
+            pressed => $on_long_pressed() swapped;
+        }
     }
     "#)]
     pub(crate) struct MessageRow {
         /// A `Message` or `SponsoredMessage`
         pub(super) message: RefCell<Option<glib::Object>>,
+        /// The box containing the content and the inline keyboard below it
+        pub(super) content_box: gtk::Box,
         pub(super) content: RefCell<Option<gtk::Widget>>,
+        /// The reactions of the content that isn't shown in a bubble, like stickers
+        pub(super) reactions: MessageReactions,
+        pub(super) inline_keyboard: MessageInlineKeyboard,
         pub(super) avatar: RefCell<Option<Avatar>>,
+        pub(super) is_pinned_handler: RefCell<Option<glib::SignalHandlerId>>,
+        /// The handler of the changes of the favorite stickers or of the saved animations,
+        /// depending on the content of the message
+        pub(super) saved_media_handler: RefCell<Option<(Session, glib::SignalHandlerId)>>,
+        pub(super) selection_check: RefCell<Option<gtk::CheckButton>>,
+        pub(super) chat_history_handler: RefCell<Option<(ChatHistory, glib::SignalHandlerId)>>,
     }
 
     #[glib::object_subclass]
@@ -80,12 +124,90 @@ mod imp {
                 widget.reply()
             });
             klass.install_action("message-row.edit", None, move |widget, _, _| widget.edit());
+            klass.install_action("message-row.forward", None, move |widget, _, _| {
+                widget.forward()
+            });
+            klass.install_action(
+                "message-row.add-favorite-sticker",
+                None,
+                move |widget, _, _| widget.set_sticker_favorite(true),
+            );
+            klass.install_action(
+                "message-row.remove-favorite-sticker",
+                None,
+                move |widget, _, _| widget.set_sticker_favorite(false),
+            );
+            klass.install_action("message-row.save-animation", None, move |widget, _, _| {
+                widget.set_animation_saved(true)
+            });
+            klass.install_action(
+                "message-row.remove-saved-animation",
+                None,
+                move |widget, _, _| widget.set_animation_saved(false),
+            );
+            klass.install_action("message-row.select", None, move |widget, _, _| {
+                widget.select()
+            });
             klass.install_action("message-row.revoke-delete", None, move |widget, _, _| {
                 widget.show_delete_dialog(true)
             });
             klass.install_action("message-row.delete", None, move |widget, _, _| {
                 widget.show_delete_dialog(false)
             });
+            klass.install_action("message-row.react", Some("s"), move |widget, _, variant| {
+                let emoji = variant.and_then(|v| v.get::<String>()).unwrap();
+                widget.toggle_reaction(emoji);
+            });
+            klass.install_action("message-row.search-sender", None, move |widget, _, _| {
+                widget.search_sender()
+            });
+            klass.install_action("message-row.pin", None, move |widget, _, _| {
+                widget.set_pinned(true)
+            });
+            klass.install_action("message-row.unpin", None, move |widget, _, _| {
+                widget.set_pinned(false)
+            });
+            klass.install_action_async(
+                "message-row.promote-sender",
+                None,
+                |widget, _, _| async move {
+                    if let Some((window, chat, user)) = widget.sender_context() {
+                        moderation::promote_member(&window, &chat, &user).await;
+                    }
+                },
+            );
+            klass.install_action_async(
+                "message-row.restrict-sender",
+                None,
+                |widget, _, _| async move {
+                    if let Some((window, chat, user)) = widget.sender_context() {
+                        moderation::restrict_member(&window, &chat, &user).await;
+                    }
+                },
+            );
+            klass.install_action_async(
+                "message-row.remove-sender",
+                None,
+                |widget, _, _| async move {
+                    if let Some((window, chat, user)) = widget.sender_context() {
+                        moderation::remove_member(&window, &chat, &user).await;
+                    }
+                },
+            );
+            klass.install_action_async("message-row.ban-sender", None, |widget, _, _| async move {
+                if let Some((window, chat, user)) = widget.sender_context() {
+                    moderation::ban_member(&window, &chat, &user).await;
+                }
+            });
+            klass.install_action_async(
+                "message-row.delete-sender-messages",
+                None,
+                |widget, _, _| async move {
+                    if let Some((window, chat, user)) = widget.sender_context() {
+                        moderation::delete_member_messages(&window, &chat, &user).await;
+                    }
+                },
+            );
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -121,18 +243,63 @@ mod imp {
             }
         }
 
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.content_box.set_orientation(gtk::Orientation::Vertical);
+            self.content_box.set_hexpand(true);
+            self.content_box.set_valign(gtk::Align::Start);
+            self.reactions.set_visible(false);
+            self.content_box.append(&self.reactions);
+            self.content_box.append(&self.inline_keyboard);
+            self.content_box.set_parent(&*self.obj());
+        }
+
         fn dispose(&self) {
+            if let Some(selection_check) = self.selection_check.borrow().as_ref() {
+                selection_check.unparent();
+            }
+
             if let Some(avatar) = self.avatar.borrow().as_ref() {
                 avatar.unparent();
             }
 
-            if let Some(content) = self.content.borrow().as_ref() {
-                content.unparent();
+            self.content_box.unparent();
+
+            if let Some((session, handler)) = self.saved_media_handler.take() {
+                session.disconnect(handler);
             }
         }
     }
 
-    impl WidgetImpl for MessageRow {}
+    impl WidgetImpl for MessageRow {
+        fn root(&self) {
+            self.parent_root();
+
+            let obj = self.obj();
+            if let Some(chat_history) = obj
+                .ancestor(ChatHistory::static_type())
+                .and_downcast::<ChatHistory>()
+            {
+                let handler = chat_history.connect_notify_local(
+                    Some("n-selected-messages"),
+                    clone!(@weak obj => move |_, _| obj.update_selection()),
+                );
+                self.chat_history_handler
+                    .replace(Some((chat_history, handler)));
+            }
+
+            obj.update_selection();
+        }
+
+        fn unroot(&self) {
+            if let Some((chat_history, handler)) = self.chat_history_handler.take() {
+                chat_history.disconnect(handler);
+            }
+
+            self.parent_unroot();
+        }
+    }
 }
 
 glib::wrapper! {
@@ -157,6 +324,87 @@ impl MessageRow {
         }
     }
 
+    #[template_callback]
+    fn on_selection_pressed(&self, _n_press: i32, _x: f64, _y: f64, gesture: &gtk::GestureClick) {
+        // In the selection mode, clicking anywhere on the row toggles its selection
+        let imp = self.imp();
+        if let Some((chat_history, _)) = &*imp.chat_history_handler.borrow() {
+            if chat_history.n_selected_messages() == 0 {
+                return;
+            }
+
+            if let Some(message) = imp.message.borrow().and_downcast_ref::<Message>() {
+                chat_history
+                    .set_message_selected(message, !chat_history.is_message_selected(message));
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+            }
+        }
+    }
+
+    #[template_callback]
+    fn on_long_pressed(&self, _x: f64, _y: f64, gesture: &gtk::GestureLongPress) {
+        // Long presses on touchscreens show the message menu instead
+        let is_touch = gesture
+            .device()
+            .map(|device| device.source() == gdk::InputSource::Touchscreen)
+            .unwrap_or_default();
+
+        if !is_touch {
+            self.select();
+        }
+    }
+
+    fn select(&self) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            self.activate_action(
+                "chat-history.select-message",
+                Some(&message.id().to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Shows or hides the selection check button, depending on whether the chat history
+    /// is in the selection mode.
+    fn update_selection(&self) {
+        let imp = self.imp();
+
+        let (selection_mode, is_selected) = match (
+            &*imp.chat_history_handler.borrow(),
+            imp.message.borrow().and_downcast_ref::<Message>(),
+        ) {
+            (Some((chat_history, _)), Some(message)) => (
+                chat_history.n_selected_messages() > 0,
+                chat_history.is_message_selected(message),
+            ),
+            _ => (false, false),
+        };
+
+        if selection_mode {
+            let selection_check = imp
+                .selection_check
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    // The row itself handles the clicks in the selection mode
+                    let selection_check = gtk::CheckButton::builder()
+                        .valign(gtk::Align::Center)
+                        .can_target(false)
+                        .focusable(false)
+                        .build();
+
+                    // Insert at the beginning
+                    selection_check.insert_after(self, gtk::Widget::NONE);
+
+                    selection_check
+                })
+                .clone();
+
+            selection_check.set_active(is_selected);
+        } else if let Some(selection_check) = imp.selection_check.take() {
+            selection_check.unparent();
+        }
+    }
+
     fn reply(&self) {
         if let Ok(message) = self.message().downcast::<Message>() {
             self.activate_action("chat-history.reply", Some(&message.id().to_variant()))
@@ -171,6 +419,107 @@ impl MessageRow {
         }
     }
 
+    fn forward(&self) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            if let Some(window) = self.root().and_downcast::<gtk::Window>() {
+                ForwardWindow::new(&window, vec![message]).present();
+            }
+        }
+    }
+
+    fn search_sender(&self) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            self.activate_action(
+                "chat-history.search-sender",
+                Some(&message.id().to_variant()),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Returns what's needed by the moderation actions on the sender of the message.
+    fn sender_context(&self) -> Option<(gtk::Window, Chat, User)> {
+        let message = self.message().downcast::<Message>().ok()?;
+        let user = message.sender().as_user()?.clone();
+        let window = self.root().and_downcast::<gtk::Window>()?;
+
+        Some((window, message.chat(), user))
+    }
+
+    fn toggle_reaction(&self, emoji: String) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            spawn(async move {
+                if let Err(e) = message.toggle_reaction(emoji).await {
+                    log::warn!("Error on toggling message reaction: {e:?}");
+                }
+            });
+        }
+    }
+
+    fn set_sticker_favorite(&self, favorite: bool) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            if let MessageContent::MessageSticker(data) = message.content().0 {
+                let sticker = InputFile::Id(InputFileId {
+                    id: data.sticker.sticker.id,
+                });
+                let client_id = message.chat().session().client_id();
+
+                spawn(async move {
+                    let result = if favorite {
+                        functions::add_favorite_sticker(sticker, client_id).await
+                    } else {
+                        functions::remove_favorite_sticker(sticker, client_id).await
+                    };
+
+                    if let Err(e) = result {
+                        log::warn!("Error on changing favorite state of a sticker: {e:?}");
+                    }
+                });
+            }
+        }
+    }
+
+    fn set_animation_saved(&self, saved: bool) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            if let MessageContent::MessageAnimation(data) = message.content().0 {
+                let animation = InputFile::Id(InputFileId {
+                    id: data.animation.animation.id,
+                });
+                let client_id = message.chat().session().client_id();
+
+                spawn(async move {
+                    let result = if saved {
+                        functions::add_saved_animation(animation, client_id).await
+                    } else {
+                        functions::remove_saved_animation(animation, client_id).await
+                    };
+
+                    if let Err(e) = result {
+                        log::warn!("Error on changing saved state of an animation: {e:?}");
+                    }
+                });
+            }
+        }
+    }
+
+    fn set_pinned(&self, pinned: bool) {
+        if let Ok(message) = self.message().downcast::<Message>() {
+            spawn(async move {
+                let result = if pinned {
+                    message.pin().await
+                } else {
+                    message.unpin().await
+                };
+
+                if let Err(e) = result {
+                    log::warn!(
+                        "Error on changing pinned state of a message (pinned = {pinned}): {e:?}"
+                    );
+                }
+            });
+        }
+    }
+
     fn show_delete_dialog(&self, revoke: bool) {
         let window: gtk::Window = self.root().and_then(|root| root.downcast().ok()).unwrap();
 
@@ -240,8 +589,8 @@ impl MessageRow {
                         avatar.set_size(AVATAR_SIZE);
                         avatar.set_valign(gtk::Align::End);
 
-                        // Insert at the beginning
-                        avatar.insert_after(self, gtk::Widget::NONE);
+                        // Insert at the beginning, after the selection check button
+                        avatar.insert_after(self, imp.selection_check.borrow().as_ref());
 
                         *avatar_borrow = Some(avatar.clone());
                         avatar
@@ -282,18 +631,57 @@ impl MessageRow {
         }
 
         self.update_content(message.clone());
+        imp.inline_keyboard
+            .set_message(message.downcast_ref::<Message>().cloned());
+
+        if let Some(handler) = imp.is_pinned_handler.take() {
+            if let Some(old_message) = imp.message.borrow().as_ref() {
+                old_message.disconnect(handler);
+            }
+        }
+        if let Some(message) = message.downcast_ref::<Message>() {
+            let handler = message.connect_notify_local(
+                Some("is-pinned"),
+                clone!(@weak self as obj => move |_, _| obj.update_actions()),
+            );
+            imp.is_pinned_handler.replace(Some(handler));
+        }
+
+        if let Some((session, handler)) = imp.saved_media_handler.take() {
+            session.disconnect(handler);
+        }
+        if let Some(message) = message.downcast_ref::<Message>() {
+            let session = message.chat().session();
+            let handler = match message.content().0 {
+                MessageContent::MessageSticker(_) => {
+                    Some(session.connect_favorite_stickers_changed(
+                        clone!(@weak self as obj => move |_| obj.update_actions()),
+                    ))
+                }
+                MessageContent::MessageAnimation(_) => {
+                    Some(session.connect_saved_animations_changed(
+                        clone!(@weak self as obj => move |_| obj.update_actions()),
+                    ))
+                }
+                _ => None,
+            };
+            if let Some(handler) = handler {
+                imp.saved_media_handler.replace(Some((session, handler)));
+            }
+        }
 
         imp.message.replace(Some(message));
 
         // TODO: Update actions when needed (e.g. chat permissions change)
         self.update_actions();
+        self.update_selection();
 
         self.notify("message");
     }
 
     fn can_reply_to_message(&self) -> bool {
         if let Some(message) = self.message().downcast_ref::<Message>() {
-            can_send_messages_in_chat(&message.chat())
+            message.chat().can_send_messages()
         } else {
             false
         }
@@ -304,7 +692,7 @@ impl MessageRow {
             let is_text_message = matches!(message.content().0, MessageContent::MessageText(_));
 
             // TODO: Support more message types in the future
-            is_text_message && message.can_be_edited() && can_send_messages_in_chat(&message.chat())
+            is_text_message && message.can_be_edited() && message.chat().can_send_messages()
         } else {
             false
         }
@@ -315,18 +703,112 @@ impl MessageRow {
         self.action_set_enabled("message-row.edit", self.can_edit_message());
 
         if let Some(message) = self.message().downcast_ref::<Message>() {
+            self.action_set_enabled("message-row.forward", message.can_be_forwarded());
+            self.action_set_enabled("message-row.select", true);
             self.action_set_enabled("message-row.delete", message.can_be_deleted_only_for_self());
             self.action_set_enabled(
                 "message-row.revoke-delete",
                 message.can_be_deleted_for_all_users(),
             );
+
+            let is_group = match message.chat().type_() {
+                ChatType::BasicGroup(_) => true,
+                ChatType::Supergroup(supergroup) => !supergroup.is_channel(),
+                _ => false,
+            };
+            self.action_set_enabled("message-row.search-sender", is_group);
+
+            let can_pin_messages = message.chat().can_pin_messages();
+            self.action_set_enabled("message-row.pin", can_pin_messages && !message.is_pinned());
+            self.action_set_enabled("message-row.unpin", can_pin_messages && message.is_pinned());
+
+            let is_favorite_sticker = match message.content().0 {
+                MessageContent::MessageSticker(data) => Some(
+                    message
+                        .chat()
+                        .session()
+                        .is_favorite_sticker(data.sticker.sticker.id),
+                ),
+                _ => None,
+            };
+            self.action_set_enabled(
+                "message-row.add-favorite-sticker",
+                is_favorite_sticker == Some(false),
+            );
+            self.action_set_enabled(
+                "message-row.remove-favorite-sticker",
+                is_favorite_sticker == Some(true),
+            );
+
+            let is_saved_animation = match message.content().0 {
+                MessageContent::MessageAnimation(data) => Some(
+                    message
+                        .chat()
+                        .session()
+                        .is_saved_animation(data.animation.animation.id),
+                ),
+                _ => None,
+            };
+            self.action_set_enabled(
+                "message-row.save-animation",
+                is_saved_animation == Some(false),
+            );
+            self.action_set_enabled(
+                "message-row.remove-saved-animation",
+                is_saved_animation == Some(true),
+            );
+
+            // Moderation actions can only be done on other users
+            let chat = message.chat();
+            let can_moderate =
+                is_group && !message.is_outgoing() && message.sender().as_user().is_some();
+            let is_supergroup = matches!(chat.type_(), ChatType::Supergroup(_));
+            self.action_set_enabled(
+                "message-row.promote-sender",
+                can_moderate && chat.can_promote_members(),
+            );
+            self.action_set_enabled(
+                "message-row.restrict-sender",
+                can_moderate
+                    && chat.can_restrict_members()
+                    && moderation::supports_restrictions(&chat),
+            );
+            self.action_set_enabled(
+                "message-row.remove-sender",
+                can_moderate && chat.can_restrict_members(),
+            );
+            self.action_set_enabled(
+                "message-row.ban-sender",
+                can_moderate && chat.can_restrict_members() && is_supergroup,
+            );
+            self.action_set_enabled(
+                "message-row.delete-sender-messages",
+                can_moderate && chat.can_delete_messages() && is_supergroup,
+            );
         } else {
+            self.action_set_enabled("message-row.forward", false);
+            self.action_set_enabled("message-row.select", false);
             self.action_set_enabled("message-row.delete", false);
             self.action_set_enabled("message-row.revoke-delete", false);
+            self.action_set_enabled("message-row.search-sender", false);
+            self.action_set_enabled("message-row.pin", false);
+            self.action_set_enabled("message-row.unpin", false);
+            self.action_set_enabled("message-row.add-favorite-sticker", false);
+            self.action_set_enabled("message-row.remove-favorite-sticker", false);
+            self.action_set_enabled("message-row.save-animation", false);
+            self.action_set_enabled("message-row.remove-saved-animation", false);
+            self.action_set_enabled("message-row.promote-sender", false);
+            self.action_set_enabled("message-row.restrict-sender", false);
+            self.action_set_enabled("message-row.remove-sender", false);
+            self.action_set_enabled("message-row.ban-sender", false);
+            self.action_set_enabled("message-row.delete-sender-messages", false);
         }
     }
 
     fn update_content(&self, message: glib::Object) {
+        // Bubbles show the reactions themselves
+        let mut reactions_message = None;
+
         let is_outgoing = if let Some(message_) = message.downcast_ref::<Message>() {
             // Do not mark channel messages as outgoing
             let is_outgoing = match message_.chat().type_() {
@@ -335,28 +817,45 @@ impl MessageRow {
             };
 
             match message_.content().0 {
-                // FIXME: Re-enable MessageVideo when
-                // https://github.com/paper-plane-developers/paper-plane/issues/410 is fixed
-                MessageContent::MessageAnimation(_) /*| MessageContent::MessageVideo(_)*/ => {
+                MessageContent::MessageAnimation(_) | MessageContent::MessageVideo(_) => {
                     self.update_specific_content::<_, MessageVideo>(message_.clone());
                 }
                 MessageContent::MessageAnimatedEmoji(data)
-                    if data.animated_emoji.sticker.clone().map(
-                        |s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs)
-                    ).unwrap_or_default() => {
+                    if data
+                        .animated_emoji
+                        .sticker
+                        .clone()
+                        .map(|s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs))
+                        .unwrap_or_default() =>
+                {
                     self.update_specific_content::<_, MessageSticker>(message_.clone());
+                    reactions_message = Some(message_.clone());
                 }
                 MessageContent::MessagePhoto(_) => {
                     self.update_specific_content::<_, MessagePhoto>(message_.clone());
                 }
                 MessageContent::MessageSticker(data)
-                    if matches!(data.sticker.format, StickerFormat::Webp | StickerFormat::Tgs) =>
+                    if matches!(
+                        data.sticker.format,
+                        StickerFormat::Webp | StickerFormat::Tgs
+                    ) =>
                 {
                     self.update_specific_content::<_, MessageSticker>(message_.clone());
+                    reactions_message = Some(message_.clone());
+                }
+                MessageContent::MessageAudio(_) | MessageContent::MessageVoiceNote(_) => {
+                    self.update_specific_content::<_, MessageAudio>(message_.clone());
                 }
                 MessageContent::MessageDocument(_) => {
                     self.update_specific_content::<_, MessageDocument>(message_.clone());
                 }
+                MessageContent::MessageVideoNote(_) => {
+                    self.update_specific_content::<_, MessageVideoNote>(message_.clone());
+                    reactions_message = Some(message_.clone());
+                }
+                MessageContent::MessagePoll(_) => {
+                    self.update_specific_content::<_, MessagePoll>(message_.clone());
+                }
                 _ => {
                     self.update_specific_content::<_, MessageText>(message);
                 }
@@ -368,61 +867,43 @@ impl MessageRow {
             false
         };
 
-        let content_ref = self.imp().content.borrow();
+        let imp = self.imp();
+        imp.reactions.set_message(reactions_message);
+
+        let content_ref = imp.content.borrow();
         let content = content_ref.as_ref().unwrap();
 
-        if is_outgoing {
-            content.set_halign(gtk::Align::End);
+        let halign = if is_outgoing {
+            gtk::Align::End
         } else {
-            content.set_halign(gtk::Align::Start);
-        }
+            gtk::Align::Start
+        };
+        content.set_halign(halign);
+        imp.content_box.set_halign(halign);
     }
 
     fn update_specific_content<M, B>(&self, message: M)
     where
         B: MessageBaseExt<Message = M>,
     {
-        let mut content_ref = self.imp().content.borrow_mut();
+        let imp = self.imp();
+        let mut content_ref = imp.content.borrow_mut();
         match content_ref.as_ref().and_then(|c| c.downcast_ref::<B>()) {
             Some(content) => {
                 content.set_message(message);
             }
             None => {
                 if let Some(old_content) = &*content_ref {
-                    old_content.unparent();
+                    imp.content_box.remove(old_content);
                 }
 
                 let content = B::new(&message);
-                content.set_hexpand(true);
-                content.set_valign(gtk::Align::Start);
 
-                // Insert at the end
-                content.insert_before(self, gtk::Widget::NONE);
+                // Insert above the inline keyboard
+                imp.content_box.prepend(&content);
 
                 *content_ref = Some(content.upcast());
             }
         }
     }
 }
-
-fn can_send_messages_in_chat(chat: &Chat) -> bool {
-    use tdlib::enums::ChatMemberStatus::*;
-    let member_status = match chat.type_() {
-        ChatType::Supergroup(supergroup) => Some(supergroup.status()),
-        ChatType::BasicGroup(supergroup) => Some(supergroup.status()),
-        _ => None,
-    };
-    member_status
-        .map(|s| match s.0 {
-            Creator(_) => true,
-            Administrator(_) => true,
-            Member => chat.permissions().0.can_send_basic_messages,
-            Restricted(data) => {
-                chat.permissions().0.can_send_basic_messages
-                    && data.permissions.can_send_basic_messages
-            }
-            Left => false,
-            Banned(_) => false,
-        })
-        .unwrap_or(true)
-}
@@ -16,6 +16,7 @@ use crate::session::content::message_row::MediaPicture;
 use crate::session::content::message_row::MessageBase;
 use crate::session::content::message_row::MessageBaseImpl;
 use crate::session::content::message_row::MessageBubble;
+use crate::session::content::MediaViewer;
 use crate::tdlib::BoxedMessageContent;
 use crate::tdlib::Message;
 use crate::utils::decode_image_from_path;
@@ -46,6 +47,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
+            klass.bind_template_instance_callbacks();
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -82,6 +84,8 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
 
+            self.picture.set_cursor_from_name(Some("pointer"));
+
             self.obj().connect_scale_factor_notify(|obj| {
                 obj.update_photo(obj.imp().message.borrow().as_ref().unwrap());
             });
@@ -147,7 +151,15 @@ impl MessageBaseExt for MessagePhoto {
     }
 }
 
+#[gtk::template_callbacks]
 impl MessagePhoto {
+    #[template_callback]
+    fn on_picture_released(&self) {
+        if let Some(parent) = self.root().and_downcast::<gtk::Window>() {
+            MediaViewer::new(&parent, &self.message()).present();
+        }
+    }
+
     fn update_photo(&self, message: &Message) {
         if let MessageContent::MessagePhoto(mut data) = message.content().0 {
             let imp = self.imp();
@@ -0,0 +1,440 @@
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::enums::PollType;
+use tdlib::types::Poll;
+use tdlib::types::PollOption;
+
+use super::base::MessageBaseExt;
+use crate::i18n::ngettext_f;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageBubble;
+use crate::tdlib::Message;
+use crate::utils::parse_formatted_text;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessagePoll : $MessageBase {
+        $MessageBubble message_bubble {
+            styles ["poll"]
+
+            prefix: Box {
+                orientation: vertical;
+                spacing: 6;
+
+                Label question_label {
+                    wrap: true;
+                    wrap-mode: word_char;
+                    xalign: 0;
+
+                    styles ["heading"]
+                }
+
+                Label type_label {
+                    xalign: 0;
+
+                    styles ["dim-label", "caption"]
+                }
+
+                Box options_box {
+                    orientation: vertical;
+                    spacing: 3;
+                }
+
+                Label explanation_label {
+                    visible: false;
+                    wrap: true;
+                    wrap-mode: word_char;
+                    xalign: 0;
+                    use-markup: true;
+
+                    styles ["explanation"]
+                }
+
+                Button vote_button {
+                    visible: false;
+                    sensitive: false;
+                    label: _("_Vote");
+                    use-underline: true;
+                    clicked => $on_vote_clicked() swapped;
+
+                    styles ["flat"]
+                }
+
+                Button retract_button {
+                    visible: false;
+                    label: _("_Retract Vote");
+                    use-underline: true;
+                    clicked => $on_retract_clicked() swapped;
+
+                    styles ["flat"]
+                }
+
+                Label total_voters_label {
+                    xalign: 0;
+
+                    styles ["dim-label", "caption", "numeric"]
+                }
+            };
+        }
+    }
+    "#)]
+    pub(crate) struct MessagePoll {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        /// The options chosen in a poll with multiple answers, before voting
+        pub(super) chosen_options: RefCell<Vec<i32>>,
+        #[template_child]
+        pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) question_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) type_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) options_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) explanation_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) vote_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) retract_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) total_voters_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessagePoll {
+        const NAME: &'static str = "MessagePoll";
+        type Type = super::MessagePoll;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessagePoll {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for MessagePoll {}
+    impl MessageBaseImpl for MessagePoll {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessagePoll(ObjectSubclass<imp::MessagePoll>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessagePoll {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        imp.chosen_options.borrow_mut().clear();
+        imp.message_bubble.update_from_message(&message, false);
+
+        // Votes are received as updates of the message content
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_poll(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+        self.update_poll(&message);
+
+        imp.message.replace(Some(message));
+        self.notify("message");
+    }
+}
+
+#[gtk::template_callbacks]
+impl MessagePoll {
+    fn update_poll(&self, message: &Message) {
+        let MessageContent::MessagePoll(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+        let poll = data.poll;
+        let has_voted = poll.options.iter().any(|o| o.is_chosen);
+        let show_results = has_voted || poll.is_closed;
+        let is_quiz = matches!(poll.r#type, PollType::Quiz(_));
+        let allow_multiple_answers = allows_multiple_answers(&poll);
+
+        imp.message_bubble.set_label(String::new());
+        imp.question_label.set_label(&poll.question);
+        imp.type_label.set_label(&poll_type_label(&poll));
+
+        while let Some(child) = imp.options_box.first_child() {
+            imp.options_box.remove(&child);
+        }
+
+        // Options of single answer polls are shown as radio buttons
+        let mut group: Option<gtk::CheckButton> = None;
+        for (option_id, option) in poll.options.iter().enumerate() {
+            let row = self.option_row(&poll, option_id as i32, option, show_results);
+
+            if let Some(check_button) = row.first_child().and_downcast::<gtk::CheckButton>() {
+                if !allow_multiple_answers {
+                    match &group {
+                        Some(group) => check_button.set_group(Some(group)),
+                        None => group = Some(check_button.clone()),
+                    }
+                }
+            }
+
+            if show_results {
+                imp.options_box.append(&row);
+            } else {
+                let button = gtk::Button::builder().child(&row).build();
+                button.add_css_class("flat");
+                button.add_css_class("poll-option");
+                button.connect_clicked(clone!(@weak self as obj => move |_| {
+                    obj.choose_option(option_id as i32);
+                }));
+                imp.options_box.append(&button);
+            }
+        }
+
+        let explanation = match poll.r#type {
+            PollType::Quiz(data) if show_results && !data.explanation.text.is_empty() => {
+                Some(parse_formatted_text(data.explanation))
+            }
+            _ => None,
+        };
+        imp.explanation_label.set_visible(explanation.is_some());
+        imp.explanation_label
+            .set_label(&explanation.unwrap_or_default());
+
+        imp.vote_button
+            .set_visible(allow_multiple_answers && !show_results);
+        imp.vote_button
+            .set_sensitive(!imp.chosen_options.borrow().is_empty());
+
+        // Answers to quizzes can't be changed
+        imp.retract_button
+            .set_visible(has_voted && !poll.is_closed && !is_quiz);
+
+        let n_voters = poll.total_voter_count;
+        imp.total_voters_label.set_label(&if n_voters == 0 {
+            if is_quiz {
+                gettext("No answers yet")
+            } else {
+                gettext("No votes yet")
+            }
+        } else if is_quiz {
+            ngettext_f(
+                "{num} answer",
+                "{num} answers",
+                n_voters as u32,
+                &[("num", &n_voters.to_string())],
+            )
+        } else {
+            ngettext_f(
+                "{num} vote",
+                "{num} votes",
+                n_voters as u32,
+                &[("num", &n_voters.to_string())],
+            )
+        });
+    }
+
+    /// Creates the widget of a poll option. Before voting, it's a check button with the option
+    /// text, otherwise it shows the percentage of votes and whether the option was chosen.
+    fn option_row(
+        &self,
+        poll: &Poll,
+        option_id: i32,
+        option: &PollOption,
+        show_results: bool,
+    ) -> gtk::Widget {
+        let text_label = gtk::Label::builder()
+            .label(&option.text)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .xalign(0.0)
+            .hexpand(true)
+            .build();
+
+        if !show_results {
+            let row = gtk::Box::builder().spacing(6).build();
+
+            // The button of the option handles the clicks
+            let check_button = gtk::CheckButton::builder()
+                .active(self.imp().chosen_options.borrow().contains(&option_id))
+                .can_target(false)
+                .focusable(false)
+                .build();
+
+            row.append(&check_button);
+            row.append(&text_label);
+
+            return row.upcast();
+        }
+
+        let correct_option_id = match &poll.r#type {
+            PollType::Quiz(data) => Some(data.correct_option_id),
+            PollType::Regular(_) => None,
+        };
+        let is_correct = correct_option_id == Some(option_id);
+
+        let percentage_label = gtk::Label::builder()
+            .label(&format!("{}%", option.vote_percentage))
+            .width_chars(4)
+            .xalign(1.0)
+            .build();
+        percentage_label.add_css_class("numeric");
+        percentage_label.add_css_class("heading");
+
+        let top_box = gtk::Box::builder().spacing(6).build();
+        top_box.append(&percentage_label);
+        top_box.append(&text_label);
+
+        let progress_bar = gtk::ProgressBar::builder()
+            .fraction(option.vote_percentage as f64 / 100.0)
+            .build();
+
+        if is_correct {
+            top_box.append(&gtk::Image::from_icon_name("object-select-symbolic"));
+            progress_bar.add_css_class("success");
+        } else if option.is_chosen {
+            if correct_option_id.is_some() {
+                top_box.append(&gtk::Image::from_icon_name("window-close-symbolic"));
+                progress_bar.add_css_class("error");
+            } else {
+                top_box.append(&gtk::Image::from_icon_name("object-select-symbolic"));
+            }
+        }
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(3)
+            .build();
+        row.add_css_class("poll-option");
+        row.append(&top_box);
+        row.append(&progress_bar);
+
+        row.upcast()
+    }
+
+    fn choose_option(&self, option_id: i32) {
+        let imp = self.imp();
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+
+        let allow_multiple_answers = match message.content().0 {
+            MessageContent::MessagePoll(data) => allows_multiple_answers(&data.poll),
+            _ => false,
+        };
+
+        if allow_multiple_answers {
+            {
+                let mut chosen_options = imp.chosen_options.borrow_mut();
+                match chosen_options.iter().position(|id| *id == option_id) {
+                    Some(position) => {
+                        chosen_options.remove(position);
+                    }
+                    None => chosen_options.push(option_id),
+                }
+            }
+
+            self.update_poll(&message);
+        } else {
+            self.vote(vec![option_id]);
+        }
+    }
+
+    fn vote(&self, option_ids: Vec<i32>) {
+        if let Some(message) = self.imp().message.borrow().clone() {
+            spawn(async move {
+                if let Err(e) = message.set_poll_answer(option_ids).await {
+                    log::warn!("Error setting a poll answer: {e:?}");
+                }
+            });
+        }
+    }
+
+    #[template_callback]
+    fn on_vote_clicked(&self) {
+        let mut option_ids = self.imp().chosen_options.take();
+        option_ids.sort_unstable();
+        self.vote(option_ids);
+    }
+
+    #[template_callback]
+    fn on_retract_clicked(&self) {
+        self.vote(Vec::new());
+    }
+}
+
+fn allows_multiple_answers(poll: &Poll) -> bool {
+    matches!(&poll.r#type, PollType::Regular(data) if data.allow_multiple_answers)
+}
+
+fn poll_type_label(poll: &Poll) -> String {
+    let is_quiz = matches!(poll.r#type, PollType::Quiz(_));
+
+    if poll.is_closed {
+        gettext("Final Results")
+    } else if is_quiz && poll.is_anonymous {
+        gettext("Anonymous Quiz")
+    } else if is_quiz {
+        gettext("Quiz")
+    } else if poll.is_anonymous {
+        gettext("Anonymous Poll")
+    } else {
+        gettext("Public Poll")
+    }
+}
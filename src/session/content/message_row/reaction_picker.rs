@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+
+use crate::tdlib::Message;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageReactionPicker {
+        layout-manager: BinLayout {};
+
+        ScrolledWindow scrolled_window {
+            hscrollbar-policy: never;
+            propagate-natural-height: true;
+            max-content-height: 200;
+
+            FlowBox flow_box {
+                selection-mode: none;
+                column-spacing: 3;
+                row-spacing: 3;
+                homogeneous: true;
+                min-children-per-line: 8;
+                max-children-per-line: 8;
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct ReactionPicker {
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) request_generation: RequestGeneration,
+        #[template_child]
+        pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) flow_box: TemplateChild<gtk::FlowBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ReactionPicker {
+        const NAME: &'static str = "MessageReactionPicker";
+        type Type = super::ReactionPicker;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("reactionpicker");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ReactionPicker {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_visible(false);
+        }
+
+        fn dispose(&self) {
+            self.scrolled_window.unparent();
+        }
+    }
+
+    impl WidgetImpl for ReactionPicker {}
+}
+
+glib::wrapper! {
+    pub(crate) struct ReactionPicker(ObjectSubclass<imp::ReactionPicker>)
+        @extends gtk::Widget;
+}
+
+impl Default for ReactionPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReactionPicker {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<Message>) {
+        let imp = self.imp();
+
+        if *imp.message.borrow() == message {
+            return;
+        }
+
+        self.clear();
+
+        let generation = imp.request_generation.next();
+        imp.message.replace(message.clone());
+
+        if let Some(message) = message {
+            spawn(clone!(@weak self as obj => async move {
+                match message.available_reactions().await {
+                    Ok(reactions) => {
+                        if obj.imp().request_generation.is_current(generation) {
+                            obj.set_reactions(reactions);
+                        }
+                    }
+                    Err(e) => log::warn!("Error getting available reactions: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    fn set_reactions(&self, reactions: Vec<String>) {
+        let imp = self.imp();
+
+        for emoji in reactions {
+            let button = gtk::Button::builder()
+                .label(&emoji)
+                .action_name("message-row.react")
+                .action_target(&emoji.to_variant())
+                .build();
+            button.add_css_class("flat");
+            button.add_css_class("circular");
+
+            // Actions don't close the menu when activated from a custom child
+            button.connect_clicked(|button| {
+                if let Some(popover) = button.ancestor(gtk::Popover::static_type()) {
+                    popover.downcast::<gtk::Popover>().unwrap().popdown();
+                }
+            });
+
+            imp.flow_box.append(&button);
+        }
+
+        self.set_visible(imp.flow_box.first_child().is_some());
+    }
+
+    fn clear(&self) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.flow_box.first_child() {
+            imp.flow_box.remove(&child);
+        }
+        self.set_visible(false);
+    }
+}
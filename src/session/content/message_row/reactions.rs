@@ -0,0 +1,192 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::ReactionType;
+
+use crate::tdlib::Message;
+use crate::tdlib::MessageInteractionInfo;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageReactions {
+        layout-manager: BinLayout {};
+
+        FlowBox flow_box {
+            selection-mode: none;
+            column-spacing: 4;
+            row-spacing: 4;
+            max-children-per-line: 20;
+        }
+    }
+    "#)]
+    pub(crate) struct MessageReactions {
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) interaction_info_signal_group: OnceCell<glib::SignalGroup>,
+        #[template_child]
+        pub(super) flow_box: TemplateChild<gtk::FlowBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageReactions {
+        const NAME: &'static str = "MessageReactions";
+        type Type = super::MessageReactions;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_css_name("messagereactions");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageReactions {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let interaction_info_signal_group = glib::SignalGroup::new::<MessageInteractionInfo>();
+            interaction_info_signal_group.connect_notify_local(
+                Some("reactions"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_reactions();
+                }),
+            );
+            self.interaction_info_signal_group
+                .set(interaction_info_signal_group)
+                .unwrap();
+        }
+
+        fn dispose(&self) {
+            self.flow_box.unparent();
+        }
+    }
+
+    impl WidgetImpl for MessageReactions {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageReactions(ObjectSubclass<imp::MessageReactions>)
+        @extends gtk::Widget;
+}
+
+impl Default for MessageReactions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageReactions {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub(crate) fn message(&self) -> Option<Message> {
+        self.imp().message.borrow().clone()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<Message>) {
+        if self.message() == message {
+            return;
+        }
+
+        let imp = self.imp();
+
+        imp.interaction_info_signal_group
+            .get()
+            .unwrap()
+            .set_target(message.as_ref().map(Message::interaction_info));
+        imp.message.replace(message);
+
+        self.update_reactions();
+
+        self.notify("message");
+    }
+
+    fn update_reactions(&self) {
+        let imp = self.imp();
+
+        while let Some(child) = imp.flow_box.first_child() {
+            imp.flow_box.remove(&child);
+        }
+
+        let reactions = self
+            .message()
+            .map(|message| message.interaction_info().reactions())
+            .unwrap_or_default();
+
+        for reaction in reactions {
+            // Custom emoji reactions can't be rendered yet
+            let ReactionType::Emoji(data) = reaction.r#type else {
+                continue;
+            };
+
+            let button = gtk::Button::builder()
+                .label(format!("{} {}", data.emoji, reaction.total_count))
+                .build();
+            button.add_css_class("reaction");
+
+            if reaction.is_chosen {
+                button.add_css_class("chosen");
+            }
+
+            button.connect_clicked(clone!(@weak self as obj => move |_| {
+                obj.toggle_reaction(data.emoji.clone());
+            }));
+
+            imp.flow_box.append(&button);
+        }
+
+        self.set_visible(imp.flow_box.first_child().is_some());
+    }
+
+    fn toggle_reaction(&self, emoji: String) {
+        if let Some(message) = self.message() {
+            spawn(async move {
+                if let Err(e) = message.toggle_reaction(emoji).await {
+                    log::warn!("Error on toggling message reaction: {e:?}");
+                }
+            });
+        }
+    }
+}
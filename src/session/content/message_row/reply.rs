@@ -22,6 +22,11 @@ mod imp {
     #[properties(wrapper_type = super::MessageReply)]
     #[template(string = r#"
     template $MessageReply : Widget {
+        GestureClick {
+            button: 1;
+            released => $on_released() swapped;
+        }
+
         Separator separator {
             width-request: 2;
         }
@@ -74,6 +79,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            klass.bind_template_instance_callbacks();
             klass.set_layout_manager_type::<gtk::BoxLayout>();
             klass.set_css_name("messagereply");
         }
@@ -97,6 +103,8 @@ mod imp {
         }
 
         fn constructed(&self) {
+            self.obj().set_cursor_from_name(Some("pointer"));
+
             self.message_label
                 .set_label(&gettextrs::gettext("Loading…"));
 
@@ -119,7 +127,28 @@ glib::wrapper! {
         @extends gtk::Widget;
 }
 
+#[gtk::template_callbacks]
 impl MessageReply {
+    /// Jumps to the replied message in the chat history.
+    #[template_callback]
+    fn on_released(&self, n_press: i32, _x: f64, _y: f64) {
+        let message = self.message().unwrap();
+
+        // Replies to messages from other chats can't be shown in this chat history
+        if n_press != 1
+            || (message.reply_in_chat_id() != 0
+                && message.reply_in_chat_id() != message.chat().id())
+        {
+            return;
+        }
+
+        self.activate_action(
+            "chat-history.scroll-to-message",
+            Some(&message.reply_to_message_id().to_variant()),
+        )
+        .unwrap();
+    }
+
     pub(crate) fn new(message: &Message) -> Self {
         glib::Object::builder().property("message", message).build()
     }
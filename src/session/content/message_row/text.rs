@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 
 use gettextrs::gettext;
+use glib::clone;
 use glib::closure;
 use gtk::glib;
 use gtk::prelude::*;
@@ -8,15 +9,18 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use tdlib::enums::MessageContent;
+use tdlib::types;
 
 use super::base::MessageBaseExt;
 use crate::session::content::message_row::MessageBase;
 use crate::session::content::message_row::MessageBaseImpl;
 use crate::session::content::message_row::MessageBubble;
+use crate::session::content::message_row::MessageWebPage;
 use crate::tdlib::BoxedMessageContent;
 use crate::tdlib::Message;
 use crate::tdlib::SponsoredMessage;
 use crate::utils::parse_formatted_text;
+use crate::Session;
 
 mod imp {
     use super::*;
@@ -26,8 +30,11 @@ mod imp {
     pub(crate) struct MessageText {
         pub(super) bindings: RefCell<Vec<gtk::ExpressionWatch>>,
         pub(super) message: RefCell<Option<glib::Object>>,
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
         #[template_child]
         pub(super) message_bubble: TemplateChild<MessageBubble>,
+        #[template_child]
+        pub(super) web_page: TemplateChild<MessageWebPage>,
     }
 
     #[glib::object_subclass]
@@ -98,6 +105,14 @@ impl MessageBaseExt for MessageText {
             binding.unwatch();
         }
 
+        if let Some(handler_id) = imp.handler_id.take() {
+            imp.message
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .disconnect(handler_id);
+        }
+
         if let Some(message) = message.downcast_ref::<Message>() {
             imp.message_bubble.update_from_message(message, false);
 
@@ -108,6 +123,14 @@ impl MessageBaseExt for MessageText {
                 }))
                 .bind(&*imp.message_bubble, "label", Some(message));
             bindings.push(text_binding);
+
+            // Show the preview of the web page, if any
+            let handler_id =
+                message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                    obj.update_web_page(message.content().0, &message.chat().session());
+                }));
+            imp.handler_id.replace(Some(handler_id));
+            self.update_web_page(message.content().0, &message.chat().session());
         } else if let Some(sponsored_message) = message.downcast_ref::<SponsoredMessage>() {
             imp.message_bubble
                 .update_from_sponsored_message(sponsored_message);
@@ -121,6 +144,11 @@ impl MessageBaseExt for MessageText {
                 ))
                 .bind(&*imp.message_bubble, "label", Some(sponsored_message));
             bindings.push(text_binding);
+
+            self.update_web_page(
+                sponsored_message.content().0.clone(),
+                &sponsored_message.sponsor_chat().session(),
+            );
         } else {
             unreachable!("Unexpected message type: {:?}", message);
         }
@@ -130,6 +158,23 @@ impl MessageBaseExt for MessageText {
     }
 }
 
+impl MessageText {
+    fn update_web_page(&self, content: MessageContent, session: &Session) {
+        let imp = self.imp();
+
+        if let MessageContent::MessageText(types::MessageText {
+            web_page: Some(web_page),
+            ..
+        }) = content
+        {
+            imp.web_page.set_web_page(web_page, session);
+            imp.web_page.set_visible(true);
+        } else {
+            imp.web_page.set_visible(false);
+        }
+    }
+}
+
 fn format_message_content_text(content: MessageContent) -> String {
     match content {
         MessageContent::MessageText(content) => parse_formatted_text(content.text),
@@ -1,6 +1,7 @@
 use std::cell::Cell;
 use std::cell::RefCell;
 
+use gettextrs::gettext;
 use glib::clone;
 use gtk::gdk;
 use gtk::glib;
@@ -9,17 +10,25 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 use tdlib::enums::MessageContent;
+use tdlib::types::File;
 
 use super::base::MessageBaseExt;
 use crate::session::content::message_row::MediaPicture;
 use crate::session::content::message_row::MessageBase;
 use crate::session::content::message_row::MessageBaseImpl;
 use crate::session::content::message_row::MessageBubble;
+use crate::session::content::MediaViewer;
 use crate::tdlib::Message;
+use crate::utils::decode_minithumbnail;
+use crate::utils::format_playback_time;
 use crate::utils::parse_formatted_text;
 use crate::utils::spawn;
 use crate::Session;
 
+/// The maximum duration in seconds of the videos that are played automatically, without
+/// sound, like animations.
+const AUTOPLAY_MAX_DURATION: i32 = 30;
+
 mod imp {
     use super::*;
 
@@ -39,6 +48,37 @@ mod imp {
 
                     styles ["osd-indicator"]
                 }
+
+                [overlay]
+                Button status_button {
+                    halign: center;
+                    valign: center;
+                    visible: false;
+                    clicked => $on_status_button_clicked() swapped;
+
+                    styles ["osd", "circular", "status-button"]
+                }
+
+                [overlay]
+                Box controls_box {
+                    valign: end;
+                    visible: false;
+
+                    MediaControls media_controls {
+                        hexpand: true;
+                    }
+
+                    Button {
+                        valign: center;
+                        icon-name: "view-fullscreen-symbolic";
+                        tooltip-text: _("Fullscreen");
+                        clicked => $on_fullscreen_button_clicked() swapped;
+
+                        styles ["flat"]
+                    }
+
+                    styles ["osd", "toolbar"]
+                }
             };
         }
     }
@@ -47,12 +87,25 @@ mod imp {
         pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) message: RefCell<Option<Message>>,
         pub(super) is_animation: Cell<bool>,
+        /// The video file, as of the last update received
+        pub(super) file: RefCell<Option<File>>,
+        pub(super) duration: Cell<i32>,
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
+        /// Whether the video is being played with sound and controls, rather than
+        /// automatically
+        pub(super) is_playing_with_controls: Cell<bool>,
         #[template_child]
         pub(super) message_bubble: TemplateChild<MessageBubble>,
         #[template_child]
         pub(super) picture: TemplateChild<MediaPicture>,
         #[template_child]
         pub(super) indicator: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) status_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) controls_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) media_controls: TemplateChild<gtk::MediaControls>,
     }
 
     #[glib::object_subclass]
@@ -63,6 +116,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            klass.bind_template_instance_callbacks();
             klass.set_layout_manager_type::<gtk::BinLayout>();
         }
 
@@ -96,9 +150,36 @@ mod imp {
                 _ => unimplemented!(),
             }
         }
+
+        fn dispose(&self) {
+            if let Some(media) = self.media.take() {
+                media.pause();
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageVideo {
+        fn map(&self) {
+            self.parent_map();
+
+            // Resume the videos that are played automatically
+            if !self.is_playing_with_controls.get() {
+                if let Some(media) = &*self.media.borrow() {
+                    media.play();
+                }
+            }
+        }
+
+        fn unmap(&self) {
+            // Don't keep playing videos that have been scrolled away
+            if let Some(media) = &*self.media.borrow() {
+                media.pause();
+            }
+
+            self.parent_unmap();
+        }
     }
 
-    impl WidgetImpl for MessageVideo {}
     impl MessageBaseImpl for MessageVideo {}
 }
 
@@ -122,6 +203,7 @@ impl MessageBaseExt for MessageVideo {
             old_message.disconnect(handler_id);
         }
 
+        self.stop();
         imp.message_bubble.update_from_message(&message, true);
 
         let handler_id =
@@ -130,13 +212,14 @@ impl MessageBaseExt for MessageVideo {
             }));
         imp.handler_id.replace(Some(handler_id));
 
+        imp.message.replace(Some(message.clone()));
         self.update_content(message.content().0, &message.chat().session());
 
-        imp.message.replace(Some(message));
         self.notify("message");
     }
 }
 
+#[gtk::template_callbacks]
 impl MessageVideo {
     fn update_content(&self, content: MessageContent, session: &Session) {
         let imp = self.imp();
@@ -145,6 +228,7 @@ impl MessageVideo {
             if let MessageContent::MessageAnimation(data) = content {
                 imp.indicator.set_label("GIF");
                 imp.is_animation.set(true);
+                imp.duration.set(data.animation.duration);
                 (
                     data.caption,
                     data.animation.animation,
@@ -154,6 +238,7 @@ impl MessageVideo {
             } else if let MessageContent::MessageVideo(data) = content {
                 self.update_remaining_time(data.video.duration as i64);
                 imp.is_animation.set(false);
+                imp.duration.set(data.video.duration);
                 (
                     data.caption,
                     data.video.video,
@@ -169,31 +254,102 @@ impl MessageVideo {
 
         imp.picture.set_aspect_ratio(aspect_ratio);
 
+        // The content is updated again when the message is edited, in which case the
+        // video may not have changed
+        let is_same_file = imp.file.borrow().as_ref().map(|f| f.id) == Some(file.id);
+        if is_same_file && imp.media.borrow().is_some() {
+            return;
+        }
+        imp.file.replace(Some(file.clone()));
+
+        // Shown until the video is loaded, or until it's played for long videos
+        imp.picture.set_paintable(
+            minithumbnail
+                .and_then(|m| decode_minithumbnail(&m))
+                .as_ref(),
+        );
+
         if file.local.is_downloading_completed {
             self.load_video(&file.local.path);
-        } else {
-            imp.picture.set_paintable(
-                minithumbnail
-                    .and_then(|m| {
-                        gdk::Texture::from_bytes(&glib::Bytes::from_owned(glib::base64_decode(
-                            &m.data,
-                        )))
-                        .ok()
-                    })
-                    .as_ref(),
-            );
+            return;
+        }
 
+        if imp.is_animation.get() || imp.duration.get() <= AUTOPLAY_MAX_DURATION {
+            // Animations and short clips are small enough to be downloaded right away
             let file_id = file.id;
             spawn(clone!(@weak self as obj, @weak session => async move {
                 obj.download_video(file_id, &session).await;
             }));
+        } else {
+            self.update_download_status(&file);
         }
     }
 
+    #[template_callback]
+    fn on_status_button_clicked(&self) {
+        let imp = self.imp();
+
+        let Some(file) = imp.file.borrow().clone() else {
+            return;
+        };
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let session = message.chat().session();
+
+        if file.local.is_downloading_completed {
+            self.play_with_controls();
+        } else if file.local.is_downloading_active {
+            session.cancel_download_file(file.id);
+        } else {
+            let file_id = file.id;
+            session.download_file_with_updates(
+                file_id,
+                clone!(@weak self as obj => move |file| {
+                    // The row could have been reused for another message in the meantime
+                    if obj.imp().file.borrow().as_ref().map(|f| f.id) != Some(file_id) {
+                        return;
+                    }
+
+                    obj.imp().file.replace(Some(file.clone()));
+
+                    if file.local.is_downloading_completed {
+                        obj.load_video(&file.local.path);
+                    } else {
+                        obj.update_download_status(&file);
+                    }
+                }),
+            );
+        }
+    }
+
+    #[template_callback]
+    fn on_fullscreen_button_clicked(&self) {
+        let imp = self.imp();
+
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let Some(parent) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        if let Some(media) = &*imp.media.borrow() {
+            media.pause();
+        }
+
+        let viewer = MediaViewer::new(&parent, &message);
+        viewer.fullscreen();
+        viewer.present();
+    }
+
     async fn download_video(&self, file_id: i32, session: &Session) {
         match session.download_file(file_id).await {
             Ok(file) => {
-                self.load_video(&file.local.path);
+                // The row could have been reused for another message in the meantime
+                if self.imp().file.borrow().as_ref().map(|f| f.id) == Some(file_id) {
+                    self.load_video(&file.local.path);
+                }
             }
             Err(e) => {
                 log::warn!("Failed to download a video: {e:?}");
@@ -204,11 +360,33 @@ impl MessageVideo {
     fn load_video(&self, path: &str) {
         let imp = self.imp();
 
-        let media = gtk::MediaFile::for_filename(path);
+        if !imp.is_animation.get() {
+            self.update_remaining_time(imp.duration.get() as i64);
+
+            imp.status_button.set_visible(true);
+            imp.status_button
+                .set_icon_name("media-playback-start-symbolic");
+            imp.status_button.set_tooltip_text(Some(&gettext("Play")));
+
+            // Long videos are only loaded when the user wants to play them
+            if imp.duration.get() > AUTOPLAY_MAX_DURATION {
+                return;
+            }
+        }
+
+        let media = self.new_media_file(path);
         media.set_muted(true);
         media.set_loop(true);
-        media.play();
 
+        if self.is_mapped() {
+            media.play();
+        }
+    }
+
+    fn new_media_file(&self, path: &str) -> gtk::MediaFile {
+        let imp = self.imp();
+
+        let media = gtk::MediaFile::for_filename(path);
         if !imp.is_animation.get() {
             media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
                 let time = (media.duration() - media.timestamp()) / i64::pow(10, 6);
@@ -216,20 +394,87 @@ impl MessageVideo {
             }));
         }
 
-        imp.picture.set_paintable(Some(&media));
+        imp.picture.set_paintable(Some(media.upcast_ref()));
+        imp.media.replace(Some(media.clone()));
+
+        media
     }
 
-    fn update_remaining_time(&self, time: i64) {
+    fn play_with_controls(&self) {
+        let imp = self.imp();
+
+        let media = imp.media.borrow().clone();
+        let media = match media {
+            Some(media) => media,
+            None => {
+                let Some(file) = imp.file.borrow().clone() else {
+                    return;
+                };
+                self.new_media_file(&file.local.path)
+            }
+        };
+
+        imp.is_playing_with_controls.set(true);
+
+        // Restart the video with sound, if it was being played automatically
+        media.set_loop(false);
+        media.set_muted(false);
+        media.seek(0);
+        media.play();
+
+        imp.media_controls.set_media_stream(Some(&media));
+        imp.controls_box.set_visible(true);
+        imp.status_button.set_visible(false);
+    }
+
+    /// Stops the video and releases its resources.
+    fn stop(&self) {
         let imp = self.imp();
-        let seconds = time % 60;
-        let minutes = (time % (60 * 60)) / 60;
-        let hours = time / (60 * 60);
 
-        if hours > 0 {
-            imp.indicator
-                .set_label(&format!("{hours}:{minutes:02}:{seconds:02}"));
+        if let Some(media) = imp.media.take() {
+            media.pause();
+        }
+
+        imp.file.replace(None);
+        imp.picture.set_paintable(gdk::Paintable::NONE);
+        imp.is_playing_with_controls.set(false);
+        imp.media_controls.set_media_stream(gtk::MediaStream::NONE);
+        imp.controls_box.set_visible(false);
+        imp.status_button.set_visible(false);
+    }
+
+    fn update_download_status(&self, file: &File) {
+        let imp = self.imp();
+        let size = file.size.max(file.expected_size) as u64;
+
+        imp.status_button.set_visible(true);
+
+        if file.local.is_downloading_active {
+            imp.status_button
+                .set_icon_name("media-playback-stop-symbolic");
+            imp.status_button
+                .set_tooltip_text(Some(&gettext("Cancel Download")));
+
+            imp.indicator.set_label(&format!(
+                "{} / {}",
+                glib::format_size(file.local.downloaded_size as u64),
+                glib::format_size(size)
+            ));
         } else {
-            imp.indicator.set_label(&format!("{minutes}:{seconds:02}"));
+            imp.status_button.set_icon_name("folder-download-symbolic");
+            imp.status_button
+                .set_tooltip_text(Some(&gettext("Download")));
+
+            self.update_remaining_time(imp.duration.get() as i64);
+            imp.indicator.set_label(&format!(
+                "{} · {}",
+                imp.indicator.label(),
+                glib::format_size(size)
+            ));
         }
     }
+
+    fn update_remaining_time(&self, time: i64) {
+        self.imp().indicator.set_label(&format_playback_time(time));
+    }
 }
@@ -0,0 +1,448 @@
+mod progress_ring;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::types::File;
+
+use self::progress_ring::ProgressRing;
+use super::base::MessageBaseExt;
+use crate::session::content::message_row::MessageBase;
+use crate::session::content::message_row::MessageBaseImpl;
+use crate::session::content::message_row::MessageIndicators;
+use crate::session::content::message_row::MessageReply;
+use crate::tdlib::Message;
+use crate::utils::decode_minithumbnail;
+use crate::utils::format_playback_time;
+use crate::utils::spawn;
+
+const MAX_REPLY_CHAR_WIDTH: i32 = 18;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageVideoNote : $MessageBase {
+        layout-manager: BoxLayout {};
+
+        Overlay overlay {
+            GestureClick {
+                button: 1;
+                released => $on_released() swapped;
+            }
+
+            Box {
+                overflow: hidden;
+
+                Picture picture {
+                    content-fit: cover;
+                    width-request: 200;
+                    height-request: 200;
+                }
+
+                styles ["video-note"]
+            }
+
+            [overlay]
+            $MessageVideoNoteProgressRing progress_ring {}
+
+            [overlay]
+            Image status_image {
+                halign: center;
+                valign: center;
+
+                styles ["osd", "status-image"]
+            }
+
+            [overlay]
+            Label duration_label {
+                halign: start;
+                valign: end;
+
+                styles ["osd-indicator"]
+            }
+
+            [overlay]
+            $MessageIndicators indicators {
+                halign: end;
+                valign: end;
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MessageVideoNote {
+        pub(super) handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) message: RefCell<Option<Message>>,
+        pub(super) reply: RefCell<Option<MessageReply>>,
+        /// The video file, as of the last update received
+        pub(super) file: RefCell<Option<File>>,
+        pub(super) duration: Cell<i32>,
+        pub(super) media: RefCell<Option<gtk::MediaFile>>,
+        /// Whether the video note is being played with sound, rather than looped
+        /// without it
+        pub(super) is_playing_with_sound: Cell<bool>,
+        #[template_child]
+        pub(super) overlay: TemplateChild<gtk::Overlay>,
+        #[template_child]
+        pub(super) picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub(super) progress_ring: TemplateChild<ProgressRing>,
+        #[template_child]
+        pub(super) status_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub(super) duration_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) indicators: TemplateChild<MessageIndicators>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageVideoNote {
+        const NAME: &'static str = "MessageVideoNote";
+        type Type = super::MessageVideoNote;
+        type ParentType = MessageBase;
+
+        fn class_init(klass: &mut Self::Class) {
+            ProgressRing::static_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.set_css_name("messagevideonote");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageVideoNote {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "message" => self.message.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_cursor_from_name(Some("pointer"));
+        }
+
+        fn dispose(&self) {
+            if let Some(media) = self.media.take() {
+                media.pause();
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageVideoNote {
+        fn map(&self) {
+            self.parent_map();
+
+            // Only loop the video notes that are visible
+            if !self.is_playing_with_sound.get() {
+                if let Some(media) = &*self.media.borrow() {
+                    media.play();
+                }
+            }
+        }
+
+        fn unmap(&self) {
+            if let Some(media) = &*self.media.borrow() {
+                media.pause();
+            }
+
+            self.parent_unmap();
+        }
+    }
+
+    impl MessageBaseImpl for MessageVideoNote {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageVideoNote(ObjectSubclass<imp::MessageVideoNote>)
+        @extends gtk::Widget, MessageBase;
+}
+
+impl MessageBaseExt for MessageVideoNote {
+    type Message = Message;
+
+    fn set_message(&self, message: Self::Message) {
+        let imp = self.imp();
+
+        if imp.message.borrow().as_ref() == Some(&message) {
+            return;
+        }
+
+        if let Some(old_message) = imp.message.take() {
+            let handler_id = imp.handler_id.take().unwrap();
+            old_message.disconnect(handler_id);
+        }
+
+        self.stop();
+        imp.indicators.set_message(message.clone().upcast());
+
+        if let Some(reply) = imp.reply.take() {
+            reply.unparent();
+        }
+        if message.reply_to_message_id() != 0 {
+            let reply = MessageReply::new(&message);
+            reply.set_valign(gtk::Align::Start);
+            reply.set_max_char_width(MAX_REPLY_CHAR_WIDTH);
+
+            // The video note and the reply should be at the opposite sides of the box
+            if message.is_outgoing() {
+                reply.insert_before(self, Some(&imp.overlay.get()));
+            } else {
+                reply.insert_after(self, Some(&imp.overlay.get()));
+            }
+            imp.reply.replace(Some(reply));
+        }
+
+        // Video notes are marked as viewed through an update of the content
+        let handler_id =
+            message.connect_content_notify(clone!(@weak self as obj => move |message, _| {
+                obj.update_content(message);
+            }));
+        imp.handler_id.replace(Some(handler_id));
+
+        imp.message.replace(Some(message.clone()));
+        self.update_content(&message);
+
+        self.notify("message");
+    }
+}
+
+#[gtk::template_callbacks]
+impl MessageVideoNote {
+    fn update_content(&self, message: &Message) {
+        let MessageContent::MessageVideoNote(data) = message.content().0 else {
+            return;
+        };
+
+        let imp = self.imp();
+        let video_note = data.video_note;
+
+        imp.duration.set(video_note.duration);
+        if !imp.is_playing_with_sound.get() {
+            imp.duration_label
+                .set_label(&format_playback_time(video_note.duration as i64));
+        }
+
+        // The content is updated again when the video note is viewed, in which case the
+        // video has already been loaded
+        let is_same_file = imp.file.borrow().as_ref().map(|f| f.id) == Some(video_note.video.id);
+        if is_same_file && imp.media.borrow().is_some() {
+            return;
+        }
+
+        if video_note.video.local.is_downloading_completed {
+            imp.file.replace(Some(video_note.video.clone()));
+            self.load_video(&video_note.video.local.path);
+        } else {
+            imp.picture.set_paintable(
+                video_note
+                    .minithumbnail
+                    .and_then(|m| decode_minithumbnail(&m))
+                    .as_ref(),
+            );
+
+            self.update_download_status(&video_note.video);
+            imp.file.replace(Some(video_note.video));
+        }
+    }
+
+    #[template_callback]
+    fn on_released(&self) {
+        let imp = self.imp();
+
+        let Some(file) = imp.file.borrow().clone() else {
+            return;
+        };
+        let Some(message) = imp.message.borrow().clone() else {
+            return;
+        };
+        let session = message.chat().session();
+
+        if file.local.is_downloading_completed {
+            self.toggle_playback();
+        } else if file.local.is_downloading_active {
+            session.cancel_download_file(file.id);
+        } else {
+            let file_id = file.id;
+            session.download_file_with_updates(
+                file_id,
+                clone!(@weak self as obj => move |file| {
+                    // The row could have been reused for another message in the meantime
+                    if obj.imp().file.borrow().as_ref().map(|f| f.id) != Some(file_id) {
+                        return;
+                    }
+
+                    obj.imp().file.replace(Some(file.clone()));
+
+                    if file.local.is_downloading_completed {
+                        obj.load_video(&file.local.path);
+                    } else {
+                        obj.update_download_status(&file);
+                    }
+                }),
+            );
+        }
+    }
+
+    fn update_download_status(&self, file: &File) {
+        let imp = self.imp();
+
+        if file.local.is_downloading_active {
+            let size = file.size.max(file.expected_size) as f64;
+            imp.progress_ring
+                .set_progress(file.local.downloaded_size as f64 / size);
+            imp.status_image
+                .set_icon_name(Some("media-playback-stop-symbolic"));
+        } else {
+            imp.progress_ring.set_progress(0.0);
+            imp.status_image
+                .set_icon_name(Some("folder-download-symbolic"));
+        }
+        imp.status_image.set_visible(true);
+    }
+
+    fn load_video(&self, path: &str) {
+        let imp = self.imp();
+
+        let media = gtk::MediaFile::for_filename(path);
+        media.set_muted(true);
+        media.set_loop(true);
+        media.connect_timestamp_notify(clone!(@weak self as obj => move |media| {
+            obj.update_progress(media);
+        }));
+        media.connect_ended_notify(clone!(@weak self as obj => move |media| {
+            if media.is_ended() {
+                obj.on_playback_ended();
+            }
+        }));
+
+        imp.picture.set_paintable(Some(&media));
+        imp.progress_ring.set_progress(0.0);
+        imp.status_image
+            .set_icon_name(Some("audio-volume-muted-symbolic"));
+        imp.status_image.set_visible(true);
+
+        if self.is_mapped() {
+            media.play();
+        }
+
+        imp.media.replace(Some(media));
+    }
+
+    fn toggle_playback(&self) {
+        let imp = self.imp();
+
+        let Some(media) = imp.media.borrow().clone() else {
+            return;
+        };
+
+        if imp.is_playing_with_sound.get() {
+            if media.is_playing() {
+                media.pause();
+            } else {
+                media.play();
+            }
+            return;
+        }
+
+        // Restart the video note with sound
+        imp.is_playing_with_sound.set(true);
+        imp.status_image.set_visible(false);
+
+        media.set_loop(false);
+        media.set_muted(false);
+        media.seek(0);
+        media.play();
+    }
+
+    fn on_playback_ended(&self) {
+        let imp = self.imp();
+
+        if !imp.is_playing_with_sound.get() {
+            return;
+        }
+
+        if let Some(message) = imp.message.borrow().clone() {
+            if let MessageContent::MessageVideoNote(data) = message.content().0 {
+                if !message.is_outgoing() && !data.is_viewed {
+                    spawn(clone!(@weak message => async move {
+                        if let Err(e) = message.open_content().await {
+                            log::warn!("Error marking a video note as viewed: {e:?}");
+                        }
+                    }));
+                }
+            }
+        }
+
+        // Go back to looping the video note without sound
+        imp.is_playing_with_sound.set(false);
+        imp.progress_ring.set_progress(0.0);
+        imp.status_image.set_visible(true);
+        imp.duration_label
+            .set_label(&format_playback_time(imp.duration.get() as i64));
+
+        if let Some(media) = &*imp.media.borrow() {
+            media.set_muted(true);
+            media.set_loop(true);
+            media.seek(0);
+            media.play();
+        }
+    }
+
+    fn update_progress(&self, media: &gtk::MediaFile) {
+        let imp = self.imp();
+
+        if !imp.is_playing_with_sound.get() || media.duration() <= 0 {
+            return;
+        }
+
+        imp.progress_ring
+            .set_progress(media.timestamp() as f64 / media.duration() as f64);
+
+        let time = (media.duration() - media.timestamp()) / i64::pow(10, 6);
+        imp.duration_label.set_label(&format_playback_time(time));
+    }
+
+    /// Stops the video note and releases its resources.
+    fn stop(&self) {
+        let imp = self.imp();
+
+        if let Some(media) = imp.media.take() {
+            media.pause();
+        }
+
+        imp.file.replace(None);
+        imp.is_playing_with_sound.set(false);
+        imp.progress_ring.set_progress(0.0);
+    }
+}
@@ -0,0 +1,87 @@
+use std::cell::Cell;
+use std::f64::consts::PI;
+
+use gtk::glib;
+use gtk::graphene;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+const LINE_WIDTH: f64 = 4.0;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct ProgressRing {
+        pub(super) progress: Cell<f64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProgressRing {
+        const NAME: &'static str = "MessageVideoNoteProgressRing";
+        type Type = super::ProgressRing;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("progressring");
+        }
+    }
+
+    impl ObjectImpl for ProgressRing {}
+
+    impl WidgetImpl for ProgressRing {
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let width = widget.width() as f64;
+            let height = widget.height() as f64;
+            let progress = self.progress.get();
+
+            if progress <= 0.0 {
+                return;
+            }
+
+            let cr =
+                snapshot.append_cairo(&graphene::Rect::new(0.0, 0.0, width as f32, height as f32));
+
+            let radius = (width.min(height) - LINE_WIDTH) / 2.0;
+            let start_angle = -PI / 2.0;
+
+            cr.set_line_width(LINE_WIDTH);
+            cr.set_line_cap(gtk::cairo::LineCap::Round);
+            let color = widget.color();
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                color.alpha() as f64,
+            );
+            cr.arc(
+                width / 2.0,
+                height / 2.0,
+                radius,
+                start_angle,
+                start_angle + 2.0 * PI * progress.min(1.0),
+            );
+
+            if let Err(e) = cr.stroke() {
+                log::warn!("Error drawing a progress ring: {e:?}");
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct ProgressRing(ObjectSubclass<imp::ProgressRing>)
+        @extends gtk::Widget;
+}
+
+impl ProgressRing {
+    /// Sets the fraction of the ring to draw, starting from the top.
+    pub(crate) fn set_progress(&self, progress: f64) {
+        let imp = self.imp();
+        if imp.progress.get() != progress {
+            imp.progress.set(progress);
+            self.queue_draw();
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::glib::clone;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::types::WebPage;
+
+use crate::session::content::message_row::MediaPicture;
+use crate::utils::decode_image_from_path;
+use crate::utils::spawn;
+use crate::Session;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $MessageWebPage : Widget {
+        GestureClick {
+            button: 1;
+            released => $on_released() swapped;
+        }
+
+        Separator {
+            width-request: 2;
+        }
+
+        Box {
+            orientation: vertical;
+            hexpand: true;
+
+            Label site_name_label {
+                ellipsize: end;
+                xalign: 0;
+
+                styles ["caption-heading"]
+            }
+
+            Label title_label {
+                ellipsize: end;
+                xalign: 0;
+                wrap: true;
+                lines: 2;
+
+                styles ["heading"]
+            }
+
+            Label description_label {
+                ellipsize: end;
+                xalign: 0;
+                wrap: true;
+                wrap-mode: word_char;
+                lines: 4;
+            }
+
+            $MessageMediaPicture picture {
+                visible: false;
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MessageWebPage {
+        pub(super) url: RefCell<String>,
+        /// The id of the file of the photo currently shown, so that the outdated
+        /// downloads can be discarded
+        pub(super) photo_file_id: Cell<i32>,
+        #[template_child]
+        pub(super) site_name_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) description_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) picture: TemplateChild<MediaPicture>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageWebPage {
+        const NAME: &'static str = "MessageWebPage";
+        type Type = super::MessageWebPage;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            klass.bind_template_instance_callbacks();
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+            klass.set_css_name("messagewebpage");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageWebPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_cursor_from_name(Some("pointer"));
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MessageWebPage {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageWebPage(ObjectSubclass<imp::MessageWebPage>)
+        @extends gtk::Widget;
+}
+
+impl Default for MessageWebPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[gtk::template_callbacks]
+impl MessageWebPage {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    #[template_callback]
+    fn on_released(&self) {
+        let url = self.imp().url.borrow().clone();
+        if let Err(e) = gio::AppInfo::launch_default_for_uri(&url, gio::AppLaunchContext::NONE) {
+            log::warn!("Error opening an URI: {e:?}");
+        }
+    }
+
+    pub(crate) fn set_web_page(&self, web_page: WebPage, session: &Session) {
+        let imp = self.imp();
+
+        imp.url.replace(web_page.url);
+
+        set_label_text(&imp.site_name_label, &web_page.site_name);
+        set_label_text(&imp.title_label, &web_page.title);
+        set_label_text(&imp.description_label, &web_page.description.text);
+
+        // Choose the right photo size based on the screen scale factor, like for the
+        // photo messages
+        let photo_size = web_page.photo.and_then(|mut photo| {
+            if self.scale_factor() > 2 {
+                photo.sizes.pop()
+            } else {
+                let type_ = if self.scale_factor() > 1 { "y" } else { "x" };
+
+                match photo.sizes.iter().position(|s| s.r#type == type_) {
+                    Some(pos) => Some(photo.sizes.swap_remove(pos)),
+                    None => photo.sizes.pop(),
+                }
+            }
+        });
+
+        let Some(photo_size) = photo_size else {
+            imp.photo_file_id.set(0);
+            imp.picture.set_paintable(gdk::Paintable::NONE);
+            imp.picture.set_visible(false);
+            return;
+        };
+
+        let file_id = photo_size.photo.id;
+        if imp.photo_file_id.get() == file_id {
+            return;
+        }
+        imp.photo_file_id.set(file_id);
+
+        imp.picture
+            .set_aspect_ratio(photo_size.width as f64 / photo_size.height as f64);
+        imp.picture.set_paintable(gdk::Paintable::NONE);
+        imp.picture.set_visible(true);
+
+        if photo_size.photo.local.is_downloading_completed {
+            self.load_photo(file_id, photo_size.photo.local.path);
+        } else {
+            spawn(clone!(@weak self as obj, @weak session => async move {
+                match session.download_file(file_id).await {
+                    Ok(file) => obj.load_photo(file_id, file.local.path),
+                    Err(e) => log::warn!("Failed to download the photo of a web page: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    fn load_photo(&self, file_id: i32, path: String) {
+        spawn(clone!(@weak self as obj => async move {
+            let result = gio::spawn_blocking(move || decode_image_from_path(&path))
+                .await
+                .unwrap();
+
+            // The web page could have been changed while decoding the image
+            if obj.imp().photo_file_id.get() != file_id {
+                return;
+            }
+
+            match result {
+                Ok(texture) => obj.imp().picture.set_paintable(Some(&texture)),
+                Err(e) => log::warn!("Error decoding the photo of a web page: {e:?}"),
+            }
+        }));
+    }
+}
+
+fn set_label_text(label: &gtk::Label, text: &str) {
+    label.set_label(text);
+    label.set_visible(!text.is_empty());
+}
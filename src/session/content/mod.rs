@@ -1,3 +1,4 @@
+mod animation_picker;
 mod background;
 mod chat_action_bar;
 mod chat_history;
@@ -5,9 +6,19 @@ mod chat_history_item;
 mod chat_history_model;
 mod chat_history_row;
 mod chat_info_window;
+mod chat_search_bar;
+mod create_poll_window;
 mod event_row;
+mod forward_window;
+mod inline_bot_results;
+mod media_viewer;
+mod member_list;
 mod message_row;
+mod moderation;
+mod pinned_message_bar;
 mod send_media_window;
+mod shared_media;
+mod sticker_picker;
 
 use std::cell::RefCell;
 
@@ -18,6 +29,8 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 
+use self::animation_picker::AnimationPicker;
+use self::animation_picker::Preview as AnimationPreview;
 use self::background::Background;
 use self::chat_action_bar::ChatActionBar;
 use self::chat_history::ChatHistory;
@@ -27,9 +40,19 @@ use self::chat_history_model::ChatHistoryError;
 use self::chat_history_model::ChatHistoryModel;
 use self::chat_history_row::ChatHistoryRow;
 use self::chat_info_window::ChatInfoWindow;
+use self::chat_search_bar::ChatSearchBar;
+use self::create_poll_window::CreatePollWindow;
 use self::event_row::EventRow;
+use self::forward_window::ForwardWindow;
+use self::inline_bot_results::InlineBotResults;
+use self::media_viewer::MediaViewer;
+use self::member_list::MemberList;
 use self::message_row::MessageRow;
+use self::message_row::ReactionPicker;
+use self::pinned_message_bar::PinnedMessageBar;
 use self::send_media_window::SendMediaWindow;
+use self::shared_media::SharedMediaPage;
+use self::sticker_picker::StickerPicker;
 use crate::tdlib::Chat;
 
 mod imp {
@@ -119,6 +142,10 @@ impl Content {
         self.imp().chat_history.handle_paste_action();
     }
 
+    pub(crate) fn scroll_to_message(&self, message_id: i64) {
+        self.imp().chat_history.scroll_to_message(message_id);
+    }
+
     pub(crate) fn chat(&self) -> Option<Chat> {
         self.imp().chat.borrow().clone()
     }
@@ -0,0 +1,512 @@
+use std::cell::Cell;
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use adw::subclass::prelude::AdwWindowImpl;
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::types::ChatAdministratorRights;
+use tdlib::types::ChatMemberStatusAdministrator;
+use tdlib::types::ChatMemberStatusRestricted;
+use tdlib::types::ChatPermissions;
+
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::User;
+use crate::utils::spawn;
+
+/// The restriction durations, in seconds, in the order of the duration row.
+const DURATIONS: [i32; 4] = [0, 60 * 60 * 24, 60 * 60 * 24 * 7, 60 * 60 * 24 * 30];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemberRightsKind {
+    #[default]
+    Restrict,
+    Promote,
+}
+
+/// A permission of a restricted member or a right of an administrator, toggled by a switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Right {
+    SendBasicMessages,
+    SendPhotos,
+    SendVideos,
+    SendVideoNotes,
+    SendVoiceNotes,
+    SendAudios,
+    SendDocuments,
+    SendOtherMessages,
+    SendPolls,
+    AddWebPagePreviews,
+    InviteUsers,
+    PinMessages,
+    ChangeInfo,
+    PostMessages,
+    EditMessages,
+    DeleteMessages,
+    RestrictMembers,
+    ManageVideoChats,
+    PromoteMembers,
+    IsAnonymous,
+}
+
+impl Right {
+    fn set_permission(self, permissions: &mut ChatPermissions, value: bool) {
+        let permission = match self {
+            Self::SendBasicMessages => &mut permissions.can_send_basic_messages,
+            Self::SendPhotos => &mut permissions.can_send_photos,
+            Self::SendVideos => &mut permissions.can_send_videos,
+            Self::SendVideoNotes => &mut permissions.can_send_video_notes,
+            Self::SendVoiceNotes => &mut permissions.can_send_voice_notes,
+            Self::SendAudios => &mut permissions.can_send_audios,
+            Self::SendDocuments => &mut permissions.can_send_documents,
+            Self::SendOtherMessages => &mut permissions.can_send_other_messages,
+            Self::SendPolls => &mut permissions.can_send_polls,
+            Self::AddWebPagePreviews => &mut permissions.can_add_web_page_previews,
+            Self::InviteUsers => &mut permissions.can_invite_users,
+            Self::PinMessages => &mut permissions.can_pin_messages,
+            Self::ChangeInfo => &mut permissions.can_change_info,
+            _ => return,
+        };
+        *permission = value;
+    }
+
+    fn set_right(self, rights: &mut ChatAdministratorRights, value: bool) {
+        let right = match self {
+            Self::InviteUsers => &mut rights.can_invite_users,
+            Self::PinMessages => &mut rights.can_pin_messages,
+            Self::ChangeInfo => &mut rights.can_change_info,
+            Self::PostMessages => &mut rights.can_post_messages,
+            Self::EditMessages => &mut rights.can_edit_messages,
+            Self::DeleteMessages => &mut rights.can_delete_messages,
+            Self::RestrictMembers => &mut rights.can_restrict_members,
+            Self::ManageVideoChats => &mut rights.can_manage_video_chats,
+            Self::PromoteMembers => &mut rights.can_promote_members,
+            Self::IsAnonymous => &mut rights.is_anonymous,
+            _ => return,
+        };
+        *right = value;
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    using Adw 1;
+
+    template $ContentMemberRightsWindow : Adw.Window {
+        modal: true;
+        default-width: 360;
+        default-height: 560;
+
+        content: Adw.ToolbarView {
+            [top]
+            Adw.HeaderBar {
+                show-start-title-buttons: false;
+                show-end-title-buttons: false;
+
+                title-widget: Adw.WindowTitle window_title {};
+
+                [start]
+                Button {
+                    label: _("_Cancel");
+                    use-underline: true;
+                    action-name: "window.close";
+                }
+
+                [end]
+                Button {
+                    label: _("_Apply");
+                    use-underline: true;
+                    clicked => $on_apply_clicked() swapped;
+
+                    styles ["suggested-action"]
+                }
+            }
+
+            content: Adw.ToastOverlay toast_overlay {
+                Adw.PreferencesPage {
+                    Adw.PreferencesGroup rights_group {}
+
+                    Adw.PreferencesGroup {
+                        Adw.EntryRow custom_title_row {
+                            title: _("Custom Title");
+                            visible: false;
+                        }
+
+                        Adw.ComboRow duration_row {
+                            title: _("Duration");
+                            visible: false;
+                            model: StringList {
+                                strings [
+                                    _("Forever"),
+                                    _("1 Day"),
+                                    _("1 Week"),
+                                    _("1 Month"),
+                                ]
+                            };
+                        }
+                    }
+                }
+            };
+        };
+    }
+    "#)]
+    pub(crate) struct MemberRightsWindow {
+        pub(super) chat: OnceCell<Chat>,
+        pub(super) user: OnceCell<User>,
+        pub(super) kind: Cell<MemberRightsKind>,
+        /// The switches of the rights, in the order they are added to the group
+        pub(super) switches: RefCell<Vec<(Right, gtk::Switch)>>,
+        pub(super) permissions: RefCell<ChatPermissions>,
+        pub(super) rights: RefCell<ChatAdministratorRights>,
+        #[template_child]
+        pub(super) window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub(super) rights_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub(super) custom_title_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub(super) duration_row: TemplateChild<adw::ComboRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MemberRightsWindow {
+        const NAME: &'static str = "ContentMemberRightsWindow";
+        type Type = super::MemberRightsWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MemberRightsWindow {}
+    impl WidgetImpl for MemberRightsWindow {}
+    impl WindowImpl for MemberRightsWindow {}
+    impl AdwWindowImpl for MemberRightsWindow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MemberRightsWindow(ObjectSubclass<imp::MemberRightsWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+#[gtk::template_callbacks]
+impl MemberRightsWindow {
+    pub(crate) fn new(
+        parent: &gtk::Window,
+        chat: &Chat,
+        user: &User,
+        kind: MemberRightsKind,
+        status: ChatMemberStatus,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        let imp = obj.imp();
+
+        imp.chat.set(chat.clone()).unwrap();
+        imp.user.set(user.clone()).unwrap();
+        imp.kind.set(kind);
+
+        imp.window_title
+            .set_subtitle(&strings::user_display_name(user, true));
+
+        match kind {
+            MemberRightsKind::Restrict => obj.setup_permissions(status),
+            MemberRightsKind::Promote => obj.setup_rights(status),
+        }
+
+        obj
+    }
+
+    fn setup_permissions(&self, status: ChatMemberStatus) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+
+        // Members that aren't restricted yet have the default permissions of the chat
+        let permissions = match status {
+            ChatMemberStatus::Restricted(data) => data.permissions,
+            _ => chat.permissions().0,
+        };
+
+        imp.window_title.set_title(&gettext("Restrict Member"));
+        imp.rights_group
+            .set_title(&gettext("What can this member do?"));
+        imp.duration_row.set_visible(true);
+
+        self.add_switch(
+            Right::SendBasicMessages,
+            &gettext("Send Messages"),
+            permissions.can_send_basic_messages,
+        );
+        self.add_switch(
+            Right::SendPhotos,
+            &gettext("Send Photos"),
+            permissions.can_send_photos,
+        );
+        self.add_switch(
+            Right::SendVideos,
+            &gettext("Send Videos"),
+            permissions.can_send_videos,
+        );
+        self.add_switch(
+            Right::SendVideoNotes,
+            &gettext("Send Video Messages"),
+            permissions.can_send_video_notes,
+        );
+        self.add_switch(
+            Right::SendVoiceNotes,
+            &gettext("Send Voice Messages"),
+            permissions.can_send_voice_notes,
+        );
+        self.add_switch(
+            Right::SendAudios,
+            &gettext("Send Music"),
+            permissions.can_send_audios,
+        );
+        self.add_switch(
+            Right::SendDocuments,
+            &gettext("Send Files"),
+            permissions.can_send_documents,
+        );
+        self.add_switch(
+            Right::SendOtherMessages,
+            &gettext("Send Stickers and GIFs"),
+            permissions.can_send_other_messages,
+        );
+        self.add_switch(
+            Right::SendPolls,
+            &gettext("Send Polls"),
+            permissions.can_send_polls,
+        );
+        self.add_switch(
+            Right::AddWebPagePreviews,
+            &gettext("Embed Links"),
+            permissions.can_add_web_page_previews,
+        );
+        self.add_switch(
+            Right::InviteUsers,
+            &gettext("Add Members"),
+            permissions.can_invite_users,
+        );
+        self.add_switch(
+            Right::PinMessages,
+            &gettext("Pin Messages"),
+            permissions.can_pin_messages,
+        );
+        self.add_switch(
+            Right::ChangeInfo,
+            &gettext("Change Chat Info"),
+            permissions.can_change_info,
+        );
+
+        imp.permissions.replace(permissions);
+    }
+
+    fn setup_rights(&self, status: ChatMemberStatus) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap();
+        let is_channel =
+            matches!(chat.type_(), ChatType::Supergroup(supergroup) if supergroup.is_channel());
+
+        // We can only grant the rights that we have ourselves
+        let can_grant = |f: fn(&ChatAdministratorRights) -> bool| chat.has_administrator_right(f);
+
+        let (rights, custom_title) = match status {
+            ChatMemberStatus::Administrator(data) => (data.rights, data.custom_title),
+            _ => (
+                ChatAdministratorRights {
+                    can_manage_chat: true,
+                    can_change_info: can_grant(|r| r.can_change_info),
+                    can_post_messages: is_channel && can_grant(|r| r.can_post_messages),
+                    can_edit_messages: is_channel && can_grant(|r| r.can_edit_messages),
+                    can_delete_messages: can_grant(|r| r.can_delete_messages),
+                    can_invite_users: can_grant(|r| r.can_invite_users),
+                    can_restrict_members: can_grant(|r| r.can_restrict_members),
+                    can_pin_messages: !is_channel && can_grant(|r| r.can_pin_messages),
+                    can_manage_video_chats: can_grant(|r| r.can_manage_video_chats),
+                    ..Default::default()
+                },
+                String::new(),
+            ),
+        };
+
+        imp.window_title.set_title(&gettext("Promote Member"));
+        imp.rights_group
+            .set_title(&gettext("What can this administrator do?"));
+
+        // Channels don't support custom titles
+        imp.custom_title_row.set_visible(!is_channel);
+        imp.custom_title_row.set_text(&custom_title);
+
+        self.add_switch(
+            Right::ChangeInfo,
+            &gettext("Change Chat Info"),
+            rights.can_change_info,
+        )
+        .set_sensitive(can_grant(|r| r.can_change_info));
+        if is_channel {
+            self.add_switch(
+                Right::PostMessages,
+                &gettext("Post Messages"),
+                rights.can_post_messages,
+            )
+            .set_sensitive(can_grant(|r| r.can_post_messages));
+            self.add_switch(
+                Right::EditMessages,
+                &gettext("Edit Messages of Others"),
+                rights.can_edit_messages,
+            )
+            .set_sensitive(can_grant(|r| r.can_edit_messages));
+        }
+        self.add_switch(
+            Right::DeleteMessages,
+            &gettext("Delete Messages"),
+            rights.can_delete_messages,
+        )
+        .set_sensitive(can_grant(|r| r.can_delete_messages));
+        self.add_switch(
+            Right::RestrictMembers,
+            &gettext("Ban Members"),
+            rights.can_restrict_members,
+        )
+        .set_sensitive(can_grant(|r| r.can_restrict_members));
+        self.add_switch(
+            Right::InviteUsers,
+            &gettext("Add Members"),
+            rights.can_invite_users,
+        )
+        .set_sensitive(can_grant(|r| r.can_invite_users));
+        if !is_channel {
+            self.add_switch(
+                Right::PinMessages,
+                &gettext("Pin Messages"),
+                rights.can_pin_messages,
+            )
+            .set_sensitive(can_grant(|r| r.can_pin_messages));
+        }
+        self.add_switch(
+            Right::ManageVideoChats,
+            &gettext("Manage Video Chats"),
+            rights.can_manage_video_chats,
+        )
+        .set_sensitive(can_grant(|r| r.can_manage_video_chats));
+        self.add_switch(
+            Right::PromoteMembers,
+            &gettext("Add New Admins"),
+            rights.can_promote_members,
+        )
+        .set_sensitive(can_grant(|r| r.can_promote_members));
+        if !is_channel {
+            self.add_switch(
+                Right::IsAnonymous,
+                &gettext("Remain Anonymous"),
+                rights.is_anonymous,
+            )
+            .set_sensitive(can_grant(|r| r.is_anonymous));
+        }
+
+        imp.rights.replace(rights);
+    }
+
+    fn add_switch(&self, right: Right, title: &str, active: bool) -> adw::ActionRow {
+        let imp = self.imp();
+
+        let switch = gtk::Switch::builder()
+            .active(active)
+            .valign(gtk::Align::Center)
+            .build();
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .activatable_widget(&switch)
+            .build();
+        row.add_suffix(&switch);
+
+        imp.rights_group.add(&row);
+        imp.switches.borrow_mut().push((right, switch));
+
+        row
+    }
+
+    #[template_callback]
+    fn on_apply_clicked(&self) {
+        let imp = self.imp();
+        let chat = imp.chat.get().unwrap().clone();
+        let user = imp.user.get().unwrap().clone();
+
+        let status = match imp.kind.get() {
+            MemberRightsKind::Restrict => self.restricted_status(),
+            MemberRightsKind::Promote => self.administrator_status(),
+        };
+
+        // Keep the window open until the change is applied, so that it can be retried
+        self.set_sensitive(false);
+
+        spawn(clone!(@weak self as obj => async move {
+            match chat.set_member_status(&user, status).await {
+                Ok(_) => obj.close(),
+                Err(e) => {
+                    log::warn!("Error changing the status of a chat member: {e:?}");
+                    obj.imp().toast_overlay.add_toast(adw::Toast::new(&gettext(
+                        "Failed to change the rights of the member",
+                    )));
+                    obj.set_sensitive(true);
+                }
+            }
+        }));
+    }
+
+    fn restricted_status(&self) -> ChatMemberStatus {
+        let imp = self.imp();
+        let mut permissions = imp.permissions.borrow().clone();
+        for (right, switch) in imp.switches.borrow().iter() {
+            right.set_permission(&mut permissions, switch.is_active());
+        }
+
+        let duration = DURATIONS[imp.duration_row.selected() as usize];
+        let restricted_until_date = if duration > 0 {
+            glib::DateTime::now_utc().unwrap().to_unix() as i32 + duration
+        } else {
+            0
+        };
+
+        ChatMemberStatus::Restricted(ChatMemberStatusRestricted {
+            is_member: true,
+            restricted_until_date,
+            permissions,
+        })
+    }
+
+    fn administrator_status(&self) -> ChatMemberStatus {
+        let imp = self.imp();
+
+        let mut rights = imp.rights.borrow().clone();
+        for (right, switch) in imp.switches.borrow().iter() {
+            right.set_right(&mut rights, switch.is_active());
+        }
+        rights.can_manage_chat = true;
+
+        ChatMemberStatus::Administrator(ChatMemberStatusAdministrator {
+            custom_title: imp.custom_title_row.text().into(),
+            can_be_edited: true,
+            rights,
+        })
+    }
+}
@@ -0,0 +1,205 @@
+mod member_rights_window;
+
+use adw::prelude::*;
+use gettextrs::gettext;
+use tdlib::enums::ChatMemberStatus;
+use tdlib::types::ChatMemberStatusBanned;
+
+use self::member_rights_window::MemberRightsKind;
+use self::member_rights_window::MemberRightsWindow;
+use crate::i18n::gettext_f;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::ChatType;
+use crate::tdlib::User;
+
+/// Returns whether we can act on a member of the chat with the specified status.
+pub(crate) fn can_moderate(chat: &Chat, user: &User, status: &ChatMemberStatus) -> bool {
+    if user.id() == chat.session().me().id() {
+        return false;
+    }
+
+    match status {
+        ChatMemberStatus::Creator(_) => false,
+        ChatMemberStatus::Administrator(data) => data.can_be_edited,
+        _ => chat.can_restrict_members() || chat.can_promote_members(),
+    }
+}
+
+/// Returns whether we can remove or ban a member of the chat with the specified status.
+/// Administrators can only be removed by the creator or by whoever can edit their rights.
+pub(crate) fn can_remove(chat: &Chat, status: &ChatMemberStatus) -> bool {
+    match status {
+        ChatMemberStatus::Creator(_) => false,
+        ChatMemberStatus::Administrator(data) => {
+            (chat.is_creator() || data.can_be_edited) && chat.can_restrict_members()
+        }
+        _ => chat.can_restrict_members(),
+    }
+}
+
+/// Returns whether members of the chat can be restricted. Basic groups only support
+/// removing members.
+pub(crate) fn supports_restrictions(chat: &Chat) -> bool {
+    matches!(chat.type_(), ChatType::Supergroup(supergroup) if !supergroup.is_channel())
+}
+
+/// Shows the editor of the administrator rights of a member.
+pub(crate) async fn promote_member(parent: &gtk::Window, chat: &Chat, user: &User) {
+    show_member_rights_window(parent, chat, user, MemberRightsKind::Promote).await;
+}
+
+/// Shows the editor of the permissions of a member.
+pub(crate) async fn restrict_member(parent: &gtk::Window, chat: &Chat, user: &User) {
+    show_member_rights_window(parent, chat, user, MemberRightsKind::Restrict).await;
+}
+
+async fn show_member_rights_window(
+    parent: &gtk::Window,
+    chat: &Chat,
+    user: &User,
+    kind: MemberRightsKind,
+) {
+    match chat.member(user).await {
+        Ok(member) => {
+            MemberRightsWindow::new(parent, chat, user, kind, member.status).present();
+        }
+        Err(e) => log::warn!("Error getting a chat member: {e:?}"),
+    }
+}
+
+/// Removes a member from the chat, after asking for confirmation. Returns whether the member
+/// has been removed.
+pub(crate) async fn remove_member(parent: &gtk::Window, chat: &Chat, user: &User) -> bool {
+    if !check_removable(parent, chat, user).await {
+        return false;
+    }
+
+    let confirmed = confirm(
+        parent,
+        &gettext("Remove Member?"),
+        &gettext_f(
+            "Do you want to remove {user} from this chat?",
+            &[("user", &strings::user_display_name(user, true))],
+        ),
+        &gettext("_Remove"),
+    )
+    .await;
+
+    if !confirmed {
+        return false;
+    }
+
+    // Members of supergroups are removed by banning them and then lifting the ban, so
+    // that they are still able to join again
+    let result = if let ChatType::Supergroup(_) = chat.type_() {
+        let banned = ChatMemberStatus::Banned(ChatMemberStatusBanned {
+            banned_until_date: 0,
+        });
+        match chat.set_member_status(user, banned).await {
+            Ok(_) => chat.set_member_status(user, ChatMemberStatus::Left).await,
+            Err(e) => Err(e),
+        }
+    } else {
+        chat.set_member_status(user, ChatMemberStatus::Left).await
+    };
+
+    match result {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Error removing a chat member: {e:?}");
+            false
+        }
+    }
+}
+
+/// Bans a member from the chat, after asking for confirmation. Returns whether the member
+/// has been banned.
+pub(crate) async fn ban_member(parent: &gtk::Window, chat: &Chat, user: &User) -> bool {
+    if !check_removable(parent, chat, user).await {
+        return false;
+    }
+
+    let confirmed = confirm(
+        parent,
+        &gettext("Ban Member?"),
+        &gettext_f(
+            "Do you want to ban {user}? They will not be able to join this chat again.",
+            &[("user", &strings::user_display_name(user, true))],
+        ),
+        &gettext("_Ban"),
+    )
+    .await;
+
+    if !confirmed {
+        return false;
+    }
+
+    let banned = ChatMemberStatus::Banned(ChatMemberStatusBanned {
+        banned_until_date: 0,
+    });
+    match chat.set_member_status(user, banned).await {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Error banning a chat member: {e:?}");
+            false
+        }
+    }
+}
+
+/// Deletes all the messages of a member of the chat, after asking for confirmation.
+pub(crate) async fn delete_member_messages(parent: &gtk::Window, chat: &Chat, user: &User) {
+    let confirmed = confirm(
+        parent,
+        &gettext("Delete All Messages?"),
+        &gettext_f(
+            "Do you want to delete all the messages sent by {user} in this chat?",
+            &[("user", &strings::user_display_name(user, true))],
+        ),
+        &gettext("_Delete"),
+    )
+    .await;
+
+    if confirmed {
+        if let Err(e) = chat.delete_messages_from_user(user).await {
+            log::warn!("Error deleting the messages of a chat member: {e:?}");
+        }
+    }
+}
+
+/// Returns whether the member can be removed from the chat, telling the user why otherwise.
+async fn check_removable(parent: &gtk::Window, chat: &Chat, user: &User) -> bool {
+    let status = match chat.member(user).await {
+        Ok(member) => member.status,
+        Err(e) => {
+            log::warn!("Error getting a chat member: {e:?}");
+            return false;
+        }
+    };
+
+    if can_remove(chat, &status) {
+        return true;
+    }
+
+    let dialog = adw::MessageDialog::new(
+        Some(parent),
+        Some(&gettext("Cannot Remove Member")),
+        Some(&gettext(
+            "Only the creator of the chat or whoever promoted this administrator can remove them.",
+        )),
+    );
+    dialog.add_response("close", &gettext("_Close"));
+    dialog.choose_future().await;
+
+    false
+}
+
+async fn confirm(parent: &gtk::Window, heading: &str, body: &str, response: &str) -> bool {
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), Some(body));
+    dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("confirm", response)]);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.set_response_appearance("confirm", adw::ResponseAppearance::Destructive);
+
+    dialog.choose_future().await == "confirm"
+}
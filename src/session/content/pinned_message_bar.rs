@@ -0,0 +1,246 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::i18n::gettext_f;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentPinnedMessageBar {
+        layout-manager: BinLayout {};
+        visible: false;
+
+        Button button {
+            clicked => $on_clicked() swapped;
+
+            styles ["flat"]
+
+            Box {
+                orientation: vertical;
+
+                Label title_label {
+                    styles ["caption-heading", "accent"]
+
+                    xalign: 0;
+                }
+
+                Label content_label {
+                    ellipsize: end;
+                    single-line-mode: true;
+                    xalign: 0;
+                }
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct PinnedMessageBar {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) chat_handler: RefCell<Option<glib::SignalHandlerId>>,
+        /// The pinned messages, from the newest to the oldest
+        pub(super) messages: RefCell<Vec<Message>>,
+        pub(super) current_index: Cell<usize>,
+        #[template_child]
+        pub(super) button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) content_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PinnedMessageBar {
+        const NAME: &'static str = "ContentPinnedMessageBar";
+        type Type = super::PinnedMessageBar;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.set_css_name("pinnedmessagebar");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PinnedMessageBar {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Chat>("chat")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.button.unparent();
+        }
+    }
+
+    impl WidgetImpl for PinnedMessageBar {}
+}
+
+glib::wrapper! {
+    pub(crate) struct PinnedMessageBar(ObjectSubclass<imp::PinnedMessageBar>)
+        @extends gtk::Widget;
+}
+
+#[gtk::template_callbacks]
+impl PinnedMessageBar {
+    /// Jumps to the shown pinned message and then shows the next older one.
+    #[template_callback]
+    fn on_clicked(&self) {
+        let imp = self.imp();
+        let n_messages = imp.messages.borrow().len();
+
+        if n_messages == 0 {
+            return;
+        }
+
+        let index = imp.current_index.get();
+        let message_id = imp.messages.borrow()[index].id();
+
+        imp.current_index.set((index + 1) % n_messages);
+        self.update();
+
+        self.activate_action(
+            "chat-history.scroll-to-message",
+            Some(&message_id.to_variant()),
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(old_chat) = self.chat() {
+            if let Some(handler) = imp.chat_handler.take() {
+                old_chat.disconnect(handler);
+            }
+        }
+
+        if let Some(ref chat) = chat {
+            let handler =
+                chat.connect_pinned_messages_changed(clone!(@weak self as obj => move |_| {
+                    obj.load_pinned_messages();
+                }));
+            imp.chat_handler.replace(Some(handler));
+        }
+
+        imp.chat.replace(chat);
+        imp.messages.borrow_mut().clear();
+        imp.current_index.set(0);
+
+        self.update();
+        self.load_pinned_messages();
+
+        self.notify("chat");
+    }
+
+    fn load_pinned_messages(&self) {
+        if let Some(chat) = self.chat() {
+            spawn(clone!(@weak self as obj => async move {
+                match chat.pinned_messages().await {
+                    Ok(messages) => {
+                        // The chat could have been changed in the meantime
+                        if obj.chat().as_ref() == Some(&chat) {
+                            obj.set_messages(messages);
+                        }
+                    }
+                    Err(e) => log::warn!("Error loading pinned messages: {e:?}"),
+                }
+            }));
+        }
+    }
+
+    fn set_messages(&self, messages: Vec<Message>) {
+        let imp = self.imp();
+
+        // Try to keep showing the same message after a reload
+        let current_id = imp
+            .messages
+            .borrow()
+            .get(imp.current_index.get())
+            .map(Message::id);
+        let index = current_id
+            .and_then(|id| messages.iter().position(|m| m.id() == id))
+            .unwrap_or_default();
+
+        imp.messages.replace(messages);
+        imp.current_index.set(index);
+
+        self.update();
+    }
+
+    fn update(&self) {
+        let imp = self.imp();
+        let messages = imp.messages.borrow();
+
+        match messages.get(imp.current_index.get()) {
+            Some(message) => {
+                let title = if messages.len() > 1 {
+                    gettext_f(
+                        "Pinned Message {index} of {count}",
+                        &[
+                            ("index", &(imp.current_index.get() + 1).to_string()),
+                            ("count", &messages.len().to_string()),
+                        ],
+                    )
+                } else {
+                    gettext("Pinned Message")
+                };
+
+                imp.title_label.set_label(&title);
+                imp.content_label
+                    .set_label(&strings::message_content(message));
+
+                self.set_visible(true);
+            }
+            None => {
+                self.set_visible(false);
+            }
+        }
+    }
+}
@@ -112,7 +112,7 @@ impl SendMediaWindow {
             let chooser = gtk::EmojiChooser::new();
             chooser.set_parent(parent);
             chooser.connect_emoji_picked(clone!(@weak self as obj => move |_, emoji| {
-                obj.imp().caption_entry.insert_at_cursor(emoji);
+                obj.imp().caption_entry.insert_emoji(emoji);
             }));
             chooser.connect_hide(clone!(@weak self as obj => move |_| {
                 obj.imp().caption_entry.grab_focus();
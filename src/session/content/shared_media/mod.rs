@@ -0,0 +1,604 @@
+mod thumbnail;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use glib::clone;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+use tdlib::enums::SearchMessagesFilter;
+use tdlib::enums::TextEntityType;
+use tdlib::types::File;
+
+use self::thumbnail::Thumbnail;
+use super::MediaViewer;
+use crate::strings;
+use crate::tdlib::Chat;
+use crate::tdlib::Message;
+use crate::utils::human_friendly_duration;
+use crate::utils::spawn;
+
+const PAGE_SIZE: i32 = 50;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "ContentSharedMediaKind")]
+pub(crate) enum SharedMediaKind {
+    #[default]
+    Media,
+    Files,
+    Links,
+    Voice,
+    Music,
+}
+
+impl SharedMediaKind {
+    fn filter(self) -> SearchMessagesFilter {
+        match self {
+            Self::Media => SearchMessagesFilter::PhotoAndVideo,
+            Self::Files => SearchMessagesFilter::Document,
+            Self::Links => SearchMessagesFilter::Url,
+            Self::Voice => SearchMessagesFilter::VoiceNote,
+            Self::Music => SearchMessagesFilter::Audio,
+        }
+    }
+
+    fn empty_text(self) -> String {
+        match self {
+            Self::Media => gettext("No Media"),
+            Self::Files => gettext("No Files"),
+            Self::Links => gettext("No Links"),
+            Self::Voice => gettext("No Voice Messages"),
+            Self::Music => gettext("No Music"),
+        }
+    }
+
+    fn icon_name(self) -> &'static str {
+        match self {
+            Self::Media => "image-x-generic-symbolic",
+            Self::Files => "folder-documents-symbolic",
+            Self::Links => "insert-link-symbolic",
+            Self::Voice => "audio-input-microphone-symbolic",
+            Self::Music => "audio-x-generic-symbolic",
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentSharedMediaPage {
+        layout-manager: BinLayout {};
+
+        Stack stack {
+            vhomogeneous: false;
+
+            StackPage {
+                name: "loading";
+                child: Spinner {
+                    spinning: true;
+                    halign: center;
+                    margin-top: 24;
+                    margin-bottom: 24;
+                    width-request: 24;
+                    height-request: 24;
+                };
+            }
+
+            StackPage {
+                name: "empty";
+                child: Label empty_label {
+                    margin-top: 24;
+                    margin-bottom: 24;
+
+                    styles ["dim-label"]
+                };
+            }
+
+            StackPage {
+                name: "items";
+                child: Box {
+                    orientation: vertical;
+
+                    FlowBox flow_box {
+                        visible: false;
+                        homogeneous: true;
+                        selection-mode: none;
+                        min-children-per-line: 3;
+                        max-children-per-line: 4;
+                        column-spacing: 3;
+                        row-spacing: 3;
+                        child-activated => $on_flow_box_child_activated() swapped;
+                    }
+
+                    ListBox list_box {
+                        visible: false;
+                        selection-mode: none;
+                        row-activated => $on_list_box_row_activated() swapped;
+
+                        styles ["boxed-list"]
+                    }
+                };
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct SharedMediaPage {
+        pub(super) chat: RefCell<Option<Chat>>,
+        pub(super) kind: Cell<SharedMediaKind>,
+        /// The loaded messages, from the newest to the oldest
+        pub(super) messages: RefCell<Vec<Message>>,
+        /// The id of the message to load the next page from, as returned by the last search
+        pub(super) next_from_message_id: Cell<i64>,
+        pub(super) is_loading: Cell<bool>,
+        pub(super) is_exhausted: Cell<bool>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) empty_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) flow_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub(super) list_box: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SharedMediaPage {
+        const NAME: &'static str = "ContentSharedMediaPage";
+        type Type = super::SharedMediaPage;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+
+            klass.install_action("shared-media.show-in-chat", Some("x"), |widget, _, data| {
+                let message_id = data.unwrap().get().unwrap();
+                widget.show_in_chat(message_id);
+            });
+            klass.install_action_async(
+                "shared-media.save-as",
+                Some("x"),
+                |widget, _, data| async move {
+                    let message_id = data.unwrap().get().unwrap();
+                    widget.save_as(message_id).await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SharedMediaPage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Chat>("chat")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<SharedMediaKind>("kind")
+                        .construct_only()
+                        .build(),
+                ]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.set_chat(value.get().unwrap()),
+                "kind" => {
+                    let kind = value.get().unwrap();
+                    self.kind.set(kind);
+                    self.empty_label.set_label(&kind.empty_text());
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat" => obj.chat().to_value(),
+                "kind" => obj.kind().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.stack.unparent();
+        }
+    }
+
+    impl WidgetImpl for SharedMediaPage {
+        fn map(&self) {
+            self.parent_map();
+
+            // Only load the messages once the page is shown for the first time
+            if self.messages.borrow().is_empty() {
+                self.obj().load_more();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub(crate) struct SharedMediaPage(ObjectSubclass<imp::SharedMediaPage>)
+        @extends gtk::Widget;
+}
+
+#[gtk::template_callbacks]
+impl SharedMediaPage {
+    #[template_callback]
+    fn on_flow_box_child_activated(&self, child: &gtk::FlowBoxChild) {
+        self.activate_message_at(child.index());
+    }
+
+    #[template_callback]
+    fn on_list_box_row_activated(&self, row: &gtk::ListBoxRow) {
+        self.activate_message_at(row.index());
+    }
+
+    fn activate_message_at(&self, index: i32) {
+        let Some(message) = self.imp().messages.borrow().get(index as usize).cloned() else {
+            return;
+        };
+
+        // Photos and videos are opened in the media viewer
+        if self.kind() == SharedMediaKind::Media {
+            if let Some(parent) = self.root().and_downcast::<gtk::Window>() {
+                MediaViewer::new(&parent, &message).present();
+            }
+        } else {
+            self.show_in_chat(message.id());
+        }
+    }
+
+    pub(crate) fn chat(&self) -> Option<Chat> {
+        self.imp().chat.borrow().clone()
+    }
+
+    pub(crate) fn set_chat(&self, chat: Option<Chat>) {
+        if self.chat() == chat {
+            return;
+        }
+
+        self.imp().chat.replace(chat);
+        self.clear();
+
+        if self.is_mapped() {
+            self.load_more();
+        }
+
+        self.notify("chat");
+    }
+
+    pub(crate) fn kind(&self) -> SharedMediaKind {
+        self.imp().kind.get()
+    }
+
+    fn clear(&self) {
+        let imp = self.imp();
+
+        imp.messages.borrow_mut().clear();
+        imp.next_from_message_id.set(0);
+        imp.is_exhausted.set(false);
+
+        while let Some(child) = imp.flow_box.first_child() {
+            imp.flow_box.remove(&child);
+        }
+        while let Some(child) = imp.list_box.first_child() {
+            imp.list_box.remove(&child);
+        }
+
+        self.update_stack();
+    }
+
+    /// Loads the next page of older messages, if there are any left.
+    pub(crate) fn load_more(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading.get() || imp.is_exhausted.get() {
+            return;
+        }
+
+        let Some(chat) = self.chat() else {
+            return;
+        };
+
+        let from_message_id = imp.next_from_message_id.get();
+        let filter = self.kind().filter();
+
+        imp.is_loading.set(true);
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = chat
+                .search_messages_page(
+                    String::new(),
+                    None,
+                    from_message_id,
+                    Some(filter),
+                    PAGE_SIZE,
+                )
+                .await;
+
+            let imp = obj.imp();
+            imp.is_loading.set(false);
+
+            // The chat could have been changed in the meantime
+            if obj.chat().as_ref() != Some(&chat) {
+                return;
+            }
+
+            match result {
+                Ok(found) => {
+                    let new_messages: Vec<Message> = found
+                        .messages
+                        .into_iter()
+                        .filter(|message| !imp.messages.borrow().contains(message))
+                        .collect();
+
+                    imp.next_from_message_id.set(found.next_from_message_id);
+                    if found.next_from_message_id == 0 || new_messages.is_empty() {
+                        imp.is_exhausted.set(true);
+                    }
+
+                    for message in new_messages {
+                        obj.append_message(message);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error loading shared media: {e:?}");
+                    imp.is_exhausted.set(true);
+                }
+            }
+
+            obj.update_stack();
+        }));
+    }
+
+    fn append_message(&self, message: Message) {
+        let imp = self.imp();
+        let kind = self.kind();
+
+        if kind == SharedMediaKind::Media {
+            imp.flow_box.append(&Thumbnail::new(&message));
+            imp.flow_box.set_visible(true);
+        } else {
+            imp.list_box.append(&new_message_row(kind, &message));
+            imp.list_box.set_visible(true);
+        }
+
+        imp.messages.borrow_mut().push(message);
+    }
+
+    fn update_stack(&self) {
+        let imp = self.imp();
+
+        let name = if !imp.messages.borrow().is_empty() {
+            "items"
+        } else if imp.is_exhausted.get() {
+            "empty"
+        } else {
+            "loading"
+        };
+
+        imp.stack.set_visible_child_name(name);
+    }
+
+    fn show_in_chat(&self, message_id: i64) {
+        let Some(message) = self.chat().and_then(|chat| chat.message(message_id)) else {
+            return;
+        };
+
+        if let Some(window) = self.root().and_downcast::<gtk::Window>() {
+            window.close();
+        }
+
+        message.chat().session().select_message(&message);
+    }
+
+    async fn save_as(&self, message_id: i64) {
+        let Some(message) = self.chat().and_then(|chat| chat.message(message_id)) else {
+            return;
+        };
+
+        let parent = self.root().and_downcast::<gtk::Window>();
+        save_message_file(&message, parent.as_ref()).await;
+    }
+}
+
+/// Asks the user where to save the file attached to a message and saves it there,
+/// downloading it first if needed.
+pub(super) async fn save_message_file(message: &Message, parent: Option<&gtk::Window>) {
+    let Some((file, file_name)) = message_file(message) else {
+        return;
+    };
+
+    let dialog = gtk::FileDialog::builder()
+        .initial_name(file_name)
+        .modal(true)
+        .build();
+
+    // An error here means that the user has cancelled the dialog
+    let Ok(destination) = dialog.save_future(parent).await else {
+        return;
+    };
+
+    let file = if file.local.is_downloading_completed {
+        file
+    } else {
+        match message.chat().session().download_file(file.id).await {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Failed to download a file: {e:?}");
+                return;
+            }
+        }
+    };
+
+    let (copy, _) = gio::File::for_path(&file.local.path).copy_future(
+        &destination,
+        gio::FileCopyFlags::OVERWRITE,
+        glib::Priority::DEFAULT,
+    );
+    if let Err(e) = copy.await {
+        log::warn!("Failed to save a file: {e:?}");
+    }
+}
+
+/// Returns the menu with the actions that can be done on a shared message.
+fn message_menu(message: &Message) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let item = gio::MenuItem::new(Some(&gettext("Show in Chat")), None);
+    item.set_action_and_target_value(
+        Some("shared-media.show-in-chat"),
+        Some(&message.id().to_variant()),
+    );
+    menu.append_item(&item);
+
+    if message_file(message).is_some() {
+        let item = gio::MenuItem::new(Some(&gettext("Save As…")), None);
+        item.set_action_and_target_value(
+            Some("shared-media.save-as"),
+            Some(&message.id().to_variant()),
+        );
+        menu.append_item(&item);
+    }
+
+    menu
+}
+
+/// Returns the file attached to a message, if any, along with a suggested file name.
+fn message_file(message: &Message) -> Option<(File, String)> {
+    match message.content().0 {
+        MessageContent::MessagePhoto(mut data) => data
+            .photo
+            .sizes
+            .pop()
+            .map(|s| (s.photo, format!("photo_{}.jpg", message.id()))),
+        MessageContent::MessageVideo(data) => {
+            let file_name = if data.video.file_name.is_empty() {
+                format!("video_{}.mp4", message.id())
+            } else {
+                data.video.file_name
+            };
+            Some((data.video.video, file_name))
+        }
+        MessageContent::MessageDocument(data) => {
+            Some((data.document.document, data.document.file_name))
+        }
+        MessageContent::MessageAudio(data) => Some((data.audio.audio, data.audio.file_name)),
+        MessageContent::MessageVoiceNote(data) => {
+            Some((data.voice_note.voice, format!("voice_{}.ogg", message.id())))
+        }
+        _ => None,
+    }
+}
+
+fn new_message_row(kind: SharedMediaKind, message: &Message) -> adw::ActionRow {
+    let date = glib::DateTime::from_unix_local(message.date() as i64)
+        .and_then(|d| d.format("%x"))
+        .map(String::from)
+        .unwrap_or_default();
+
+    let (title, subtitle) = match message.content().0 {
+        MessageContent::MessageDocument(data) => (
+            data.document.file_name,
+            format!(
+                "{} · {date}",
+                glib::format_size(data.document.document.size as u64)
+            ),
+        ),
+        MessageContent::MessageVoiceNote(data) => (
+            strings::message_sender(message.sender(), true),
+            format!(
+                "{} · {date}",
+                human_friendly_duration(data.voice_note.duration)
+            ),
+        ),
+        MessageContent::MessageAudio(data) => {
+            let title = if data.audio.title.is_empty() {
+                data.audio.file_name
+            } else {
+                data.audio.title
+            };
+            let subtitle = if data.audio.performer.is_empty() {
+                human_friendly_duration(data.audio.duration)
+            } else {
+                format!(
+                    "{} · {}",
+                    data.audio.performer,
+                    human_friendly_duration(data.audio.duration)
+                )
+            };
+            (title, subtitle)
+        }
+        _ => {
+            let content = strings::message_content(message);
+            match message_link(message) {
+                Some(link) => (link, content),
+                None => (content, date),
+            }
+        }
+    };
+
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .title_lines(1)
+        .subtitle_lines(1)
+        .use_markup(false)
+        .activatable(true)
+        .build();
+    row.add_prefix(&gtk::Image::from_icon_name(kind.icon_name()));
+
+    let menu_button = gtk::MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .menu_model(&message_menu(message))
+        .valign(gtk::Align::Center)
+        .build();
+    menu_button.add_css_class("flat");
+    row.add_suffix(&menu_button);
+
+    row
+}
+
+/// Returns the first link contained in a text message.
+fn message_link(message: &Message) -> Option<String> {
+    let MessageContent::MessageText(data) = message.content().0 else {
+        return None;
+    };
+
+    if let Some(web_page) = data.web_page {
+        return Some(web_page.url);
+    }
+
+    // The entities offsets are in UTF-16 code units
+    let text = data.text.text.encode_utf16().collect::<Vec<_>>();
+    data.text
+        .entities
+        .into_iter()
+        .find_map(|entity| match entity.r#type {
+            TextEntityType::TextUrl(data) => Some(data.url),
+            TextEntityType::Url => {
+                let start = entity.offset as usize;
+                let end = start + entity.length as usize;
+                text.get(start..end).map(String::from_utf16_lossy)
+            }
+            _ => None,
+        })
+}
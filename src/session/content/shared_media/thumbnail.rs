@@ -0,0 +1,233 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums::MessageContent;
+
+use super::message_menu;
+use crate::session::sidebar::MiniThumbnail;
+use crate::tdlib::Message;
+use crate::utils::decode_image_from_path;
+use crate::utils::decode_minithumbnail;
+use crate::utils::format_playback_time;
+use crate::utils::spawn;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentSharedMediaThumbnail {
+        layout-manager: BinLayout {};
+
+        Overlay overlay {
+            width-request: 96;
+            height-request: 96;
+
+            $SidebarMiniThumbnail picture {}
+
+            [overlay]
+            Label duration_label {
+                visible: false;
+                halign: end;
+                valign: end;
+
+                styles ["osd", "caption", "numeric"]
+            }
+        }
+
+        GestureClick {
+            button: 3;
+            pressed => $on_pressed() swapped;
+        }
+
+        GestureLongPress {
+            touch-only: true;
+            pressed => $on_long_pressed() swapped;
+        }
+    }
+    "#)]
+    pub(crate) struct Thumbnail {
+        pub(super) message: OnceCell<Message>,
+        pub(super) menu: RefCell<Option<gtk::PopoverMenu>>,
+        #[template_child]
+        pub(super) overlay: TemplateChild<gtk::Overlay>,
+        #[template_child]
+        pub(super) picture: TemplateChild<MiniThumbnail>,
+        #[template_child]
+        pub(super) duration_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Thumbnail {
+        const NAME: &'static str = "ContentSharedMediaThumbnail";
+        type Type = super::Thumbnail;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            MiniThumbnail::static_type();
+            klass.bind_template();
+            klass.bind_template_callbacks();
+            klass.set_css_name("sharedmediathumbnail");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl Thumbnail {
+        #[template_callback]
+        fn on_pressed(&self, _n_press: i32, x: f64, y: f64) {
+            self.show_menu(x as i32, y as i32);
+        }
+
+        #[template_callback]
+        fn on_long_pressed(&self, x: f64, y: f64) {
+            self.show_menu(x as i32, y as i32);
+        }
+
+        fn show_menu(&self, x: i32, y: i32) {
+            let obj = self.obj();
+            let menu = self
+                .menu
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let menu = gtk::PopoverMenu::from_model(Some(&message_menu(obj.message())));
+                    menu.set_has_arrow(false);
+                    menu.set_parent(&*obj);
+                    menu
+                })
+                .clone();
+
+            menu.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 0, 0)));
+            menu.popup();
+        }
+    }
+
+    impl ObjectImpl for Thumbnail {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .construct_only()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "message" => self.message.set(value.get().unwrap()).unwrap(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().load_thumbnail();
+        }
+
+        fn dispose(&self) {
+            self.overlay.unparent();
+            if let Some(menu) = self.menu.take() {
+                menu.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for Thumbnail {}
+}
+
+glib::wrapper! {
+    pub(crate) struct Thumbnail(ObjectSubclass<imp::Thumbnail>)
+        @extends gtk::Widget;
+}
+
+impl Thumbnail {
+    pub(crate) fn new(message: &Message) -> Self {
+        glib::Object::builder().property("message", message).build()
+    }
+
+    pub(crate) fn message(&self) -> &Message {
+        self.imp().message.get().unwrap()
+    }
+
+    fn load_thumbnail(&self) {
+        let imp = self.imp();
+
+        let (minithumbnail, file) = match self.message().content().0 {
+            MessageContent::MessagePhoto(data) => {
+                // The "m" size is the smallest one that still looks sharp in the grid
+                let file = data
+                    .photo
+                    .sizes
+                    .iter()
+                    .find(|s| s.r#type == "m")
+                    .or_else(|| data.photo.sizes.last())
+                    .map(|s| s.photo.clone());
+                (data.photo.minithumbnail, file)
+            }
+            MessageContent::MessageVideo(data) => {
+                imp.duration_label
+                    .set_label(&format_playback_time(data.video.duration as i64));
+                imp.duration_label.set_visible(true);
+
+                (
+                    data.video.minithumbnail,
+                    data.video.thumbnail.map(|t| t.file),
+                )
+            }
+            _ => (None, None),
+        };
+
+        imp.picture.set_paintable(
+            minithumbnail
+                .and_then(|m| decode_minithumbnail(&m))
+                .map(|t| t.upcast()),
+        );
+
+        if let Some(file) = file {
+            if file.local.is_downloading_completed {
+                self.load_picture(file.local.path);
+            } else {
+                let session = self.message().chat().session();
+                spawn(clone!(@weak self as obj => async move {
+                    match session.download_file(file.id).await {
+                        Ok(file) => obj.load_picture(file.local.path),
+                        Err(e) => log::warn!("Failed to download a thumbnail: {e:?}"),
+                    }
+                }));
+            }
+        }
+    }
+
+    fn load_picture(&self, path: String) {
+        spawn(clone!(@weak self as obj => async move {
+            let result = gio::spawn_blocking(move || decode_image_from_path(&path))
+                .await
+                .unwrap();
+
+            match result {
+                Ok(texture) => obj.imp().picture.set_paintable(Some(texture.upcast())),
+                Err(e) => log::warn!("Error decoding a thumbnail: {e:?}"),
+            }
+        }));
+    }
+}
@@ -0,0 +1,587 @@
+use std::cell::Cell;
+use std::cell::OnceCell;
+use std::cell::RefCell;
+use std::future::Future;
+
+use gettextrs::gettext;
+use glib::clone;
+use glib::subclass::Signal;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+use tdlib::enums;
+use tdlib::enums::StickerFormat;
+use tdlib::enums::StickerType;
+use tdlib::functions;
+use tdlib::types::Animation;
+use tdlib::types::Error as TdError;
+use tdlib::types::Sticker as TdSticker;
+use tdlib::types::StickerSetInfo;
+
+use crate::components::Sticker;
+use crate::session::content::AnimationPicker;
+use crate::session::Session;
+use crate::tdlib::BoxedAnimation;
+use crate::tdlib::BoxedSticker;
+use crate::tdlib::Chat;
+use crate::utils::spawn;
+use crate::utils::RequestGeneration;
+
+const STICKER_SIZE: i32 = 64;
+const SET_ICON_SIZE: i32 = 24;
+const SEARCH_LIMIT: i32 = 100;
+
+/// The stickers shown by the picker when it's not searching.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Tab {
+    #[default]
+    Recent,
+    Favorite,
+    Set(i64),
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $ContentStickerPicker : Popover {
+        styles ["sticker-picker"]
+
+        child: Box {
+            orientation: vertical;
+            spacing: 6;
+
+            StackSwitcher {
+                stack: main_stack;
+            }
+
+            Stack main_stack {
+                notify::visible-child-name => $on_main_stack_visible_child_notify() swapped;
+
+                StackPage {
+                    name: "stickers";
+                    title: _("Stickers");
+                    child: Box {
+                        orientation: vertical;
+                        spacing: 6;
+
+                        SearchEntry search_entry {
+                            placeholder-text: _("Search by Emoji");
+                            search-changed => $on_search_changed() swapped;
+                        }
+
+                        ScrolledWindow {
+                            vscrollbar-policy: never;
+
+                            child: Box tabs_box {
+                                spacing: 3;
+
+                                ToggleButton recent_button {
+                                    icon-name: "document-open-recent-symbolic";
+                                    tooltip-text: _("Recent");
+                                    active: true;
+                                    toggled => $on_tab_toggled() swapped;
+
+                                    styles ["flat"]
+                                }
+
+                                ToggleButton favorite_button {
+                                    icon-name: "starred-symbolic";
+                                    tooltip-text: _("Favorites");
+                                    group: recent_button;
+                                    toggled => $on_tab_toggled() swapped;
+
+                                    styles ["flat"]
+                                }
+                            };
+                        }
+
+                        Stack stack {
+                            width-request: 360;
+                            height-request: 320;
+
+                            StackPage {
+                                name: "stickers";
+                                child: ScrolledWindow scrolled_window {
+                                    hscrollbar-policy: never;
+
+                                    child: GridView grid_view {
+                                        max-columns: 5;
+                                        single-click-activate: true;
+                                        activate => $on_grid_view_activated() swapped;
+                                    };
+                                };
+                            }
+
+                            StackPage {
+                                name: "loading";
+                                child: Spinner {
+                                    spinning: true;
+                                    halign: center;
+                                    valign: center;
+                                };
+                            }
+
+                            StackPage {
+                                name: "empty";
+                                child: Label empty_label {
+                                    wrap: true;
+                                    justify: center;
+
+                                    styles ["dim-label"]
+                                };
+                            }
+                        }
+                    };
+                }
+
+                StackPage {
+                    name: "animations";
+                    title: _("GIFs");
+                    child: $ContentAnimationPicker animation_picker {
+                        width-request: 360;
+                        height-request: 400;
+                    };
+                }
+            }
+        };
+    }
+    "#)]
+    pub(crate) struct StickerPicker {
+        pub(super) chat: glib::WeakRef<Chat>,
+        pub(super) model: OnceCell<gio::ListStore>,
+        pub(super) tab: Cell<Tab>,
+        pub(super) set_buttons: RefCell<Vec<(i64, gtk::ToggleButton)>>,
+        pub(super) request_generation: RequestGeneration,
+        #[template_child]
+        pub(super) main_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub(super) tabs_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) recent_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub(super) favorite_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) grid_view: TemplateChild<gtk::GridView>,
+        #[template_child]
+        pub(super) empty_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) animation_picker: TemplateChild<AnimationPicker>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StickerPicker {
+        const NAME: &'static str = "ContentStickerPicker";
+        type Type = super::StickerPicker;
+        type ParentType = gtk::Popover;
+
+        fn class_init(klass: &mut Self::Class) {
+            AnimationPicker::static_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StickerPicker {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("sticker-picked")
+                        .param_types([BoxedSticker::static_type()])
+                        .build(),
+                    Signal::builder("animation-picked")
+                        .param_types([BoxedAnimation::static_type()])
+                        .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.setup_grid_view();
+
+            self.animation_picker.connect_animation_picked(
+                clone!(@weak obj => move |_, animation| {
+                    obj.emit_by_name::<()>("animation-picked", &[&BoxedAnimation(animation)]);
+                    obj.popdown();
+                }),
+            );
+        }
+    }
+
+    impl WidgetImpl for StickerPicker {}
+    impl PopoverImpl for StickerPicker {}
+}
+
+glib::wrapper! {
+    pub(crate) struct StickerPicker(ObjectSubclass<imp::StickerPicker>)
+        @extends gtk::Widget, gtk::Popover;
+}
+
+impl Default for StickerPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[gtk::template_callbacks]
+impl StickerPicker {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Shows the picker with the stickers and the GIFs that can be sent to the
+    /// specified chat.
+    pub(crate) fn popup_for_chat(&self, chat: &Chat) {
+        let imp = self.imp();
+
+        imp.chat.set(Some(chat));
+
+        if imp.main_stack.visible_child_name().as_deref() == Some("animations") {
+            imp.animation_picker.load_saved_animations(chat);
+        }
+
+        if imp.search_entry.text().is_empty() {
+            self.load_tab();
+        } else {
+            // The stickers of the selected tab will be loaded by the search entry
+            imp.search_entry.set_text("");
+        }
+        self.load_sticker_sets();
+
+        self.popup();
+    }
+
+    fn setup_grid_view(&self) {
+        let imp = self.imp();
+
+        let model = gio::ListStore::new::<glib::BoxedAnyObject>();
+        imp.grid_view
+            .set_model(Some(&gtk::NoSelection::new(Some(model.clone()))));
+        imp.model.set(model).unwrap();
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+
+            let sticker = Sticker::new();
+            sticker.set_longer_side_size(STICKER_SIZE);
+
+            // Animated stickers are only played once, so replay them when hovered
+            let motion = gtk::EventControllerMotion::new();
+            motion.connect_enter(clone!(@weak sticker => move |_, _, _| {
+                sticker.play_animation();
+            }));
+            sticker.add_controller(motion);
+
+            list_item.set_child(Some(&sticker));
+        });
+        // Only the visible stickers are bound, so that they're downloaded and
+        // rendered lazily
+        factory.connect_bind(clone!(@weak self as obj => move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let item = list_item.item().and_downcast::<glib::BoxedAnyObject>().unwrap();
+            let sticker = list_item.child().and_downcast::<Sticker>().unwrap();
+
+            if let Some(chat) = obj.imp().chat.upgrade() {
+                sticker.update_sticker(item.borrow::<TdSticker>().clone(), false, chat.session());
+            }
+        }));
+        imp.grid_view.set_factory(Some(&factory));
+    }
+
+    #[template_callback]
+    fn on_main_stack_visible_child_notify(&self) {
+        let imp = self.imp();
+
+        // The saved animations are only loaded when needed
+        if imp.main_stack.visible_child_name().as_deref() == Some("animations") {
+            if let Some(chat) = imp.chat.upgrade() {
+                imp.animation_picker.load_saved_animations(&chat);
+            }
+        }
+    }
+
+    #[template_callback]
+    fn on_tab_toggled(&self, button: &gtk::ToggleButton) {
+        if !button.is_active() {
+            return;
+        }
+
+        if button == &*self.imp().favorite_button {
+            self.select_tab(Tab::Favorite);
+        } else {
+            self.select_tab(Tab::Recent);
+        }
+    }
+
+    #[template_callback]
+    fn on_search_changed(&self) {
+        let query = self.imp().search_entry.text();
+        if query.is_empty() {
+            self.load_tab();
+            return;
+        }
+
+        let Some(chat) = self.imp().chat.upgrade() else {
+            return;
+        };
+        let chat_id = chat.id();
+        let client_id = chat.session().client_id();
+        let query = query.to_string();
+
+        self.load_stickers(async move {
+            functions::get_stickers(
+                StickerType::Regular,
+                query,
+                SEARCH_LIMIT,
+                chat_id,
+                client_id,
+            )
+            .await
+            .map(|enums::Stickers::Stickers(data)| data.stickers)
+        });
+    }
+
+    #[template_callback]
+    fn on_grid_view_activated(&self, position: u32) {
+        let Some(item) = self
+            .imp()
+            .model
+            .get()
+            .unwrap()
+            .item(position)
+            .and_downcast::<glib::BoxedAnyObject>()
+        else {
+            return;
+        };
+
+        let sticker = item.borrow::<TdSticker>().clone();
+        self.emit_by_name::<()>("sticker-picked", &[&BoxedSticker(sticker)]);
+        self.popdown();
+    }
+
+    fn select_tab(&self, tab: Tab) {
+        let imp = self.imp();
+
+        if imp.tab.replace(tab) == tab {
+            return;
+        }
+
+        if imp.search_entry.text().is_empty() {
+            self.load_tab();
+        } else {
+            // The stickers of the selected tab will be loaded by the search entry
+            imp.search_entry.set_text("");
+        }
+    }
+
+    fn load_tab(&self) {
+        let Some(chat) = self.imp().chat.upgrade() else {
+            return;
+        };
+        let client_id = chat.session().client_id();
+
+        match self.imp().tab.get() {
+            Tab::Recent => self.load_stickers(async move {
+                functions::get_recent_stickers(false, client_id)
+                    .await
+                    .map(|enums::Stickers::Stickers(data)| data.stickers)
+            }),
+            Tab::Favorite => self.load_stickers(async move {
+                functions::get_favorite_stickers(client_id)
+                    .await
+                    .map(|enums::Stickers::Stickers(data)| data.stickers)
+            }),
+            Tab::Set(set_id) => self.load_stickers(async move {
+                functions::get_sticker_set(set_id, client_id)
+                    .await
+                    .map(|enums::StickerSet::StickerSet(data)| data.stickers)
+            }),
+        }
+    }
+
+    fn load_stickers<F>(&self, future: F)
+    where
+        F: Future<Output = Result<Vec<TdSticker>, TdError>> + 'static,
+    {
+        let imp = self.imp();
+
+        let generation = imp.request_generation.next();
+        imp.stack.set_visible_child_name("loading");
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = future.await;
+
+            if !obj.imp().request_generation.is_current(generation) {
+                return;
+            }
+
+            match result {
+                Ok(stickers) => obj.set_stickers(stickers),
+                Err(e) => {
+                    log::warn!("Error loading stickers: {e:?}");
+                    obj.set_stickers(vec![]);
+                }
+            }
+        }));
+    }
+
+    fn set_stickers(&self, stickers: Vec<TdSticker>) {
+        let imp = self.imp();
+
+        // Video stickers are not supported yet
+        let items = stickers
+            .into_iter()
+            .filter(|s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs))
+            .map(glib::BoxedAnyObject::new)
+            .collect::<Vec<_>>();
+
+        let model = imp.model.get().unwrap();
+        model.splice(0, model.n_items(), &items);
+
+        if items.is_empty() {
+            let label = if !imp.search_entry.text().is_empty() {
+                gettext("No stickers found")
+            } else {
+                match imp.tab.get() {
+                    Tab::Recent => gettext("No recently used stickers"),
+                    Tab::Favorite => gettext("No favorite stickers"),
+                    Tab::Set(_) => gettext("This sticker set is empty"),
+                }
+            };
+            imp.empty_label.set_label(&label);
+            imp.stack.set_visible_child_name("empty");
+        } else {
+            imp.scrolled_window.vadjustment().set_value(0.0);
+            imp.stack.set_visible_child_name("stickers");
+        }
+    }
+
+    fn load_sticker_sets(&self) {
+        let Some(chat) = self.imp().chat.upgrade() else {
+            return;
+        };
+        let session = chat.session();
+        let client_id = session.client_id();
+
+        spawn(clone!(@weak self as obj, @weak session => async move {
+            match functions::get_installed_sticker_sets(StickerType::Regular, client_id).await {
+                Ok(enums::StickerSets::StickerSets(data)) => {
+                    obj.set_sticker_sets(data.sets, &session);
+                }
+                Err(e) => log::warn!("Error loading the installed sticker sets: {e:?}"),
+            }
+        }));
+    }
+
+    fn set_sticker_sets(&self, sets: Vec<StickerSetInfo>, session: &Session) {
+        let imp = self.imp();
+
+        for (_, button) in imp.set_buttons.take() {
+            imp.tabs_box.remove(&button);
+        }
+
+        let buttons = sets
+            .into_iter()
+            .map(|set| {
+                let button = gtk::ToggleButton::builder()
+                    .tooltip_text(&set.title)
+                    .group(&*imp.recent_button)
+                    .css_classes(["flat"])
+                    .build();
+
+                let set_id = set.id;
+                button.set_child(Some(&sticker_set_icon(set, session)));
+                button.connect_toggled(clone!(@weak self as obj => move |button| {
+                    if button.is_active() {
+                        obj.select_tab(Tab::Set(set_id));
+                    }
+                }));
+
+                imp.tabs_box.append(&button);
+                (set_id, button)
+            })
+            .collect::<Vec<_>>();
+
+        // Keep the selected set, unless it has been removed in the meantime
+        if let Tab::Set(set_id) = imp.tab.get() {
+            match buttons.iter().find(|(id, _)| *id == set_id) {
+                Some((_, button)) => button.set_active(true),
+                None => imp.recent_button.set_active(true),
+            }
+        }
+
+        imp.set_buttons.replace(buttons);
+    }
+
+    pub(crate) fn connect_sticker_picked<F: Fn(&Self, TdSticker) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("sticker-picked", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let sticker = values[1].get::<BoxedSticker>().unwrap();
+            f(&obj, sticker.0);
+
+            None
+        })
+    }
+
+    pub(crate) fn connect_animation_picked<F: Fn(&Self, Animation) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("animation-picked", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let animation = values[1].get::<BoxedAnimation>().unwrap();
+            f(&obj, animation.0);
+
+            None
+        })
+    }
+}
+
+/// Returns the widget representing a sticker set in the tabs, which is its first
+/// sticker or, if it can't be shown, the initial of its title.
+fn sticker_set_icon(set: StickerSetInfo, session: &Session) -> gtk::Widget {
+    let cover = set
+        .covers
+        .into_iter()
+        .find(|s| matches!(s.format, StickerFormat::Webp | StickerFormat::Tgs));
+
+    match cover {
+        Some(cover) => {
+            let sticker = Sticker::new();
+            sticker.set_longer_side_size(SET_ICON_SIZE);
+            sticker.update_sticker(cover, false, session.clone());
+            sticker.upcast()
+        }
+        None => {
+            let initial = set
+                .title
+                .chars()
+                .next()
+                .map(String::from)
+                .unwrap_or_default();
+            gtk::Label::new(Some(&initial)).upcast()
+        }
+    }
+}
@@ -10,8 +10,11 @@ use std::collections::hash_map::Entry;
 use std::collections::hash_map::HashMap;
 
 use adw::subclass::prelude::BinImpl;
+use gettextrs::gettext;
 use glib::clone;
+use glib::subclass::Signal;
 use glib::Sender;
+use gtk::gio;
 use gtk::glib;
 use gtk::glib::WeakRef;
 use gtk::prelude::*;
@@ -23,6 +26,8 @@ use tdlib::enums::ChatList as TdChatList;
 use tdlib::enums::NotificationSettingsScope;
 use tdlib::enums::Update;
 use tdlib::functions;
+use tdlib::types::ChatFolderInfo;
+use tdlib::types::ChatListFolder;
 use tdlib::types::ChatPosition as TdChatPosition;
 use tdlib::types::Error as TdError;
 use tdlib::types::File;
@@ -36,6 +41,7 @@ use crate::tdlib::BasicGroup;
 use crate::tdlib::BoxedScopeNotificationSettings;
 use crate::tdlib::Chat;
 use crate::tdlib::ChatList;
+use crate::tdlib::Message;
 use crate::tdlib::SecretChat;
 use crate::tdlib::Supergroup;
 use crate::tdlib::User;
@@ -58,6 +64,7 @@ mod imp {
         pub(super) main_chat_list: OnceCell<ChatList>,
         pub(super) archive_chat_list: OnceCell<ChatList>,
         pub(super) folder_chat_lists: RefCell<HashMap<i32, ChatList>>,
+        pub(super) chat_folder_list: OnceCell<gio::ListStore>,
         pub(super) chats: RefCell<HashMap<i64, Chat>>,
         pub(super) users: RefCell<HashMap<i64, User>>,
         pub(super) basic_groups: RefCell<HashMap<i64, BasicGroup>>,
@@ -70,6 +77,10 @@ mod imp {
         pub(super) channel_chats_notification_settings:
             RefCell<Option<BoxedScopeNotificationSettings>>,
         pub(super) downloading_files: RefCell<HashMap<i32, Vec<Sender<File>>>>,
+        /// The file ids of the favorite stickers, from the most recently added one
+        pub(super) favorite_sticker_ids: RefCell<Vec<i32>>,
+        /// The file ids of the saved animations, from the most recently used one
+        pub(super) saved_animation_ids: RefCell<Vec<i32>>,
         #[template_child]
         pub(super) split_view: TemplateChild<adw::NavigationSplitView>,
         #[template_child]
@@ -113,6 +124,16 @@ mod imp {
     }
 
     impl ObjectImpl for Session {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("favorite-stickers-changed").build(),
+                    Signal::builder("saved-animations-changed").build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
                 vec![
@@ -128,6 +149,9 @@ mod imp {
                     glib::ParamSpecObject::builder::<ChatList>("main-chat-list")
                         .read_only()
                         .build(),
+                    glib::ParamSpecObject::builder::<ChatList>("archive-chat-list")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoxed::builder::<BoxedScopeNotificationSettings>(
                         "private-chats-notification-settings",
                     )
@@ -170,6 +194,7 @@ mod imp {
                 "database-info" => obj.database_info().to_value(),
                 "me" => self.me.upgrade().to_value(),
                 "main-chat-list" => obj.main_chat_list().to_value(),
+                "archive-chat-list" => obj.archive_chat_list().to_value(),
                 "private-chats-notification-settings" => {
                     obj.private_chats_notification_settings().to_value()
                 }
@@ -209,6 +234,9 @@ impl Session {
                 let chat = Chat::new(data.chat, self);
                 self.imp().chats.borrow_mut().insert(chat.id(), chat);
             }
+            Update::ChatFolders(data) => {
+                self.update_chat_folders(data.chat_folders, data.main_chat_list_position);
+            }
             Update::ChatTitle(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatPhoto(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatPermissions(ref data) => self.chat(data.chat_id).handle_update(update),
@@ -222,6 +250,9 @@ impl Session {
             Update::ChatPosition(ref data) => {
                 self.handle_chat_position_update(&self.chat(data.chat_id), &data.position)
             }
+            Update::ChatOnlineMemberCount(ref data) => {
+                self.chat(data.chat_id).handle_update(update)
+            }
             Update::ChatReadInbox(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatReadOutbox(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatDraftMessage(ref data) => {
@@ -241,11 +272,14 @@ impl Session {
             Update::ChatIsMarkedAsUnread(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::DeleteMessages(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::ChatAction(ref data) => self.chat(data.chat_id).handle_update(update),
+            Update::ChatReplyMarkup(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageContent(ref data) => self.chat(data.chat_id).handle_update(update),
+            Update::MessageContentOpened(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageEdited(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageInteractionInfo(ref data) => {
                 self.chat(data.chat_id).handle_update(update)
             }
+            Update::MessageIsPinned(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageMentionRead(ref data) => self.chat(data.chat_id).handle_update(update),
             Update::MessageSendSucceeded(ref data) => {
                 self.chat(data.message.chat_id).handle_update(update)
@@ -260,9 +294,17 @@ impl Session {
                     }
                 }
             }
+            Update::FavoriteStickers(update) => {
+                self.imp().favorite_sticker_ids.replace(update.sticker_ids);
+                self.emit_by_name::<()>("favorite-stickers-changed", &[]);
+            }
             Update::File(update) => {
                 self.handle_file_update(update.file);
             }
+            Update::SavedAnimations(update) => {
+                self.imp().saved_animation_ids.replace(update.animation_ids);
+                self.emit_by_name::<()>("saved-animations-changed", &[]);
+            }
             Update::ScopeNotificationSettings(update) => {
                 let settings = Some(BoxedScopeNotificationSettings(update.notification_settings));
                 match update.scope {
@@ -398,12 +440,20 @@ impl Session {
 
     /// Returns the main chat list.
     pub(crate) fn main_chat_list(&self) -> &ChatList {
-        self.imp().main_chat_list.get_or_init(ChatList::new)
+        self.imp().main_chat_list.get_or_init(|| {
+            let chat_list = ChatList::new(TdChatList::Main);
+            chat_list.set_title(gettext("All Chats"));
+            chat_list
+        })
     }
 
     /// Returns the list of archived chats.
     pub(crate) fn archive_chat_list(&self) -> &ChatList {
-        self.imp().archive_chat_list.get_or_init(ChatList::new)
+        self.imp().archive_chat_list.get_or_init(|| {
+            let chat_list = ChatList::new(TdChatList::Archive);
+            chat_list.set_title(gettext("Archived Chats"));
+            chat_list
+        })
     }
 
     /// Returns the folder chat list of the specified id.
@@ -412,10 +462,56 @@ impl Session {
             .folder_chat_lists
             .borrow_mut()
             .entry(chat_folder_id)
-            .or_insert_with(ChatList::new)
+            .or_insert_with(|| ChatList::new(TdChatList::Folder(ChatListFolder { chat_folder_id })))
             .clone()
     }
 
+    /// Returns the ordered list of chat lists the user can switch between in the sidebar.
+    ///
+    /// It is empty when the user has no chat folders. Otherwise, it contains the
+    /// `ChatList` of every folder and the main chat list at the position chosen by the user.
+    pub(crate) fn chat_folder_list(&self) -> &gio::ListStore {
+        self.imp()
+            .chat_folder_list
+            .get_or_init(gio::ListStore::new::<ChatList>)
+    }
+
+    fn update_chat_folders(&self, chat_folders: Vec<ChatFolderInfo>, main_chat_list_position: i32) {
+        let client_id = self.client_id();
+        let chat_folder_list = self.chat_folder_list();
+        let old_chat_lists: Vec<glib::Object> = (0..chat_folder_list.n_items())
+            .filter_map(|pos| chat_folder_list.item(pos))
+            .collect();
+
+        let mut chat_lists: Vec<ChatList> = chat_folders
+            .into_iter()
+            .map(|folder| {
+                let chat_list = self.folder_chat_list(folder.id);
+                chat_list.set_title(folder.title);
+
+                // Chats of a folder are only sent by TDLib after loading them once
+                if !old_chat_lists.contains(chat_list.upcast_ref()) {
+                    chat_list.fetch(client_id);
+                }
+
+                chat_list
+            })
+            .collect();
+
+        // Forget about the folders that have been deleted
+        self.imp()
+            .folder_chat_lists
+            .borrow_mut()
+            .retain(|_, chat_list| chat_lists.contains(chat_list));
+
+        if !chat_lists.is_empty() {
+            let position = (main_chat_list_position.max(0) as usize).min(chat_lists.len());
+            chat_lists.insert(position, self.main_chat_list().clone());
+        }
+
+        chat_folder_list.splice(0, chat_folder_list.n_items(), &chat_lists);
+    }
+
     /// Fetches the contacts of the user.
     pub(crate) async fn fetch_contacts(&self) -> Result<Vec<User>, TdError> {
         let client_id = self.imp().client_id.get();
@@ -482,6 +578,40 @@ impl Session {
         });
     }
 
+    /// Returns whether the sticker with the specified file id is among the favorite ones.
+    pub(crate) fn is_favorite_sticker(&self, file_id: i32) -> bool {
+        self.imp().favorite_sticker_ids.borrow().contains(&file_id)
+    }
+
+    pub(crate) fn connect_favorite_stickers_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("favorite-stickers-changed", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+
+            None
+        })
+    }
+
+    /// Returns whether the animation with the specified file id is among the saved ones.
+    pub(crate) fn is_saved_animation(&self, file_id: i32) -> bool {
+        self.imp().saved_animation_ids.borrow().contains(&file_id)
+    }
+
+    pub(crate) fn connect_saved_animations_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("saved-animations-changed", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+
+            None
+        })
+    }
+
     pub(crate) fn select_chat(&self, chat_id: i64) {
         match self.try_chat(chat_id) {
             Some(chat) => self.imp().sidebar.set_selected_chat(Some(chat)),
@@ -494,6 +624,13 @@ impl Session {
         }
     }
 
+    /// Opens the chat of the specified message and scrolls to it.
+    pub(crate) fn select_message(&self, message: &Message) {
+        let imp = self.imp();
+        imp.sidebar.set_selected_chat(Some(message.chat()));
+        imp.content.scroll_to_message(message.id());
+    }
+
     pub(crate) fn handle_paste_action(&self) {
         self.imp().content.handle_paste_action();
     }
@@ -624,6 +761,7 @@ impl Session {
     pub(crate) fn fetch_chats(&self) {
         let client_id = self.imp().client_id.get();
         self.main_chat_list().fetch(client_id);
+        self.archive_chat_list().fetch(client_id);
     }
 
     pub(crate) fn set_sessions(&self, sessions: gtk::SelectionModel) {
@@ -1,4 +1,5 @@
 use std::cell::OnceCell;
+use std::cell::RefCell;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
@@ -8,6 +9,9 @@ use gtk::gio;
 use gtk::glib;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
+use tdlib::enums;
+use tdlib::functions;
+use tdlib::types;
 
 use crate::config::APP_ID;
 use crate::utils::spawn;
@@ -20,11 +24,17 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/preferences-window.ui")]
     pub(crate) struct PreferencesWindow {
         pub(super) session: OnceCell<Session>,
+        /// The archive chat list settings as they were last saved on the server
+        pub(super) archive_settings: RefCell<Option<types::ArchiveChatListSettings>>,
         #[template_child]
         pub(super) follow_system_colors_switch: TemplateChild<gtk::Switch>,
         #[template_child]
         pub(super) dark_theme_switch: TemplateChild<gtk::Switch>,
         #[template_child]
+        pub(super) keep_unmuted_chats_archived_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub(super) archive_new_chats_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
         pub(super) cache_size_label: TemplateChild<gtk::Label>,
     }
 
@@ -96,6 +106,10 @@ mod imp {
 
             obj.setup_bindings();
 
+            spawn(clone!(@weak obj => async move {
+                obj.load_archive_settings().await;
+            }));
+
             spawn(clone!(@weak obj => async move {
                 obj.calculate_cache_size().await;
             }));
@@ -175,6 +189,76 @@ impl PreferencesWindow {
             .build();
     }
 
+    async fn load_archive_settings(&self) {
+        let client_id = self.session().client_id();
+        match functions::get_archive_chat_list_settings(client_id).await {
+            Ok(enums::ArchiveChatListSettings::ArchiveChatListSettings(settings)) => {
+                let imp = self.imp();
+
+                self.set_archive_switches(&settings);
+                imp.archive_settings.replace(Some(settings));
+
+                // Only start listening for changes after the initial state has been loaded
+                let save = clone!(@weak self as obj => move |_: &gtk::Switch| {
+                    spawn(clone!(@weak obj => async move {
+                        obj.save_archive_settings().await;
+                    }));
+                });
+                imp.keep_unmuted_chats_archived_switch
+                    .connect_active_notify(save.clone());
+                imp.archive_new_chats_switch.connect_active_notify(save);
+
+                imp.keep_unmuted_chats_archived_switch.set_sensitive(true);
+                imp.archive_new_chats_switch.set_sensitive(true);
+            }
+            Err(e) => {
+                log::warn!("Error getting the archive chat list settings: {e:?}");
+            }
+        }
+    }
+
+    fn set_archive_switches(&self, settings: &types::ArchiveChatListSettings) {
+        let imp = self.imp();
+        imp.keep_unmuted_chats_archived_switch
+            .set_active(settings.keep_unmuted_chats_archived);
+        imp.archive_new_chats_switch
+            .set_active(settings.archive_and_mute_new_chats_from_unknown_users);
+    }
+
+    async fn save_archive_settings(&self) {
+        let imp = self.imp();
+        let Some(previous_settings) = imp.archive_settings.borrow().clone() else {
+            return;
+        };
+
+        // The folders setting isn't exposed yet, so keep it as it is
+        let settings = types::ArchiveChatListSettings {
+            archive_and_mute_new_chats_from_unknown_users: imp.archive_new_chats_switch.is_active(),
+            keep_unmuted_chats_archived: imp.keep_unmuted_chats_archived_switch.is_active(),
+            keep_chats_from_folders_archived: previous_settings.keep_chats_from_folders_archived,
+        };
+
+        // Nothing to save, e.g. when the switches have just been reverted
+        if settings == previous_settings {
+            return;
+        }
+
+        imp.archive_settings.replace(Some(settings.clone()));
+
+        let client_id = self.session().client_id();
+        if let Err(e) = functions::set_archive_chat_list_settings(settings, client_id).await {
+            log::warn!("Error setting the archive chat list settings: {e:?}");
+            self.add_toast(adw::Toast::new(&gettext(
+                "Failed to save the archive settings",
+            )));
+
+            // Show the settings that are still in effect on the server
+            imp.archive_settings
+                .replace(Some(previous_settings.clone()));
+            self.set_archive_switches(&previous_settings);
+        }
+    }
+
     async fn calculate_cache_size(&self) {
         let client_id = self.session().client_id();
         match tdlib::functions::get_storage_statistics(0, client_id).await {
@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::tdlib::ChatList;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $SidebarFolderTab {
+        Label title_label {
+            label: bind template.chat-list as <$ChatList>.title;
+            ellipsize: end;
+            max-width-chars: 20;
+        }
+
+        Label unread_count_label {
+            label: bind template.chat-list as <$ChatList>.unread-count;
+            visible: bind template.chat-list as <$ChatList>.unread-count;
+            valign: center;
+
+            styles ["unread-count"]
+        }
+    }
+    "#)]
+    pub(crate) struct FolderTab {
+        pub(super) chat_list: RefCell<Option<ChatList>>,
+        #[template_child]
+        pub(super) title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) unread_count_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FolderTab {
+        const NAME: &'static str = "SidebarFolderTab";
+        type Type = super::FolderTab;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+            klass.set_css_name("sidebarfoldertab");
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for FolderTab {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<ChatList>("chat-list")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat-list" => obj.set_chat_list(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "chat-list" => obj.chat_list().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.title_label.unparent();
+            self.unread_count_label.unparent();
+        }
+    }
+
+    impl WidgetImpl for FolderTab {}
+}
+
+glib::wrapper! {
+    pub(crate) struct FolderTab(ObjectSubclass<imp::FolderTab>)
+        @extends gtk::Widget;
+}
+
+impl FolderTab {
+    pub(crate) fn chat_list(&self) -> Option<ChatList> {
+        self.imp().chat_list.borrow().clone()
+    }
+
+    pub(crate) fn set_chat_list(&self, chat_list: Option<ChatList>) {
+        if self.chat_list() == chat_list {
+            return;
+        }
+        self.imp().chat_list.replace(chat_list);
+        self.notify("chat-list");
+    }
+}
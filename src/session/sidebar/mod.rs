@@ -1,4 +1,5 @@
 mod avatar;
+mod folder_tab;
 mod mini_thumbnail;
 mod row;
 mod search;
@@ -17,13 +18,16 @@ use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
 
 pub(crate) use self::avatar::Avatar;
-use self::row::Row;
+use self::folder_tab::FolderTab;
+pub(crate) use self::mini_thumbnail::MiniThumbnail;
+pub(crate) use self::row::Row;
 use self::search::Search;
 use self::selection::Selection;
 use self::session_switcher::SessionSwitcher;
 use crate::components::Avatar as ComponentsAvatar;
 use crate::components::Snow as ComponentsSnow;
 use crate::tdlib::Chat;
+use crate::tdlib::ChatList;
 use crate::tdlib::ChatListItem;
 use crate::utils::spawn;
 use crate::Session;
@@ -38,6 +42,8 @@ mod imp {
         pub(super) selected_chat: RefCell<Option<Chat>>,
         pub(super) marked_as_unread_handler_id: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) session: RefCell<Option<Session>>,
+        pub(super) chat_folder_list_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        pub(super) archive_chat_list_handler_id: RefCell<Option<glib::SignalHandlerId>>,
         pub(super) row_menu: OnceCell<gtk::PopoverMenu>,
         #[template_child]
         pub(super) snow: TemplateChild<ComponentsSnow>,
@@ -48,6 +54,14 @@ mod imp {
         #[template_child]
         pub(super) session_switcher: TemplateChild<SessionSwitcher>,
         #[template_child]
+        pub(super) folder_bar: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) folder_selection: TemplateChild<gtk::SingleSelection>,
+        #[template_child]
+        pub(super) archive_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) archive_header: TemplateChild<gtk::Box>,
+        #[template_child]
         pub(super) selection: TemplateChild<Selection>,
         #[template_child]
         pub(super) search: TemplateChild<Search>,
@@ -61,6 +75,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             ComponentsAvatar::static_type();
+            FolderTab::static_type();
             Row::static_type();
             klass.bind_template();
             klass.bind_template_callbacks();
@@ -68,6 +83,12 @@ mod imp {
             klass.install_action("sidebar.start-search", None, move |widget, _, _| {
                 widget.begin_chats_search();
             });
+            klass.install_action("sidebar.show-archive", None, move |widget, _, _| {
+                widget.show_archive();
+            });
+            klass.install_action("sidebar.hide-archive", None, move |widget, _, _| {
+                widget.hide_archive();
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -85,6 +106,13 @@ mod imp {
             self.obj().set_selected_chat(Some(item.chat()));
         }
 
+        #[template_callback]
+        fn folder_activate(&self, pos: u32) {
+            self.folder_selection.set_selected(pos);
+            self.obj()
+                .set_chat_list(self.folder_selection.selected_item().and_downcast());
+        }
+
         #[template_callback]
         fn close_search(&self) {
             self.stack.set_visible_child(&*self.main_view);
@@ -227,7 +255,9 @@ impl Sidebar {
             );
             imp.marked_as_unread_handler_id.replace(Some(handler_id));
 
-            let item = chat.session().main_chat_list().find_chat_item(chat.id());
+            let item = self
+                .chat_list()
+                .and_then(|chat_list| chat_list.find_chat_item(chat.id()));
             imp.selection.set_selected_item(item.map(|i| i.upcast()));
 
             if chat.is_marked_as_unread() {
@@ -256,15 +286,123 @@ impl Sidebar {
 
         let imp = self.imp();
 
+        if let Some(old_session) = imp.session.replace(session.clone()) {
+            if let Some(handler_id) = imp.chat_folder_list_handler_id.take() {
+                old_session.chat_folder_list().disconnect(handler_id);
+            }
+            if let Some(handler_id) = imp.archive_chat_list_handler_id.take() {
+                old_session.archive_chat_list().disconnect(handler_id);
+            }
+        }
+
         if let Some(ref session) = session {
-            imp.selection
-                .set_model(Some(session.main_chat_list().clone().upcast()));
+            let chat_folder_list = session.chat_folder_list();
+            imp.folder_selection.set_model(Some(chat_folder_list));
+            let handler_id = chat_folder_list.connect_items_changed(
+                clone!(@weak self as obj => move |_, _, _, _| {
+                    obj.update_folder_bar();
+                }),
+            );
+            imp.chat_folder_list_handler_id.replace(Some(handler_id));
+            let handler_id = session.archive_chat_list().connect_items_changed(
+                clone!(@weak self as obj => move |_, _, _, _| {
+                    obj.update_archive_widgets();
+                }),
+            );
+            imp.archive_chat_list_handler_id.replace(Some(handler_id));
+
+            self.set_chat_list(Some(session.main_chat_list().clone()));
+            self.update_folder_bar();
         }
 
-        imp.session.replace(session);
         self.notify("session");
     }
 
+    /// Returns the `ChatList` currently shown in the sidebar.
+    fn chat_list(&self) -> Option<ChatList> {
+        self.imp().selection.model().and_downcast()
+    }
+
+    fn set_chat_list(&self, chat_list: Option<ChatList>) {
+        if self.chat_list() == chat_list {
+            return;
+        }
+
+        let imp = self.imp();
+        imp.selection
+            .set_model(chat_list.clone().map(|l| l.upcast()));
+
+        // Keep the selected chat highlighted, if it's part of the new chat list
+        let item = self
+            .selected_chat()
+            .and_then(|chat| chat_list.and_then(|chat_list| chat_list.find_chat_item(chat.id())));
+        imp.selection.set_selected_item(item.map(|i| i.upcast()));
+
+        self.update_archive_widgets();
+    }
+
+    fn show_archive(&self) {
+        if let Some(session) = self.session() {
+            self.imp()
+                .folder_selection
+                .set_selected(gtk::INVALID_LIST_POSITION);
+            self.set_chat_list(Some(session.archive_chat_list().clone()));
+        }
+    }
+
+    fn hide_archive(&self) {
+        if let Some(session) = self.session() {
+            self.set_chat_list(Some(session.main_chat_list().clone()));
+            self.update_folder_bar();
+        }
+    }
+
+    fn update_archive_widgets(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let imp = self.imp();
+        let chat_list = self.chat_list();
+        let archive_chat_list = session.archive_chat_list();
+
+        imp.archive_button.set_visible(
+            chat_list.as_ref() == Some(session.main_chat_list()) && archive_chat_list.n_items() > 0,
+        );
+        imp.archive_header
+            .set_visible(chat_list.as_ref() == Some(archive_chat_list));
+    }
+
+    fn update_folder_bar(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let imp = self.imp();
+        let n_items = imp.folder_selection.n_items();
+
+        imp.folder_bar.set_visible(n_items > 0);
+
+        let find_position = |chat_list: &ChatList| {
+            (0..n_items).find(|pos| {
+                imp.folder_selection.item(*pos).as_ref() == Some(chat_list.upcast_ref())
+            })
+        };
+
+        // Stay on the same chat list after folders have been changed or reordered, or fall back
+        // to the main chat list if its folder has been deleted
+        let chat_list = self
+            .chat_list()
+            .filter(|chat_list| {
+                chat_list == session.archive_chat_list() || find_position(chat_list).is_some()
+            })
+            .unwrap_or_else(|| session.main_chat_list().clone());
+
+        imp.folder_selection
+            .set_selected(find_position(&chat_list).unwrap_or(gtk::INVALID_LIST_POSITION));
+        self.set_chat_list(Some(chat_list));
+    }
+
     pub(crate) fn session(&self) -> Option<Session> {
         self.imp().session.borrow().to_owned()
     }
@@ -10,6 +10,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use once_cell::sync::Lazy;
+use tdlib::enums::ChatList as TdChatList;
 use tdlib::enums::InputMessageContent;
 use tdlib::enums::MessageContent;
 use tdlib::enums::MessageSendingState;
@@ -83,6 +84,12 @@ mod imp {
             klass.install_action("sidebar-row.mark-as-read", None, move |widget, _, _| {
                 widget.toggle_chat_marked_as_unread()
             });
+            klass.install_action("sidebar-row.archive", None, move |widget, _, _| {
+                widget.set_chat_is_archived(true)
+            });
+            klass.install_action("sidebar-row.unarchive", None, move |widget, _, _| {
+                widget.set_chat_is_archived(false)
+            });
 
             Avatar::static_type();
         }
@@ -106,7 +113,11 @@ mod imp {
 
         fn show_menu(&self, x: i32, y: i32) {
             let obj = self.obj();
-            let sidebar = obj.ancestor(Sidebar::static_type()).unwrap();
+
+            // Rows can also be used outside of the sidebar, where there's no menu to show
+            let Some(sidebar) = obj.ancestor(Sidebar::static_type()) else {
+                return;
+            };
             let menu = sidebar.downcast_ref::<Sidebar>().unwrap().row_menu();
 
             menu.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 0, 0)));
@@ -203,6 +214,16 @@ impl Row {
         }
     }
 
+    fn set_chat_is_archived(&self, is_archived: bool) {
+        if let Some(chat) = self.item().map(|i| i.chat()) {
+            spawn(async move {
+                if let Err(e) = chat.set_is_archived(is_archived).await {
+                    log::warn!("Error on toggling chat's archived state: {e:?}");
+                }
+            });
+        }
+    }
+
     fn setup_expressions(&self) {
         let imp = self.imp();
         let item_expression = Self::this_expression("item");
@@ -556,9 +577,13 @@ impl Row {
                     chat.is_marked_as_unread(),
                 );
             }
+
+            let is_archived = matches!(item.chat_list_type(), TdChatList::Archive);
+            self.update_archive_actions(!is_archived && !chat.is_own_chat(), is_archived);
         } else {
             self.update_pin_actions(false, false);
             self.update_mark_as_unread_actions(false, false);
+            self.update_archive_actions(false, false);
         }
     }
 
@@ -571,6 +596,11 @@ impl Row {
         self.action_set_enabled("sidebar-row.mark-as-unread", unread);
         self.action_set_enabled("sidebar-row.mark-as-read", read);
     }
+
+    fn update_archive_actions(&self, archive: bool, unarchive: bool) {
+        self.action_set_enabled("sidebar-row.archive", archive);
+        self.action_set_enabled("sidebar-row.unarchive", unarchive);
+    }
 }
 
 fn sender_label(message: Message) -> Option<String> {
@@ -645,7 +675,7 @@ fn draft_message_text(message: DraftMessage) -> String {
     }
 }
 
-fn timestamp_text(date: i64) -> glib::GString {
+pub(super) fn timestamp_text(date: i64) -> glib::GString {
     let datetime_now = glib::DateTime::now_local().unwrap();
     let datetime = glib::DateTime::from_unix_utc(date)
         .and_then(|t| t.to_local())
@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use once_cell::sync::Lazy;
+
+use crate::components::Avatar;
+use crate::session::sidebar::row::timestamp_text;
+use crate::strings;
+use crate::tdlib::ChatType;
+use crate::tdlib::Message;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(string = r#"
+    template $SidebarSearchMessageRow {
+        $ComponentsAvatar avatar {
+            size: 42;
+        }
+
+        Box {
+            orientation: vertical;
+            valign: center;
+            hexpand: true;
+
+            Box {
+                spacing: 6;
+
+                Inscription chat_label {
+                    hexpand: true;
+                    text-overflow: ellipsize_end;
+
+                    styles ["heading"]
+                }
+
+                Label date_label {
+                    styles ["dim-label", "caption", "numeric"]
+                }
+            }
+
+            Inscription content_label {
+                text-overflow: ellipsize_end;
+
+                styles ["dim-label"]
+            }
+        }
+    }
+    "#)]
+    pub(crate) struct MessageRow {
+        pub(super) message: RefCell<Option<Message>>,
+        #[template_child]
+        pub(super) avatar: TemplateChild<Avatar>,
+        #[template_child]
+        pub(super) chat_label: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub(super) date_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) content_label: TemplateChild<gtk::Inscription>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageRow {
+        const NAME: &'static str = "SidebarSearchMessageRow";
+        type Type = super::MessageRow;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.set_css_name("sidebarsearchmessagerow");
+            klass.set_layout_manager_type::<gtk::BoxLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Message>("message")
+                    .explicit_notify()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.set_message(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "message" => obj.message().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.dispose_template();
+        }
+    }
+
+    impl WidgetImpl for MessageRow {}
+}
+
+glib::wrapper! {
+    pub(crate) struct MessageRow(ObjectSubclass<imp::MessageRow>)
+        @extends gtk::Widget;
+}
+
+impl MessageRow {
+    pub(crate) fn new(message: &Message) -> Self {
+        glib::Object::builder().property("message", message).build()
+    }
+
+    pub(crate) fn message(&self) -> Option<Message> {
+        self.imp().message.borrow().clone()
+    }
+
+    pub(crate) fn set_message(&self, message: Option<Message>) {
+        if self.message() == message {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(ref message) = message {
+            let chat = message.chat();
+
+            imp.avatar.set_item(Some(chat.clone().upcast()));
+            imp.chat_label.set_text(Some(&if chat.is_own_chat() {
+                gettext("Saved Messages")
+            } else {
+                chat.title()
+            }));
+            imp.date_label
+                .set_label(&timestamp_text(message.date() as i64));
+
+            // Show the sender only in groups
+            let show_sender = match chat.type_() {
+                ChatType::BasicGroup(_) => true,
+                ChatType::Supergroup(supergroup) => !supergroup.is_channel(),
+                _ => false,
+            };
+            let content = strings::message_content(message);
+            imp.content_label.set_text(Some(&if show_sender {
+                format!(
+                    "{}: {content}",
+                    strings::message_sender(message.sender(), false)
+                )
+            } else {
+                content
+            }));
+        }
+
+        imp.message.replace(message);
+        self.notify("message");
+    }
+}
@@ -1,8 +1,10 @@
 mod item_row;
+mod message_row;
 mod row;
 mod section;
 mod section_row;
 
+use std::cell::Cell;
 use std::cell::RefCell;
 
 use gettextrs::gettext;
@@ -18,16 +20,54 @@ use tdlib::enums;
 use tdlib::functions;
 
 use self::item_row::ItemRow;
+use self::message_row::MessageRow;
 use self::row::Row;
 use self::section::Section;
 use self::section::SectionType;
 use self::section_row::SectionRow;
+use crate::i18n::gettext_f;
 use crate::session::Sidebar;
 use crate::tdlib::Chat;
+use crate::tdlib::Message;
 use crate::tdlib::User;
 use crate::utils::spawn;
+use crate::utils::RequestGeneration;
 use crate::Session;
 
+/// The filters of the found messages, in the order of the filter drop-down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessagesFilter {
+    All,
+    Photos,
+    Documents,
+    Links,
+    VoiceMessages,
+}
+
+impl MessagesFilter {
+    fn from_position(position: u32) -> Self {
+        match position {
+            1 => Self::Photos,
+            2 => Self::Documents,
+            3 => Self::Links,
+            4 => Self::VoiceMessages,
+            _ => Self::All,
+        }
+    }
+
+    fn search_filter(self) -> Option<enums::SearchMessagesFilter> {
+        use enums::SearchMessagesFilter::*;
+
+        match self {
+            Self::All => None,
+            Self::Photos => Some(Photo),
+            Self::Documents => Some(Document),
+            Self::Links => Some(Url),
+            Self::VoiceMessages => Some(VoiceNote),
+        }
+    }
+}
+
 mod imp {
     use super::*;
 
@@ -35,13 +75,34 @@ mod imp {
     #[template(resource = "/app/drey/paper-plane/ui/sidebar-search.ui")]
     pub(crate) struct Search {
         pub(super) session: RefCell<Option<Session>>,
+        pub(super) messages_list: RefCell<Option<gio::ListStore>>,
+        /// The offset of the next page of found messages, `None` if there are no more
+        pub(super) messages_next_offset: RefCell<Option<String>>,
+        pub(super) messages_generation: RequestGeneration,
+        pub(super) is_loading_messages: Cell<bool>,
+        pub(super) min_date: Cell<i32>,
+        pub(super) max_date: Cell<i32>,
         #[template_child]
         pub(super) toolbar_view: TemplateChild<adw::ToolbarView>,
         #[template_child]
         pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
         #[template_child]
+        pub(super) filter_bar: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) filter_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub(super) date_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub(super) date_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub(super) min_date_calendar: TemplateChild<gtk::Calendar>,
+        #[template_child]
+        pub(super) max_date_calendar: TemplateChild<gtk::Calendar>,
+        #[template_child]
         pub(super) stack: TemplateChild<gtk::Stack>,
         #[template_child]
+        pub(super) list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
         pub(super) selection: TemplateChild<gtk::NoSelection>,
     }
 
@@ -62,6 +123,20 @@ mod imp {
             klass.install_action("sidebar-search.go-back", None, move |widget, _, _| {
                 widget.emit_by_name::<()>("close", &[]);
             });
+            klass.install_action(
+                "sidebar-search.apply-date-range",
+                None,
+                move |widget, _, _| {
+                    widget.apply_date_range();
+                },
+            );
+            klass.install_action(
+                "sidebar-search.reset-date-range",
+                None,
+                move |widget, _, _| {
+                    widget.reset_date_range();
+                },
+            );
             klass.install_action_async(
                 "sidebar-search.clear-recent-chats",
                 None,
@@ -117,6 +192,20 @@ mod imp {
             }
         }
 
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            // Load more found messages when reaching the end of the list
+            let adj = self.list_view.vadjustment().unwrap();
+            adj.connect_value_changed(clone!(@weak obj => move |adj| {
+                if adj.value() + adj.page_size() * 2.0 >= adj.upper() {
+                    obj.load_more_messages();
+                }
+            }));
+        }
+
         fn dispose(&self) {
             self.dispose_template();
         }
@@ -169,19 +258,30 @@ impl Search {
         let session = self.session().unwrap();
         let query = imp.search_entry.text().to_string();
         let list = gio::ListStore::new::<glib::Object>();
+        let messages_list = gio::ListStore::new::<glib::Object>();
         let mut found_chat_ids: Vec<i64> = vec![];
 
         const MAX_KNOWN_CHATS: i32 = 50;
 
-        imp.selection.set_model(Some(&list));
-        list.connect_items_changed(clone!(@weak self as obj => move |list, _, _, _| {
-            obj.imp().stack.set_visible_child_name(if list.n_items() > 0 {
+        // Show the found messages after the found chats
+        let lists = gio::ListStore::new::<gio::ListModel>();
+        lists.append(&list);
+        lists.append(&messages_list);
+        let model = gtk::FlattenListModel::new(Some(lists));
+
+        imp.selection.set_model(Some(&model));
+        model.connect_items_changed(clone!(@weak self as obj => move |model, _, _, _| {
+            obj.imp().stack.set_visible_child_name(if model.n_items() > 0 {
                 "results"
             } else {
                 "empty"
             });
         }));
 
+        imp.filter_bar.set_visible(!query.is_empty());
+        imp.messages_list.replace(Some(messages_list));
+        self.reload_messages();
+
         // Show the results page prematurely, so that we don't show the empty page
         // before even starting the search.
         imp.stack.set_visible_child_name("results");
@@ -226,7 +326,7 @@ impl Search {
         }
 
         // Show the empty page if there are no results after the first part of the search
-        if list.n_items() == 0 {
+        if model.n_items() == 0 {
             imp.stack.set_visible_child_name("empty");
         }
 
@@ -360,10 +460,168 @@ impl Search {
             {
                 log::warn!("Failed to add recently found chat: {:?}", e);
             }
+        } else if let Some(message) = item.downcast_ref::<Message>() {
+            session.select_message(message);
         } else {
             log::warn!("Unexpected item type: {:?}", item);
         }
 
         self.emit_by_name::<()>("close", &[]);
     }
+
+    /// Restarts the search of messages, e.g. after the filters have changed.
+    #[template_callback]
+    fn reload_messages(&self) {
+        let imp = self.imp();
+
+        if let Some(list) = imp.messages_list.borrow().as_ref() {
+            list.remove_all();
+        }
+
+        imp.messages_generation.next();
+        imp.messages_next_offset.replace(Some(String::new()));
+        imp.is_loading_messages.set(false);
+
+        self.load_more_messages();
+    }
+
+    fn load_more_messages(&self) {
+        let imp = self.imp();
+
+        if imp.is_loading_messages.get() {
+            return;
+        }
+
+        let query = imp.search_entry.text().to_string();
+        if query.is_empty() {
+            return;
+        }
+
+        let Some(list) = imp.messages_list.borrow().clone() else {
+            return;
+        };
+        let Some(offset) = imp.messages_next_offset.borrow().clone() else {
+            return;
+        };
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        const MESSAGES_LIMIT: i32 = 20;
+
+        let filter = self.messages_filter();
+        let min_date = imp.min_date.get();
+        let max_date = imp.max_date.get();
+        let generation = imp.messages_generation.current();
+
+        imp.is_loading_messages.set(true);
+
+        spawn(clone!(@weak self as obj => async move {
+            let result = functions::search_messages(
+                None,
+                query,
+                offset,
+                MESSAGES_LIMIT,
+                filter,
+                min_date,
+                max_date,
+                session.client_id(),
+            )
+            .await;
+
+            let imp = obj.imp();
+
+            // Ignore the results of an outdated search
+            if !imp.messages_generation.is_current(generation) {
+                return;
+            }
+
+            imp.is_loading_messages.set(false);
+
+            match result {
+                Ok(enums::FoundMessages::FoundMessages(data)) => {
+                    let messages: Vec<Message> = data
+                        .messages
+                        .into_iter()
+                        .map(|m| session.chat(m.chat_id).cache_message(m))
+                        .collect();
+
+                    if list.n_items() == 0 && !messages.is_empty() {
+                        list.append(&Section::new(SectionType::Messages));
+                    }
+                    list.extend_from_slice(&messages);
+
+                    imp.messages_next_offset.replace(if data.next_offset.is_empty() {
+                        None
+                    } else {
+                        Some(data.next_offset)
+                    });
+                }
+                Err(e) => {
+                    log::warn!("Error searching messages: {:?}", e);
+                    imp.messages_next_offset.replace(None);
+                }
+            }
+        }));
+    }
+
+    fn messages_filter(&self) -> Option<enums::SearchMessagesFilter> {
+        MessagesFilter::from_position(self.imp().filter_drop_down.selected()).search_filter()
+    }
+
+    fn apply_date_range(&self) {
+        let imp = self.imp();
+
+        let mut min_date = imp.min_date_calendar.date();
+        let mut max_date = imp.max_date_calendar.date();
+        if min_date > max_date {
+            std::mem::swap(&mut min_date, &mut max_date);
+        }
+
+        // Include the whole days
+        let min_date = glib::DateTime::from_local(
+            min_date.year(),
+            min_date.month(),
+            min_date.day_of_month(),
+            0,
+            0,
+            0.0,
+        )
+        .unwrap();
+        let max_date = glib::DateTime::from_local(
+            max_date.year(),
+            max_date.month(),
+            max_date.day_of_month(),
+            23,
+            59,
+            59.0,
+        )
+        .unwrap();
+
+        imp.min_date.set(min_date.to_unix() as i32);
+        imp.max_date.set(max_date.to_unix() as i32);
+
+        imp.date_button.set_label(&gettext_f(
+            "{from} – {until}",
+            &[
+                ("from", &min_date.format("%x").unwrap()),
+                ("until", &max_date.format("%x").unwrap()),
+            ],
+        ));
+        imp.date_popover.popdown();
+
+        self.reload_messages();
+    }
+
+    fn reset_date_range(&self) {
+        let imp = self.imp();
+
+        imp.min_date.set(0);
+        imp.max_date.set(0);
+
+        imp.date_button.set_label(&gettext("Any Date"));
+        imp.date_popover.popdown();
+
+        self.reload_messages();
+    }
 }
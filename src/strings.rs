@@ -2,10 +2,12 @@ use ellipse::Ellipse;
 use gettextrs::gettext;
 use gtk::glib;
 use tdlib::enums::CallDiscardReason;
+use tdlib::enums::PollType;
 use tdlib::enums::UserStatus;
 use tdlib::enums::UserType;
 use tdlib::types::MessageGame;
 use tdlib::types::MessageGameScore;
+use tdlib::types::Poll;
 
 use crate::i18n::gettext_f;
 use crate::i18n::ngettext_f;
@@ -469,6 +471,7 @@ pub(crate) fn message_content(message: &Message) -> String {
         MessageExpiredVideo => gettext("Video has expired"),
         MessageVideoNote(_) => gettext("Video Message"),
         MessageVoiceNote(data) => message_voice_note(&data.caption.text),
+        MessagePoll(data) => message_poll(&data.poll),
         MessageAnimatedEmoji(data) => data.emoji,
         MessageDice(data) => data.emoji,
         MessageCall(data) => message_call(
@@ -557,6 +560,14 @@ fn message_photo(caption: &str) -> String {
     }
 }
 
+fn message_poll(poll: &Poll) -> String {
+    if let PollType::Quiz(_) = poll.r#type {
+        gettext_f("Quiz, {question}", &[("question", &poll.question)])
+    } else {
+        gettext_f("Poll, {question}", &[("question", &poll.question)])
+    }
+}
+
 fn message_sticker(emoji: &str) -> String {
     // Translators: This is a sticker with the associated emoji
     gettext_f("{emoji} Sticker", &[("emoji", emoji)])
@@ -807,8 +818,6 @@ fn message_game_score(game: &MessageGameScore, chat: &Chat, sender: &MessageSend
 fn message_pin_message(message_id: i64, chat: &Chat, sender: &MessageSender) -> String {
     use tdlib::enums::MessageContent::*;
 
-    // TODO: Add a way to retrieve the message and update the string
-    // in case we don't have the message stored locally.
     let string = match chat.message(message_id) {
         Some(message) => match message.content().0 {
             MessageText(data) => {
@@ -9,6 +9,7 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
+use tdlib::enums::ChatList as TdChatList;
 use tdlib::enums::ChatType as TdChatType;
 use tdlib::enums::Update;
 use tdlib::functions;
@@ -22,11 +23,23 @@ use crate::tdlib::BoxedChatPermissions;
 use crate::tdlib::BoxedDraftMessage;
 use crate::tdlib::ChatActionList;
 use crate::tdlib::Message;
+use crate::tdlib::MessageSender;
 use crate::tdlib::SecretChat;
 use crate::tdlib::Supergroup;
 use crate::tdlib::User;
 use crate::Session;
 
+/// A page of the messages found by a search in a chat.
+#[derive(Debug, Default)]
+pub(crate) struct FoundChatMessages {
+    /// The found messages, from the newest to the oldest
+    pub(crate) messages: Vec<Message>,
+    /// The approximate number of messages found in total
+    pub(crate) total_count: i32,
+    /// The message to search the next page from, or 0 if there are no more results
+    pub(crate) next_from_message_id: i64,
+}
+
 #[derive(Clone, Debug, glib::Boxed)]
 #[boxed_type(name = "ChatType")]
 pub(crate) enum ChatType {
@@ -96,7 +109,9 @@ mod imp {
         pub(super) last_message: RefCell<Option<Message>>,
         pub(super) unread_mention_count: Cell<i32>,
         pub(super) unread_count: Cell<i32>,
+        pub(super) online_member_count: Cell<i32>,
         pub(super) draft_message: RefCell<Option<BoxedDraftMessage>>,
+        pub(super) reply_markup_message_id: Cell<i64>,
         pub(super) notification_settings: RefCell<Option<BoxedChatNotificationSettings>>,
         pub(super) actions: OnceCell<ChatActionList>,
         pub(super) session: WeakRef<Session>,
@@ -120,6 +135,7 @@ mod imp {
                     Signal::builder("deleted-message")
                         .param_types([Message::static_type()])
                         .build(),
+                    Signal::builder("pinned-messages-changed").build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -154,9 +170,15 @@ mod imp {
                     glib::ParamSpecInt::builder("unread-count")
                         .read_only()
                         .build(),
+                    glib::ParamSpecInt::builder("online-member-count")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoxed::builder::<BoxedDraftMessage>("draft-message")
                         .read_only()
                         .build(),
+                    glib::ParamSpecInt64::builder("reply-markup-message-id")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoxed::builder::<BoxedChatNotificationSettings>(
                         "notification-settings",
                     )
@@ -190,7 +212,9 @@ mod imp {
                 "last-message" => obj.last_message().to_value(),
                 "unread-mention-count" => obj.unread_mention_count().to_value(),
                 "unread-count" => obj.unread_count().to_value(),
+                "online-member-count" => obj.online_member_count().to_value(),
                 "draft-message" => obj.draft_message().to_value(),
+                "reply-markup-message-id" => obj.reply_markup_message_id().to_value(),
                 "notification-settings" => obj.notification_settings().to_value(),
                 "actions" => obj.actions().to_value(),
                 "permissions" => obj.permissions().to_value(),
@@ -229,6 +253,8 @@ impl Chat {
         imp.unread_mention_count.set(td_chat.unread_mention_count);
         imp.unread_count.set(td_chat.unread_count);
         imp.draft_message.replace(draft_message);
+        imp.reply_markup_message_id
+            .set(td_chat.reply_markup_message_id);
         imp.notification_settings
             .replace(Some(notification_settings));
         imp.session.set(Some(session));
@@ -265,6 +291,12 @@ impl Chat {
                 self.set_permissions(BoxedChatPermissions(update.permissions))
             }
             ChatPhoto(update) => self.set_avatar(update.photo.map(Into::into)),
+            ChatReplyMarkup(update) => {
+                self.set_reply_markup_message_id(update.reply_markup_message_id)
+            }
+            ChatOnlineMemberCount(update) => {
+                self.set_online_member_count(update.online_member_count)
+            }
             ChatReadInbox(update) => self.set_unread_count(update.unread_count),
             ChatReadOutbox(update) => {
                 self.set_last_read_outbox_message_id(update.last_read_outbox_message_id);
@@ -295,6 +327,11 @@ impl Chat {
                     message.handle_update(update);
                 }
             }
+            MessageContentOpened(ref data) => {
+                if let Some(message) = self.message(data.message_id) {
+                    message.handle_update(update);
+                }
+            }
             MessageEdited(ref data) => {
                 if let Some(message) = self.message(data.message_id) {
                     message.handle_update(update);
@@ -305,6 +342,13 @@ impl Chat {
                     message.handle_update(update);
                 }
             }
+            MessageIsPinned(ref data) => {
+                if let Some(message) = self.message(data.message_id) {
+                    message.handle_update(update);
+                }
+
+                self.emit_by_name::<()>("pinned-messages-changed", &[]);
+            }
             MessageSendSucceeded(data) => {
                 let mut messages = imp.messages.borrow_mut();
                 let old_message = messages.remove(&data.old_message_id);
@@ -427,6 +471,20 @@ impl Chat {
         self.notify("unread-mention-count");
     }
 
+    /// The number of online members of the chat. It's only updated while the chat is opened
+    /// and it's 0 if it's unknown.
+    pub(crate) fn online_member_count(&self) -> i32 {
+        self.imp().online_member_count.get()
+    }
+
+    fn set_online_member_count(&self, online_member_count: i32) {
+        if self.online_member_count() == online_member_count {
+            return;
+        }
+        self.imp().online_member_count.set(online_member_count);
+        self.notify("online-member-count");
+    }
+
     pub(crate) fn unread_count(&self) -> i32 {
         self.imp().unread_count.get()
     }
@@ -451,6 +509,22 @@ impl Chat {
         self.notify("draft-message");
     }
 
+    /// Returns the id of the message whose reply markup should be used as the custom
+    /// keyboard of this chat, or 0 if there is none.
+    pub(crate) fn reply_markup_message_id(&self) -> i64 {
+        self.imp().reply_markup_message_id.get()
+    }
+
+    fn set_reply_markup_message_id(&self, reply_markup_message_id: i64) {
+        if self.reply_markup_message_id() == reply_markup_message_id {
+            return;
+        }
+        self.imp()
+            .reply_markup_message_id
+            .set(reply_markup_message_id);
+        self.notify("reply-markup-message-id");
+    }
+
     pub(crate) fn notification_settings(&self) -> BoxedChatNotificationSettings {
         self.imp()
             .notification_settings
@@ -520,6 +594,261 @@ impl Chat {
         })
     }
 
+    pub(crate) fn connect_pinned_messages_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("pinned-messages-changed", true, move |values| {
+            let obj = values[0].get().unwrap();
+            f(obj);
+            None
+        })
+    }
+
+    /// Returns whether we are allowed to pin and unpin messages in this chat.
+    pub(crate) fn can_pin_messages(&self) -> bool {
+        match self.type_() {
+            ChatType::Private(_) => true,
+            ChatType::Secret(_) => false,
+            _ => self.has_permission(
+                |permissions| permissions.can_pin_messages,
+                |rights, is_channel| {
+                    if is_channel {
+                        rights.can_edit_messages
+                    } else {
+                        rights.can_pin_messages
+                    }
+                },
+            ),
+        }
+    }
+
+    /// Returns whether we are allowed to send messages to this chat.
+    pub(crate) fn can_send_messages(&self) -> bool {
+        match self.type_() {
+            ChatType::Private(_) | ChatType::Secret(_) => !self.is_blocked(),
+            _ => self.has_permission(
+                |permissions| permissions.can_send_basic_messages,
+                can_post_as_administrator,
+            ),
+        }
+    }
+
+    /// Returns whether we are allowed to send polls to this chat.
+    pub(crate) fn can_send_polls(&self) -> bool {
+        match self.type_() {
+            // Polls can only be sent to groups and channels
+            ChatType::Private(_) | ChatType::Secret(_) => false,
+            _ => self.has_permission(
+                |permissions| permissions.can_send_polls,
+                can_post_as_administrator,
+            ),
+        }
+    }
+
+    /// Returns whether we are allowed to send stickers, animations and inline bot results
+    /// to this chat.
+    pub(crate) fn can_send_other_messages(&self) -> bool {
+        match self.type_() {
+            ChatType::Private(_) | ChatType::Secret(_) => !self.is_blocked(),
+            _ => self.has_permission(
+                |permissions| permissions.can_send_other_messages,
+                can_post_as_administrator,
+            ),
+        }
+    }
+
+    /// Returns whether we have a permission in this group or channel. `permission` selects
+    /// it from the permissions of members, which must be granted both by the chat and by
+    /// our restrictions, while `right` selects the right that administrators need for it,
+    /// given whether the chat is a channel. Members of channels have no permissions.
+    fn has_permission<P, R>(&self, permission: P, right: R) -> bool
+    where
+        P: Fn(&types::ChatPermissions) -> bool,
+        R: Fn(&types::ChatAdministratorRights, bool) -> bool,
+    {
+        use tdlib::enums::ChatMemberStatus::*;
+
+        let (status, is_channel) = match self.type_() {
+            ChatType::BasicGroup(basic_group) => (basic_group.status().0, false),
+            ChatType::Supergroup(supergroup) => (supergroup.status().0, supergroup.is_channel()),
+            _ => return false,
+        };
+
+        match status {
+            Creator(_) => true,
+            Administrator(data) => right(&data.rights, is_channel),
+            Member if !is_channel => permission(&self.permissions().0),
+            Restricted(data) if !is_channel => {
+                permission(&self.permissions().0) && permission(&data.permissions)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether we are allowed to ban, kick and restrict members of this chat.
+    pub(crate) fn can_restrict_members(&self) -> bool {
+        self.has_administrator_right(|rights| rights.can_restrict_members)
+    }
+
+    /// Returns whether we are allowed to add new administrators to this chat.
+    pub(crate) fn can_promote_members(&self) -> bool {
+        self.has_administrator_right(|rights| rights.can_promote_members)
+    }
+
+    /// Returns whether we are allowed to delete the messages of other members of this chat.
+    pub(crate) fn can_delete_messages(&self) -> bool {
+        self.has_administrator_right(|rights| rights.can_delete_messages)
+    }
+
+    /// Returns whether we are the creator of this chat.
+    pub(crate) fn is_creator(&self) -> bool {
+        let status = match self.type_() {
+            ChatType::BasicGroup(basic_group) => basic_group.status().0,
+            ChatType::Supergroup(supergroup) => supergroup.status().0,
+            _ => return false,
+        };
+
+        matches!(status, tdlib::enums::ChatMemberStatus::Creator(_))
+    }
+
+    /// Returns whether we have the specified administrator right in this chat. The creator
+    /// has all of them.
+    pub(crate) fn has_administrator_right<F: Fn(&types::ChatAdministratorRights) -> bool>(
+        &self,
+        f: F,
+    ) -> bool {
+        use tdlib::enums::ChatMemberStatus::*;
+
+        let status = match self.type_() {
+            ChatType::BasicGroup(basic_group) => basic_group.status().0,
+            ChatType::Supergroup(supergroup) => supergroup.status().0,
+            _ => return false,
+        };
+
+        match status {
+            Creator(_) => true,
+            Administrator(data) => f(&data.rights),
+            _ => false,
+        }
+    }
+
+    /// Returns the membership information of an user in this chat.
+    pub(crate) async fn member(&self, user: &User) -> Result<types::ChatMember, types::Error> {
+        let result = functions::get_chat_member(
+            self.id(),
+            tdlib::enums::MessageSender::User(types::MessageSenderUser { user_id: user.id() }),
+            self.session().client_id(),
+        )
+        .await;
+
+        result.map(|data| {
+            let tdlib::enums::ChatMember::ChatMember(member) = data;
+            member
+        })
+    }
+
+    /// Changes the status of an user in this chat. This can be used to ban, restrict and
+    /// promote members.
+    pub(crate) async fn set_member_status(
+        &self,
+        user: &User,
+        status: tdlib::enums::ChatMemberStatus,
+    ) -> Result<(), types::Error> {
+        functions::set_chat_member_status(
+            self.id(),
+            tdlib::enums::MessageSender::User(types::MessageSenderUser { user_id: user.id() }),
+            status,
+            self.session().client_id(),
+        )
+        .await
+    }
+
+    /// Deletes all the messages sent by an user in this chat.
+    pub(crate) async fn delete_messages_from_user(&self, user: &User) -> Result<(), types::Error> {
+        functions::delete_chat_messages_by_sender(
+            self.id(),
+            tdlib::enums::MessageSender::User(types::MessageSenderUser { user_id: user.id() }),
+            self.session().client_id(),
+        )
+        .await
+    }
+
+    /// Returns the commands of the bots of this chat, which is the bot itself for private
+    /// chats with a bot. The commands are taken from the full info of the chat, as
+    /// `get_commands` is only available to bots.
+    ///
+    /// This means that the scopes of the commands can't be requested explicitly, so the
+    /// commands that a bot has set only for a specific chat, for its administrators or for
+    /// a language are only shown if Telegram includes them in the full info.
+    pub(crate) async fn bot_commands(&self) -> Result<Vec<types::BotCommands>, types::Error> {
+        let client_id = self.session().client_id();
+
+        match self.type_() {
+            ChatType::Private(user) => {
+                if !matches!(user.type_().0, tdlib::enums::UserType::Bot(_)) {
+                    return Ok(vec![]);
+                }
+
+                let tdlib::enums::UserFullInfo::UserFullInfo(full_info) =
+                    functions::get_user_full_info(user.id(), client_id).await?;
+                Ok(full_info
+                    .bot_info
+                    .map(|bot_info| types::BotCommands {
+                        bot_user_id: user.id(),
+                        commands: bot_info.commands,
+                    })
+                    .into_iter()
+                    .collect())
+            }
+            ChatType::BasicGroup(basic_group) => {
+                let tdlib::enums::BasicGroupFullInfo::BasicGroupFullInfo(full_info) =
+                    functions::get_basic_group_full_info(basic_group.id(), client_id).await?;
+                Ok(full_info.bot_commands)
+            }
+            ChatType::Supergroup(supergroup) => {
+                let tdlib::enums::SupergroupFullInfo::SupergroupFullInfo(full_info) =
+                    functions::get_supergroup_full_info(supergroup.id(), client_id).await?;
+                Ok(full_info.bot_commands)
+            }
+            ChatType::Secret(_) => Ok(vec![]),
+        }
+    }
+
+    /// Deletes the messages of the specified ids, also for the other members of the chat if
+    /// `revoke` is set.
+    pub(crate) async fn delete_messages(
+        &self,
+        message_ids: Vec<i64>,
+        revoke: bool,
+    ) -> Result<(), types::Error> {
+        functions::delete_messages(self.id(), message_ids, revoke, self.session().client_id()).await
+    }
+
+    /// Forwards messages of another chat to this chat. Setting `send_copy` hides the name of
+    /// the original sender, while `remove_caption` also removes the captions of media messages.
+    pub(crate) async fn forward_messages(
+        &self,
+        from_chat: &Chat,
+        message_ids: Vec<i64>,
+        send_copy: bool,
+        remove_caption: bool,
+    ) -> Result<(), types::Error> {
+        functions::forward_messages(
+            self.id(),
+            0,
+            from_chat.id(),
+            message_ids,
+            None,
+            send_copy,
+            remove_caption,
+            false,
+            self.session().client_id(),
+        )
+        .await
+        .map(|_| ())
+    }
+
     /// Returns the `Message` of the specified id, if present in the cache.
     pub(crate) fn message(&self, message_id: i64) -> Option<Message> {
         self.imp().messages.borrow().get(&message_id).cloned()
@@ -550,12 +879,19 @@ impl Chat {
     pub(crate) async fn get_chat_history(
         &self,
         from_message_id: i64,
+        offset: i32,
         limit: i32,
     ) -> Result<Vec<Message>, types::Error> {
         let client_id = self.session().client_id();
-        let result =
-            functions::get_chat_history(self.id(), from_message_id, 0, limit, false, client_id)
-                .await;
+        let result = functions::get_chat_history(
+            self.id(),
+            from_message_id,
+            offset,
+            limit,
+            false,
+            client_id,
+        )
+        .await;
 
         let tdlib::enums::Messages::Messages(data) = result?;
 
@@ -574,6 +910,142 @@ impl Chat {
         Ok(loaded_messages)
     }
 
+    /// Returns all the pinned messages of this chat, from the newest to the oldest.
+    pub(crate) async fn pinned_messages(&self) -> Result<Vec<Message>, types::Error> {
+        let mut pinned_messages = vec![];
+        let mut from_message_id = 0;
+
+        // The search is paginated, so keep searching until there are no more results
+        loop {
+            let found = self
+                .search_messages_page(
+                    String::new(),
+                    None,
+                    from_message_id,
+                    Some(tdlib::enums::SearchMessagesFilter::Pinned),
+                    100,
+                )
+                .await?;
+
+            pinned_messages.extend(found.messages);
+
+            if found.next_from_message_id == 0 || found.next_from_message_id == from_message_id {
+                break;
+            }
+            from_message_id = found.next_from_message_id;
+        }
+
+        Ok(pinned_messages)
+    }
+
+    /// Searches for messages in this chat, optionally sent by the specified sender,
+    /// from the newest to the oldest. The search starts from `from_message_id` (exclusive),
+    /// or from the last message if it's 0.
+    pub(crate) async fn search_messages(
+        &self,
+        query: String,
+        sender: Option<&MessageSender>,
+        from_message_id: i64,
+        filter: Option<tdlib::enums::SearchMessagesFilter>,
+        limit: i32,
+    ) -> Result<Vec<Message>, types::Error> {
+        self.search_messages_page(query, sender, from_message_id, filter, limit)
+            .await
+            .map(|found| found.messages)
+    }
+
+    /// Like `search_messages()`, but also returns what is needed to load the next pages.
+    pub(crate) async fn search_messages_page(
+        &self,
+        query: String,
+        sender: Option<&MessageSender>,
+        from_message_id: i64,
+        filter: Option<tdlib::enums::SearchMessagesFilter>,
+        limit: i32,
+    ) -> Result<FoundChatMessages, types::Error> {
+        let client_id = self.session().client_id();
+        let result = functions::search_chat_messages(
+            self.id(),
+            query,
+            sender.map(MessageSender::to_td_object),
+            from_message_id,
+            0,
+            limit,
+            filter,
+            0,
+            client_id,
+        )
+        .await;
+
+        let tdlib::enums::FoundChatMessages::FoundChatMessages(data) = result?;
+
+        Ok(FoundChatMessages {
+            messages: data
+                .messages
+                .into_iter()
+                .map(|m| self.cache_message(m))
+                .collect(),
+            total_count: data.total_count,
+            next_from_message_id: data.next_from_message_id,
+        })
+    }
+
+    /// Searches for messages in this chat newer than `from_message_id` (exclusive), from the
+    /// newest to the oldest.
+    pub(crate) async fn search_newer_messages(
+        &self,
+        from_message_id: i64,
+        filter: Option<tdlib::enums::SearchMessagesFilter>,
+        limit: i32,
+    ) -> Result<Vec<Message>, types::Error> {
+        let client_id = self.session().client_id();
+
+        // A negative offset returns the newer messages, in addition to the one we're
+        // searching from
+        let result = functions::search_chat_messages(
+            self.id(),
+            String::new(),
+            None,
+            from_message_id,
+            -limit,
+            limit + 1,
+            filter,
+            0,
+            client_id,
+        )
+        .await;
+
+        let tdlib::enums::FoundChatMessages::FoundChatMessages(data) = result?;
+
+        Ok(data
+            .messages
+            .into_iter()
+            .filter(|m| m.id > from_message_id)
+            .map(|m| self.cache_message(m))
+            .collect())
+    }
+
+    /// Returns the `Message` for the specified message of this chat, reusing the cached one
+    /// if present.
+    pub(crate) fn cache_message(&self, message: types::Message) -> Message {
+        self.imp()
+            .messages
+            .borrow_mut()
+            .entry(message.id)
+            .or_insert_with(|| Message::new(message, self))
+            .clone()
+    }
+
+    /// Informs TDLib that the chat is being viewed, so that it sends additional updates
+    /// about it, like the number of online members.
+    pub(crate) async fn open(&self) -> Result<(), types::Error> {
+        functions::open_chat(self.id(), self.session().client_id()).await
+    }
+
+    pub(crate) async fn close(&self) -> Result<(), types::Error> {
+        functions::close_chat(self.id(), self.session().client_id()).await
+    }
+
     pub(crate) async fn mark_as_read(&self) -> Result<(), types::Error> {
         if let Some(message) = self.last_message() {
             functions::view_messages(
@@ -594,4 +1066,21 @@ impl Chat {
         functions::toggle_chat_is_marked_as_unread(self.id(), true, self.session().client_id())
             .await
     }
+
+    /// Moves the chat to the archive or back to the main chat list.
+    pub(crate) async fn set_is_archived(&self, is_archived: bool) -> Result<(), types::Error> {
+        let chat_list = if is_archived {
+            TdChatList::Archive
+        } else {
+            TdChatList::Main
+        };
+
+        functions::add_chat_to_list(self.id(), chat_list, self.session().client_id()).await
+    }
+}
+
+/// Administrators can send anything to groups, but only the ones that can post messages
+/// can send to channels.
+fn can_post_as_administrator(rights: &types::ChatAdministratorRights, is_channel: bool) -> bool {
+    !is_channel || rights.can_post_messages
 }
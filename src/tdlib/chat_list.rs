@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::cell::OnceCell;
 use std::cell::RefCell;
 use std::cell::RefMut;
 use std::collections::BTreeMap;
@@ -9,6 +10,7 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
+use tdlib::enums::ChatList as TdChatList;
 use tdlib::functions;
 use tdlib::types::ChatPosition as TdChatPosition;
 
@@ -21,6 +23,8 @@ mod imp {
 
     #[derive(Debug, Default)]
     pub(crate) struct ChatList {
+        pub(super) list_type: OnceCell<TdChatList>,
+        pub(super) title: RefCell<String>,
         // order -> item
         pub(super) list: RefCell<BTreeMap<i64, ChatListItem>>,
         pub(super) unread_count: Cell<i32>,
@@ -36,9 +40,12 @@ mod imp {
     impl ObjectImpl for ChatList {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecInt::builder("unread-count")
-                    .read_only()
-                    .build()]
+                vec![
+                    glib::ParamSpecString::builder("title").read_only().build(),
+                    glib::ParamSpecInt::builder("unread-count")
+                        .read_only()
+                        .build(),
+                ]
             });
             PROPERTIES.as_ref()
         }
@@ -47,6 +54,7 @@ mod imp {
             let obj = self.obj();
 
             match pspec.name() {
+                "title" => obj.title().to_value(),
                 "unread-count" => obj.unread_count().to_value(),
                 _ => unimplemented!(),
             }
@@ -79,13 +87,16 @@ glib::wrapper! {
 }
 
 impl ChatList {
-    pub(crate) fn new() -> Self {
-        glib::Object::new()
+    pub(crate) fn new(list_type: TdChatList) -> Self {
+        let obj: Self = glib::Object::new();
+        obj.imp().list_type.set(list_type).unwrap();
+        obj
     }
 
     pub(crate) fn fetch(&self, client_id: i32) {
+        let list_type = self.list_type().clone();
         spawn(clone!(@weak self as obj => async move {
-            let result = functions::load_chats(None, 20, client_id).await;
+            let result = functions::load_chats(Some(list_type), 20, client_id).await;
 
             if let Err(err) = result {
                 // Error 404 means that all chats have been loaded
@@ -98,6 +109,22 @@ impl ChatList {
         }));
     }
 
+    pub(crate) fn list_type(&self) -> &TdChatList {
+        self.imp().list_type.get().unwrap()
+    }
+
+    pub(crate) fn title(&self) -> String {
+        self.imp().title.borrow().clone()
+    }
+
+    pub(crate) fn set_title(&self, title: String) {
+        if self.title() == title {
+            return;
+        }
+        self.imp().title.replace(title);
+        self.notify("title");
+    }
+
     pub(crate) fn find_chat_item(&self, chat_id: i64) -> Option<ChatListItem> {
         self.imp()
             .list
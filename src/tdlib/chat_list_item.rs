@@ -80,6 +80,10 @@ impl ChatListItem {
         self.imp().chat.upgrade().unwrap()
     }
 
+    pub(crate) fn chat_list_type(&self) -> &TdChatList {
+        self.imp().chat_list_type.get().unwrap()
+    }
+
     pub(crate) fn is_pinned(&self) -> bool {
         self.imp().is_pinned.get()
     }
@@ -95,7 +99,7 @@ impl ChatListItem {
     pub(crate) async fn toggle_is_pinned(&self) -> Result<(), TdError> {
         let chat = self.chat();
         functions::toggle_chat_is_pinned(
-            self.imp().chat_list_type.get().unwrap().clone(),
+            self.chat_list_type().clone(),
             chat.id(),
             !self.is_pinned(),
             chat.session().client_id(),
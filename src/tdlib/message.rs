@@ -7,15 +7,19 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use once_cell::sync::Lazy;
+use tdlib::enums;
 use tdlib::enums::MessageSender as TdMessageSender;
+use tdlib::enums::ReactionType;
 use tdlib::enums::Update;
 use tdlib::functions;
 use tdlib::types::Error as TdError;
 use tdlib::types::Message as TdMessage;
+use tdlib::types::ReactionTypeEmoji;
 
 use crate::expressions;
 use crate::tdlib::BoxedMessageContent;
 use crate::tdlib::BoxedMessageSendingState;
+use crate::tdlib::BoxedReplyMarkup;
 use crate::tdlib::Chat;
 use crate::tdlib::MessageForwardInfo;
 use crate::tdlib::MessageForwardOrigin;
@@ -44,6 +48,17 @@ impl MessageSender {
         }
     }
 
+    pub(crate) fn to_td_object(&self) -> TdMessageSender {
+        match self {
+            MessageSender::User(user) => {
+                TdMessageSender::User(tdlib::types::MessageSenderUser { user_id: user.id() })
+            }
+            MessageSender::Chat(chat) => {
+                TdMessageSender::Chat(tdlib::types::MessageSenderChat { chat_id: chat.id() })
+            }
+        }
+    }
+
     pub(crate) fn as_user(&self) -> Option<&User> {
         match self {
             MessageSender::User(user) => Some(user),
@@ -68,17 +83,21 @@ mod imp {
         pub(super) sender: OnceCell<MessageSender>,
         pub(super) is_outgoing: Cell<bool>,
         pub(super) can_be_edited: Cell<bool>,
+        pub(super) can_be_forwarded: Cell<bool>,
         pub(super) can_be_deleted_only_for_self: Cell<bool>,
         pub(super) can_be_deleted_for_all_users: Cell<bool>,
         pub(super) sending_state: RefCell<Option<BoxedMessageSendingState>>,
         pub(super) date: Cell<i32>,
         pub(super) content: RefCell<Option<BoxedMessageContent>>,
         pub(super) is_edited: Cell<bool>,
+        pub(super) is_pinned: Cell<bool>,
         pub(super) interaction_info: OnceCell<MessageInteractionInfo>,
         pub(super) chat: WeakRef<Chat>,
         pub(super) forward_info: OnceCell<Option<MessageForwardInfo>>,
         pub(super) reply_in_chat_id: Cell<i64>,
         pub(super) reply_to_message_id: Cell<i64>,
+        pub(super) reply_markup: RefCell<Option<BoxedReplyMarkup>>,
+        pub(super) via_bot_user_id: Cell<i64>,
     }
 
     #[glib::object_subclass]
@@ -101,6 +120,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("can-be-edited")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("can-be-forwarded")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecBoolean::builder("can-be-deleted-only-for-self")
                         .read_only()
                         .build(),
@@ -117,6 +139,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("is-edited")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("is-pinned")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecObject::builder::<MessageInteractionInfo>("interaction-info")
                         .read_only()
                         .build(),
@@ -132,6 +157,9 @@ mod imp {
                     glib::ParamSpecInt64::builder("reply-to-message-id")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoxed::builder::<BoxedReplyMarkup>("reply-markup")
+                        .read_only()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -145,17 +173,20 @@ mod imp {
                 "sender" => obj.sender().to_value(),
                 "is-outgoing" => obj.is_outgoing().to_value(),
                 "can-be-edited" => obj.can_be_edited().to_value(),
+                "can-be-forwarded" => obj.can_be_forwarded().to_value(),
                 "can-be-deleted-only-for-self" => obj.can_be_deleted_only_for_self().to_value(),
                 "can-be-deleted-for-all-users" => obj.can_be_deleted_for_all_users().to_value(),
                 "sending-state" => obj.sending_state().to_value(),
                 "date" => obj.date().to_value(),
                 "content" => obj.content().to_value(),
                 "is-edited" => obj.is_edited().to_value(),
+                "is-pinned" => obj.is_pinned().to_value(),
                 "interaction-info" => obj.interaction_info().to_value(),
                 "chat" => obj.chat().to_value(),
                 "forward-info" => obj.forward_info().to_value(),
                 "reply-in-chat-id" => obj.reply_in_chat_id().to_value(),
                 "reply-to-message-id" => obj.reply_to_message_id().to_value(),
+                "reply-markup" => obj.reply_markup().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -183,6 +214,7 @@ impl Message {
         imp.sender.set(sender).unwrap();
         imp.is_outgoing.set(td_message.is_outgoing);
         imp.can_be_edited.set(td_message.can_be_edited);
+        imp.can_be_forwarded.set(td_message.can_be_forwarded);
         imp.can_be_deleted_only_for_self
             .set(td_message.can_be_deleted_only_for_self);
         imp.can_be_deleted_for_all_users
@@ -191,6 +223,7 @@ impl Message {
         imp.date.set(td_message.date);
         imp.content.replace(Some(content));
         imp.is_edited.set(is_edited);
+        imp.is_pinned.set(td_message.is_pinned);
         imp.interaction_info
             .set(MessageInteractionInfo::from(td_message.interaction_info))
             .unwrap();
@@ -198,6 +231,9 @@ impl Message {
         imp.forward_info.set(forward_info).unwrap();
         imp.reply_in_chat_id.set(td_message.reply_in_chat_id);
         imp.reply_to_message_id.set(td_message.reply_to_message_id);
+        imp.reply_markup
+            .replace(td_message.reply_markup.map(BoxedReplyMarkup));
+        imp.via_bot_user_id.set(td_message.via_bot_user_id);
 
         message
     }
@@ -208,7 +244,22 @@ impl Message {
                 let new_content = BoxedMessageContent(data.new_content);
                 self.set_content(new_content);
             }
-            Update::MessageEdited(data) => self.set_is_edited(data.edit_date > 0),
+            Update::MessageContentOpened(_) => {
+                let mut content = self.content().0;
+                match content {
+                    enums::MessageContent::MessageVoiceNote(ref mut data) => {
+                        data.is_listened = true
+                    }
+                    enums::MessageContent::MessageVideoNote(ref mut data) => data.is_viewed = true,
+                    _ => return,
+                }
+                self.set_content(BoxedMessageContent(content));
+            }
+            Update::MessageEdited(data) => {
+                self.set_is_edited(data.edit_date > 0);
+                self.set_reply_markup(data.reply_markup.map(BoxedReplyMarkup));
+            }
+            Update::MessageIsPinned(data) => self.set_is_pinned(data.is_pinned),
             Update::MessageInteractionInfo(data) => {
                 self.interaction_info().update(data.interaction_info)
             }
@@ -226,6 +277,100 @@ impl Message {
         .await
     }
 
+    pub(crate) async fn pin(&self) -> Result<(), TdError> {
+        let chat = self.chat();
+        functions::pin_chat_message(
+            chat.id(),
+            self.id(),
+            false,
+            false,
+            chat.session().client_id(),
+        )
+        .await
+    }
+
+    pub(crate) async fn unpin(&self) -> Result<(), TdError> {
+        let chat = self.chat();
+        functions::unpin_chat_message(chat.id(), self.id(), chat.session().client_id()).await
+    }
+
+    /// Informs TDLib that the content of the message has been opened, which marks voice
+    /// notes as listened and video notes as viewed.
+    pub(crate) async fn open_content(&self) -> Result<(), TdError> {
+        let chat = self.chat();
+        functions::open_message_content(chat.id(), self.id(), chat.session().client_id()).await
+    }
+
+    /// Returns all the emoji reactions that can be added to the message, starting from
+    /// the top ones.
+    pub(crate) async fn available_reactions(&self) -> Result<Vec<String>, TdError> {
+        let chat = self.chat();
+        let result = functions::get_message_available_reactions(
+            chat.id(),
+            self.id(),
+            8,
+            chat.session().client_id(),
+        )
+        .await;
+
+        result.map(|data| {
+            let enums::AvailableReactions::AvailableReactions(data) = data;
+            let mut emojis: Vec<String> = Vec::new();
+            for reaction in data
+                .top_reactions
+                .into_iter()
+                .chain(data.recent_reactions)
+                .chain(data.popular_reactions)
+                .filter(|reaction| !reaction.needs_premium)
+            {
+                match reaction.r#type {
+                    ReactionType::Emoji(data) => {
+                        if !emojis.contains(&data.emoji) {
+                            emojis.push(data.emoji);
+                        }
+                    }
+                    // Custom emoji reactions can't be rendered yet
+                    ReactionType::CustomEmoji(_) => {}
+                }
+            }
+            emojis
+        })
+    }
+
+    /// Votes for the options of the specified ids in a poll message. Passing no options
+    /// retracts the vote.
+    pub(crate) async fn set_poll_answer(&self, option_ids: Vec<i32>) -> Result<(), TdError> {
+        let chat = self.chat();
+        functions::set_poll_answer(chat.id(), self.id(), option_ids, chat.session().client_id())
+            .await
+    }
+
+    /// Adds our reaction to the message or removes it, if we have already chosen it.
+    pub(crate) async fn toggle_reaction(&self, emoji: String) -> Result<(), TdError> {
+        let chat = self.chat();
+        let client_id = chat.session().client_id();
+
+        let is_chosen = self.interaction_info().reactions().iter().any(|reaction| {
+            reaction.is_chosen
+                && matches!(&reaction.r#type, ReactionType::Emoji(data) if data.emoji == emoji)
+        });
+        let reaction_type = ReactionType::Emoji(ReactionTypeEmoji { emoji });
+
+        if is_chosen {
+            functions::remove_message_reaction(chat.id(), self.id(), reaction_type, client_id).await
+        } else {
+            functions::add_message_reaction(
+                chat.id(),
+                self.id(),
+                reaction_type,
+                false,
+                true,
+                client_id,
+            )
+            .await
+        }
+    }
+
     pub(crate) fn id(&self) -> i64 {
         self.imp().id.get()
     }
@@ -242,6 +387,10 @@ impl Message {
         self.imp().can_be_edited.get()
     }
 
+    pub(crate) fn can_be_forwarded(&self) -> bool {
+        self.imp().can_be_forwarded.get()
+    }
+
     pub(crate) fn can_be_deleted_only_for_self(&self) -> bool {
         self.imp().can_be_deleted_only_for_self.get()
     }
@@ -282,6 +431,36 @@ impl Message {
         self.notify("is-edited");
     }
 
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.imp().is_pinned.get()
+    }
+
+    fn set_is_pinned(&self, is_pinned: bool) {
+        if self.is_pinned() == is_pinned {
+            return;
+        }
+        self.imp().is_pinned.set(is_pinned);
+        self.notify("is-pinned");
+    }
+
+    /// Returns the id of the inline bot through which the message has been sent, or 0
+    /// if it hasn't been sent through one.
+    pub(crate) fn via_bot_user_id(&self) -> i64 {
+        self.imp().via_bot_user_id.get()
+    }
+
+    pub(crate) fn reply_markup(&self) -> Option<BoxedReplyMarkup> {
+        self.imp().reply_markup.borrow().clone()
+    }
+
+    fn set_reply_markup(&self, reply_markup: Option<BoxedReplyMarkup>) {
+        if self.reply_markup() == reply_markup {
+            return;
+        }
+        self.imp().reply_markup.replace(reply_markup);
+        self.notify("reply-markup");
+    }
+
     pub(crate) fn interaction_info(&self) -> &MessageInteractionInfo {
         self.imp().interaction_info.get().unwrap()
     }
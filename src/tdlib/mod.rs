@@ -19,18 +19,23 @@ use gtk::glib;
 use tdlib::enums::ChatMemberStatus;
 use tdlib::enums::MessageContent;
 use tdlib::enums::MessageSendingState;
+use tdlib::enums::ReplyMarkup;
 use tdlib::enums::UserStatus;
 use tdlib::enums::UserType;
+use tdlib::types::Animation;
 use tdlib::types::ChatNotificationSettings;
 use tdlib::types::ChatPermissions;
 use tdlib::types::DraftMessage;
 use tdlib::types::FormattedText;
+use tdlib::types::MessageReaction;
 use tdlib::types::ScopeNotificationSettings;
+use tdlib::types::Sticker;
 
 pub(crate) use self::avatar::Avatar;
 pub(crate) use self::basic_group::BasicGroup;
 pub(crate) use self::chat::Chat;
 pub(crate) use self::chat::ChatType;
+pub(crate) use self::chat::FoundChatMessages;
 pub(crate) use self::chat_action::ChatAction;
 pub(crate) use self::chat_action_list::ChatActionList;
 pub(crate) use self::chat_list::ChatList;
@@ -48,6 +53,10 @@ pub(crate) use self::sponsored_message::SponsoredMessage;
 pub(crate) use self::supergroup::Supergroup;
 pub(crate) use self::user::User;
 
+#[derive(Clone, Debug, PartialEq, glib::Boxed)]
+#[boxed_type(name = "BoxedAnimation")]
+pub(crate) struct BoxedAnimation(pub(crate) Animation);
+
 #[derive(Clone, Debug, PartialEq, glib::Boxed)]
 #[boxed_type(name = "BoxedChatMemberStatus")]
 pub(crate) struct BoxedChatMemberStatus(pub(crate) ChatMemberStatus);
@@ -72,10 +81,22 @@ pub(crate) struct BoxedFormattedText(pub(crate) FormattedText);
 #[boxed_type(name = "BoxedMessageContent")]
 pub(crate) struct BoxedMessageContent(pub(crate) MessageContent);
 
+#[derive(Clone, Debug, Default, PartialEq, glib::Boxed)]
+#[boxed_type(name = "BoxedMessageReactions")]
+pub(crate) struct BoxedMessageReactions(pub(crate) Vec<MessageReaction>);
+
+#[derive(Clone, Debug, PartialEq, glib::Boxed)]
+#[boxed_type(name = "BoxedReplyMarkup", nullable)]
+pub(crate) struct BoxedReplyMarkup(pub(crate) ReplyMarkup);
+
 #[derive(Clone, Debug, PartialEq, glib::Boxed)]
 #[boxed_type(name = "BoxedScopeNotificationSettings", nullable)]
 pub(crate) struct BoxedScopeNotificationSettings(pub(crate) ScopeNotificationSettings);
 
+#[derive(Clone, Debug, PartialEq, glib::Boxed)]
+#[boxed_type(name = "BoxedSticker")]
+pub(crate) struct BoxedSticker(pub(crate) Sticker);
+
 #[derive(Clone, Debug, PartialEq, glib::Boxed)]
 #[boxed_type(name = "BoxedUserStatus")]
 pub(crate) struct BoxedUserStatus(pub(crate) UserStatus);
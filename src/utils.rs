@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::future::Future;
 use std::path::PathBuf;
 
@@ -156,6 +157,75 @@ pub(crate) fn human_friendly_duration(mut seconds: i32) -> String {
     }
 }
 
+/// Formats a time of a media file, in seconds, like a media player would do.
+pub(crate) fn format_playback_time(seconds: i64) -> String {
+    let hours = seconds / (60 * 60);
+    let minutes = (seconds % (60 * 60)) / 60;
+    let seconds = seconds % 60;
+
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Returns whether the text consists of a single emoji, including the emojis composed
+/// of multiple code points, like flags or sequences joined by a zero width joiner.
+pub(crate) fn is_single_emoji(text: &str) -> bool {
+    let mut bases = 0;
+    let mut regional_indicators = 0;
+    let mut is_joined = false;
+
+    for c in text.chars() {
+        match c as u32 {
+            // Zero width joiner
+            0x200D => is_joined = true,
+            // Variation selectors, keycap, skin tones and tags
+            0xFE0E | 0xFE0F | 0x20E3 | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F => {}
+            // Regional indicators, which form flags in pairs
+            0x1F1E6..=0x1F1FF => {
+                if regional_indicators % 2 == 0 {
+                    bases += 1;
+                }
+                regional_indicators += 1;
+            }
+            0x00A9
+            | 0x00AE
+            | 0x203C
+            | 0x2049
+            | 0x2122
+            | 0x2139
+            | 0x2194..=0x21AA
+            | 0x231A..=0x23FF
+            | 0x24C2
+            | 0x25AA..=0x27BF
+            | 0x2934..=0x2935
+            | 0x2B05..=0x2B55
+            | 0x3030
+            | 0x303D
+            | 0x3297
+            | 0x3299
+            | 0x1F000..=0x1FAFF => {
+                if !is_joined {
+                    bases += 1;
+                }
+                is_joined = false;
+            }
+            // Keycap bases, which are only emojis when followed by a keycap
+            0x23 | 0x2A | 0x30..=0x39 => {
+                if !text.contains('\u{20E3}') {
+                    return false;
+                }
+                bases += 1;
+            }
+            _ => return false,
+        }
+    }
+
+    bases == 1
+}
+
 /// Returns the Paper Plane data directory (e.g. /home/bob/.local/share/paper-plane).
 pub(crate) fn data_dir() -> &'static PathBuf {
     &APPLICATION_OPTS.get().unwrap().data_dir
@@ -213,6 +283,30 @@ pub(crate) async fn log_out(client_id: i32) {
     }
 }
 
+/// Keeps track of the latest of the asynchronous requests of the same kind, so that the
+/// results of the ones that have been superseded can be discarded.
+#[derive(Debug, Default)]
+pub(crate) struct RequestGeneration(Cell<u32>);
+
+impl RequestGeneration {
+    /// Starts a new request, returning its generation.
+    pub(crate) fn next(&self) -> u32 {
+        let generation = self.0.get().wrapping_add(1);
+        self.0.set(generation);
+        generation
+    }
+
+    /// Returns the generation of the latest request.
+    pub(crate) fn current(&self) -> u32 {
+        self.0.get()
+    }
+
+    /// Returns whether the request of the specified generation is still the latest one.
+    pub(crate) fn is_current(&self, generation: u32) -> bool {
+        self.0.get() == generation
+    }
+}
+
 /// Spawn a future on the default `MainContext`
 pub(crate) fn spawn<F: Future<Output = ()> + 'static>(fut: F) {
     let ctx = glib::MainContext::default();
@@ -235,6 +329,12 @@ pub(crate) enum DecodeError {
     Unimplemented,
 }
 
+/// Decodes the tiny blurred thumbnail that TDLib sends inline with some files.
+pub(crate) fn decode_minithumbnail(minithumbnail: &types::Minithumbnail) -> Option<gdk::Texture> {
+    let bytes = glib::Bytes::from_owned(glib::base64_decode(&minithumbnail.data));
+    gdk::Texture::from_bytes(&bytes).ok()
+}
+
 pub(crate) fn decode_image_from_path(path: &str) -> Result<gdk::MemoryTexture, DecodeError> {
     use image::DynamicImage::*;
 